@@ -10,15 +10,17 @@ use pgx::cstr_core::CStr;
 use pgx::*;
 use std::str::FromStr;
 
+/// Stored as its 364 bytes, without a varlena header
 #[derive(Copy, Clone, PostgresType)]
-#[pgvarlena_inoutfuncs]
+#[inoutfuncs]
+#[pgx(fixed_size)]
 pub struct FixedF32Array {
     array: [f32; 91],
 }
 
-impl PgVarlenaInOutFuncs for FixedF32Array {
-    fn input(input: &CStr) -> PgVarlena<Self> {
-        let mut result = PgVarlena::<Self>::new();
+impl InOutFuncs for FixedF32Array {
+    fn input(input: &CStr) -> Self {
+        let mut result = FixedF32Array { array: [0.0; 91] };
 
         for (i, value) in input.to_bytes().split(|b| *b == b',').enumerate() {
             result.array[i] =
@@ -40,7 +42,7 @@ impl PgVarlenaInOutFuncs for FixedF32Array {
 
 #[pg_operator(immutable, parallel_safe)]
 #[opname(<#>)]
-fn fixedf32array_distance(left: PgVarlena<FixedF32Array>, right: PgVarlena<FixedF32Array>) -> f64 {
+fn fixedf32array_distance(left: FixedF32Array, right: FixedF32Array) -> f64 {
     left.array
         .iter()
         .zip(right.array.iter())
@@ -50,11 +52,8 @@ fn fixedf32array_distance(left: PgVarlena<FixedF32Array>, right: PgVarlena<Fixed
 
 #[pg_operator(immutable, parallel_safe)]
 #[opname(+)]
-fn fixedf32array_add(
-    left: PgVarlena<FixedF32Array>,
-    right: PgVarlena<FixedF32Array>,
-) -> PgVarlena<FixedF32Array> {
-    let mut new = PgVarlena::<FixedF32Array>::new();
+fn fixedf32array_add(left: FixedF32Array, right: FixedF32Array) -> FixedF32Array {
+    let mut new = FixedF32Array { array: [0.0; 91] };
     left.array
         .iter()
         .zip(right.array.iter())
@@ -13,6 +13,7 @@ use serde::*;
 use std::collections::HashMap;
 
 #[derive(PostgresType, Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[pgx(subscript)]
 pub struct RustStore(HashMap<String, String>);
 
 impl Default for RustStore {
@@ -21,6 +22,28 @@ impl Default for RustStore {
     }
 }
 
+/// `SELECT rstore('a', 'b')['a']` and `UPDATE t SET rstore['a'] = 'c'`, like `hstore`
+#[cfg(feature = "pg14")]
+impl Subscriptable for RustStore {
+    type Subscript = String;
+    type Element = String;
+
+    fn fetch(&self, key: String) -> Option<String> {
+        self.0.get(&key).cloned()
+    }
+
+    fn assign(&mut self, key: String, value: Option<String>) {
+        match value {
+            Some(value) => self.0.insert(key, value),
+            None => self.0.remove(&key),
+        };
+    }
+
+    fn assign_to_null() -> Self {
+        RustStore::default()
+    }
+}
+
 #[pg_extern]
 fn rstore(key: String, value: String) -> RustStore {
     RustStore(hashmap!(key => value))
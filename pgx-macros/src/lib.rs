@@ -15,7 +15,9 @@ use operators::{impl_postgres_eq, impl_postgres_hash, impl_postgres_ord};
 use pgx_utils::rewriter::*;
 use pgx_utils::{
     sql_entity_graph::{
-        ExtensionSql, ExtensionSqlFile, PgAggregate, PgExtern, PostgresEnum, PostgresType, Schema,
+        ExtensionSql, ExtensionSqlFile, PgAggregate, PgCast, PgExtern, PgOperatorClass,
+        PgTextSearchParser, PgTextSearchTemplate, PostgresEnum, PostgresType, PostgresTypeArgs,
+        PostgresTypeStorageArg, Schema,
     },
     *,
 };
@@ -169,6 +171,100 @@ pub fn merges(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/**
+Declare a function as `#[pg_cast]` to create a Postgres cast from its argument's type to its
+return type.  It's otherwise a `#[pg_extern]`, and takes the same arguments.
+
+```rust,ignore
+use pgx::*;
+
+#[pg_cast(implicit, immutable, parallel_safe)]
+fn complex_to_point(complex: Complex) -> Point {
+    todo!()
+}
+```
+
+`cargo pgx schema` will generate `CREATE CAST (Complex AS point) WITH FUNCTION complex_to_point(Complex) AS IMPLICIT`
+after the function.  The cast is applied:
+
+* `explicit` (the default): only with `CAST(x AS y)` or `x::y`
+* `assignment`: also when assigning to a column of the target type
+* `implicit`: in any context
+
+With `inout` the cast is `WITH INOUT`, through the output function of the source type and the
+input function of the target type.  With `without_function` it's `WITHOUT FUNCTION`, for types
+which are binary coercible.  The function is still created, but the cast doesn't call it.
+*/
+#[proc_macro_attribute]
+pub fn pg_cast(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let (cast, extern_attr) = match PgCast::from_attr(attr.into()) {
+        Ok(split) => split,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let args = parse_extern_attributes(extern_attr.clone());
+
+    let sql_graph_entity_item = PgExtern::new(extern_attr, item.clone().into())
+        .unwrap()
+        .with_cast(cast);
+
+    let ast = parse_macro_input!(item as syn::Item);
+    match ast {
+        Item::Fn(func) => rewrite_item_fn(func, args, &sql_graph_entity_item).into(),
+        _ => panic!("#[pg_cast] can only be applied to top-level functions"),
+    }
+}
+
+/**
+Declare a function as `#[pg_window]` to create a Postgres window function, as in
+`my_window(value) OVER (ORDER BY ...)`.  It's otherwise a `#[pg_extern]`, and takes the same arguments.
+
+```rust,ignore
+use pgx::*;
+
+#[pg_window(immutable)]
+fn row_number_by(mut window: WindowContext, step: i64) -> i64 {
+    let row = window.partition_local::<i64>();
+    *row += step;
+    *row
+}
+```
+
+The function must take a `WindowContext`, which navigates the current row's partition and frame.
+Its other arguments are evaluated for the current row, and are the function's arguments in SQL.
+*/
+#[proc_macro_attribute]
+pub fn pg_window(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_extern_attributes(proc_macro2::TokenStream::from(attr.clone()));
+
+    let sql_graph_entity_item = PgExtern::new(attr.into(), item.clone().into())
+        .unwrap()
+        .with_window();
+
+    let ast = parse_macro_input!(item as syn::Item);
+    match ast {
+        Item::Fn(func) => {
+            let has_window_context = func.sig.inputs.iter().any(|arg| match arg {
+                syn::FnArg::Typed(ty) => ty
+                    .ty
+                    .to_token_stream()
+                    .to_string()
+                    .ends_with("WindowContext"),
+                syn::FnArg::Receiver(_) => false,
+            });
+            if !has_window_context {
+                return syn::Error::new(
+                    func.sig.span(),
+                    "#[pg_window] functions must take a `WindowContext` argument",
+                )
+                .to_compile_error()
+                .into();
+            }
+            rewrite_item_fn(func, args, &sql_graph_entity_item).into()
+        }
+        _ => panic!("#[pg_window] can only be applied to top-level functions"),
+    }
+}
+
 /**
 Declare a Rust module and its contents to be in a schema.
 
@@ -412,7 +508,11 @@ Optionally accepts the following attributes:
 * `parallel_unsafe`: Corresponds to [`PARALLEL UNSAFE`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `parallel_restricted`: Corresponds to [`PARALLEL RESTRICTED`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `no_guard`: Do not use `#[pg_guard]` with the function.
+* `materialize`: Return the rows of an `impl Iterator` in [materialize mode](https://www.postgresql.org/docs/current/xfunc-c.html#XFUNC-C-RETURN-SET),
+  putting them all into a tuplestore in a single call, rather than one row per call.
 * `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
+* `support = my_support`: Corresponds to [`SUPPORT`](https://www.postgresql.org/docs/current/xfunc-optimization.html), on Postgres 12 and later.
+  + `fn my_support(request: SupportRequest) -> SupportResponse` is called through a generated `<function>_support_wrapper` function.
 
 Functions can accept and return any type which `pgx` supports. `pgx` supports many PostgreSQL types by default.
 New types can be defined via [`macro@PostgresType`] or [`macro@PostgresEnum`].
@@ -512,6 +612,26 @@ It accepts 2 arguments:
 * A name, such as `example`
 * A type
 
+Functions may also return a `Result<T, E>` where `E: Into<pgx::PgError>`, which returns `T` as
+above, and raises an `Err` as an `ERROR` with its SQLSTATE, detail, and hint:
+
+```rust,ignore
+use pgx::*;
+#[pg_extern]
+fn read_setting(path: &str) -> Result<String, std::io::Error> {
+    std::fs::read_to_string(path)
+}
+
+#[pg_extern]
+fn positive(value: i32) -> Result<i32, PgError> {
+    if value <= 0 {
+        return Err(PgError::new(PgSqlErrorCode::ERRCODE_NUMERIC_VALUE_OUT_OF_RANGE, "not positive")
+            .with_hint("pass a value greater than zero"));
+    }
+    Ok(value)
+}
+```
+
 # Special Cases
 
 `pg_sys::Oid` is a special cased type alias, in order to use it as an argument or return it must be
@@ -566,6 +686,7 @@ fn rewrite_item_fn(
     // make the function 'extern "C"' because this is for the #[pg_extern[ macro
     func.sig.abi = Some(syn::parse_str("extern \"C\"").unwrap());
     let func_span = func.span();
+    let support_wrapper = sql_graph_entity_submission.support_wrapper().into_iter();
     let (rewritten_func, need_wrapper) = rewriter.item_fn(
         func,
         Some(sql_graph_entity_submission),
@@ -584,11 +705,15 @@ fn rewrite_item_fn(
             }
 
             #rewritten_func
+
+            #(#support_wrapper)*
         }
     } else {
         quote_spanned! {func_span=>
 
             #rewritten_func
+
+            #(#support_wrapper)*
         }
     }
 }
@@ -694,6 +819,26 @@ Optionally accepts the following attributes:
 * `inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the type.
 * `pgvarlena_inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the `PgVarlena` of this type.
 * `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
+* `#[pgx(sendrecv = custom)]`: Implement `SendRecvFuncs` for the binary send/receive functions of the type,
+  rather than sending its CBOR representation.  `pgvarlena_inoutfuncs` types only have binary
+  send/receive functions with this attribute.
+* `#[pgx(storage = "cbor" | "bincode" | "messagepack" | custom)]`: Store the type in the given serde
+  format, or implement `PostgresTypeStorage` to store it some other way.  Stored values start
+  with a version tag byte.  Without this attribute (or `#[pgx(version = N)]`) the type is stored
+  as untagged CBOR.
+* `#[pgx(version = N)]`: Tag stored values with version `N` rather than 0.  Types with a version
+  greater than 0 implement `MigrateStorage` to read the values stored by earlier versions.
+* `#[pgx(legacy_untagged)]`: Alongside `#[pgx(version = N)]`, for types that were already stored
+  as untagged CBOR.  Those values are passed to `MigrateStorage` as version 0.
+* `#[pgx(fixed_size)]`: Store a `Copy` type as its in-memory representation, with a fixed
+  `INTERNALLENGTH`, rather than as a varlena.  The type can't have padding between or after its
+  fields.  It only has binary send/receive functions with `#[pgx(sendrecv = custom)]`.
+* `#[pgx(by_value)]`: Like `#[pgx(fixed_size)]`, but the type is `PASSEDBYVALUE` in a `Datum`, so
+  must be 1, 2, 4 or 8 bytes long.
+* `#[pgx(typmod)]`: Implement `TypmodInOutFuncs` to accept a type modifier, as in `my_type(384)`.  The
+  typmod is passed to the type's input function, and a length-coercion cast applies it.
+* `#[pgx(subscript)]`: Implement `Subscriptable` to support `value[subscript]` expressions and
+  assignments.  Only on Postgres 14, where the type gets a `SUBSCRIPT` handler.
 */
 #[proc_macro_derive(
     PostgresType,
@@ -711,7 +856,24 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
     let has_lifetimes = generics.lifetimes().next();
     let funcname_in = Ident::new(&format!("{}_in", name).to_lowercase(), name.span());
     let funcname_out = Ident::new(&format!("{}_out", name).to_lowercase(), name.span());
+    let funcname_send = Ident::new(&format!("{}_send", name).to_lowercase(), name.span());
+    let funcname_recv = Ident::new(&format!("{}_recv", name).to_lowercase(), name.span());
+    let funcname_typmod_in = Ident::new(&format!("{}_typmod_in", name).to_lowercase(), name.span());
+    let funcname_typmod_out =
+        Ident::new(&format!("{}_typmod_out", name).to_lowercase(), name.span());
+    let funcname_typmod_coerce = Ident::new(
+        &format!("{}_typmod_coerce", name).to_lowercase(),
+        name.span(),
+    );
+    let funcname_subscript_handler = Ident::new(
+        &format!("{}_subscript_handler", name).to_lowercase(),
+        name.span(),
+    );
     let mut args = parse_postgres_type_args(&ast.attrs);
+    let pgx_args = match PostgresTypeArgs::from_attributes(&ast.attrs) {
+        Ok(pgx_args) => pgx_args,
+        Err(e) => return e.to_compile_error(),
+    };
     let mut stream = proc_macro2::TokenStream::new();
 
     // validate that we're only operating on a struct
@@ -730,11 +892,150 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         None => quote! {'static},
     };
 
+    // types with a typmod have it passed to their _in function, as its third argument
+    let (typmod_in_args, apply_typmod, apply_pgvarlena_typmod) = if pgx_args.typmod {
+        (
+            quote! { , _typelem: pgx::pg_sys::Oid, typmod: i32 },
+            quote! {
+                if typmod >= 0 {
+                    <#name #generics as pgx::TypmodInOutFuncs>::apply_typmod(result, typmod, false)
+                } else {
+                    result
+                }
+            },
+            quote! {
+                if typmod >= 0 {
+                    *result = <#name #generics as pgx::TypmodInOutFuncs>::apply_typmod(*result, typmod, false);
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! { result }, quote! {})
+    };
+
     // all #[derive(PostgresType)] need to implement that trait
     stream.extend(quote! {
         impl #generics pgx::PostgresType for #name #generics { }
     });
 
+    // serde types are stored in a serde format, CBOR unless they ask for another one, and only
+    // have a version tag if they ask for a storage format or version.  PgVarlena types are stored
+    // as their in-memory representation
+    let is_pgvarlena = args.contains(&PostgresTypeAttribute::PgVarlenaInOutFuncs);
+    if is_pgvarlena && (pgx_args.storage.is_some() || pgx_args.version.is_some()) {
+        return syn::Error::new(
+            name.span(),
+            "`#[pgvarlena_inoutfuncs]` types can't have a `#[pgx(storage)]` or `#[pgx(version)]`",
+        )
+        .to_compile_error();
+    } else if is_pgvarlena && pgx_args.fixed_size {
+        return syn::Error::new(
+            name.span(),
+            "`#[pgvarlena_inoutfuncs]` types can't be `#[pgx(fixed_size)]` or `#[pgx(by_value)]`, use `#[inoutfuncs]`",
+        )
+        .to_compile_error();
+    } else if pgx_args.fixed_size {
+        // fixed-size types are copied in and out of Datums as they are
+        if has_lifetimes.is_some() {
+            return syn::Error::new(
+                name.span(),
+                "`#[pgx(fixed_size)]` and `#[pgx(by_value)]` types can't have lifetimes",
+            )
+            .to_compile_error();
+        }
+
+        // padding bytes are uninitialized, so would be stored as garbage.  Without any padding,
+        // the type is exactly as big as its fields
+        let field_types = match &ast.data {
+            Data::Struct(data) => data
+                .fields
+                .iter()
+                .map(|field| &field.ty)
+                .collect::<Vec<_>>(),
+            _ => unreachable!("#[derive(PostgresType)] can only be applied to structs"),
+        };
+        stream.extend(quote! {
+            const _: () = assert!(
+                ::core::mem::size_of::<#name>() == 0 #( + ::core::mem::size_of::<#field_types>() )*,
+                "#[pgx(fixed_size)] and #[pgx(by_value)] types can't have padding"
+            );
+        });
+
+        let by_value = pgx_args.by_value;
+        if by_value {
+            stream.extend(quote! {
+                const _: () = assert!(
+                    matches!(::core::mem::size_of::<#name>(), 1 | 2 | 4 | 8),
+                    "#[pgx(by_value)] types must be 1, 2, 4 or 8 bytes long"
+                );
+            });
+        }
+
+        stream.extend(quote! {
+            impl pgx::FromDatum for #name {
+                unsafe fn from_datum(datum: pgx::pg_sys::Datum, is_null: bool, _typoid: pgx::pg_sys::Oid) -> Option<Self> {
+                    if is_null {
+                        None
+                    } else {
+                        Some(pgx::fixed_size_from_datum(datum, #by_value))
+                    }
+                }
+            }
+
+            impl pgx::IntoDatum for #name {
+                fn into_datum(self) -> Option<pgx::pg_sys::Datum> {
+                    Some(pgx::fixed_size_into_datum(self, #by_value))
+                }
+
+                fn type_oid() -> pgx::pg_sys::Oid {
+                    pgx::rust_regtypein::<Self>()
+                }
+            }
+        });
+    } else if !is_pgvarlena && pgx_args.storage != Some(PostgresTypeStorageArg::Custom) {
+        let (storage_generics, storage_lifetime) = match has_lifetimes {
+            Some(lifetime) => (quote! {#generics}, quote! {#lifetime}),
+            None => (quote! {<'de>}, quote! {'de}),
+        };
+        let format = match pgx_args.storage {
+            Some(PostgresTypeStorageArg::Bincode) => quote! { pgx::StorageFormat::Bincode },
+            Some(PostgresTypeStorageArg::MessagePack) => quote! { pgx::StorageFormat::MessagePack },
+            _ => quote! { pgx::StorageFormat::Cbor },
+        };
+        let version = match (pgx_args.storage, pgx_args.version) {
+            (_, Some(version)) => quote! { Some(#version) },
+            (Some(_), None) => quote! { Some(0) },
+            (None, None) => quote! { None },
+        };
+        let legacy_untagged = pgx_args.legacy_untagged;
+        let migrate_from = if pgx_args.version.unwrap_or(0) > 0 {
+            quote! {
+                fn migrate_from(version: u8, bytes: &#storage_lifetime [u8]) -> Self {
+                    <#name #generics as pgx::MigrateStorage<#storage_lifetime>>::migrate_from(version, bytes)
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        stream.extend(quote! {
+            impl #storage_generics pgx::PostgresTypeStorage<#storage_lifetime> for #name #generics {
+                const VERSION: Option<u8> = #version;
+                const LEGACY_UNTAGGED: bool = #legacy_untagged;
+
+                fn encode(&self, buffer: &mut pgx::StringInfo) {
+                    #format.encode(self, buffer)
+                }
+
+                fn decode(bytes: &#storage_lifetime [u8]) -> Self {
+                    #format.decode(bytes)
+                }
+
+                #migrate_from
+            }
+        });
+    }
+
     // and if we don't have custom inout/funcs, we use the JsonInOutFuncs trait
     // which implements _in and _out #[pg_extern] functions that just return the type itself
     if args.contains(&PostgresTypeAttribute::Default) {
@@ -749,8 +1050,9 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
 
             #[doc(hidden)]
             #[pg_extern(immutable,parallel_safe)]
-            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr) -> #name #generics {
-                #name::input(input)
+            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr #typmod_in_args) -> #name #generics {
+                let result = #name::input(input);
+                #apply_typmod
             }
 
             #[doc(hidden)]
@@ -767,8 +1069,9 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         stream.extend(quote! {
             #[doc(hidden)]
             #[pg_extern(immutable,parallel_safe)]
-            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr) -> #name #generics {
-                #name::input(input)
+            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr #typmod_in_args) -> #name #generics {
+                let result = #name::input(input);
+                #apply_typmod
             }
 
             #[doc(hidden)]
@@ -784,8 +1087,10 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         stream.extend(quote! {
             #[doc(hidden)]
             #[pg_extern(immutable,parallel_safe)]
-            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr) -> pgx::PgVarlena<#name #generics> {
-                #name::input(input)
+            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr #typmod_in_args) -> pgx::PgVarlena<#name #generics> {
+                let mut result = #name::input(input);
+                #apply_pgvarlena_typmod
+                result
             }
 
             #[doc(hidden)]
@@ -798,6 +1103,133 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         });
     }
 
+    // binary send/receive functions use CBOR unless the type asks for custom ones.  PgVarlena
+    // and fixed-size types are stored as their in-memory representation, so only get custom ones
+    if pgx_args.custom_sendrecv && is_pgvarlena {
+        stream.extend(quote! {
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe)]
+            pub fn #funcname_send #generics(input: pgx::PgVarlena<#name #generics>) -> Vec<u8> {
+                let mut buffer = StringInfo::new();
+                <#name #generics as pgx::SendRecvFuncs>::send(&input, &mut buffer);
+                buffer.as_bytes().to_vec()
+            }
+
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe)]
+            pub fn #funcname_recv #generics(input: pgx::Internal) -> pgx::PgVarlena<#name #generics> {
+                let buffer = input.unwrap().expect("no buffer given to a _recv function");
+                let mut result = pgx::PgVarlena::<#name #generics>::new();
+                *result = <#name #generics as pgx::SendRecvFuncs>::recv(unsafe {
+                    pgx::recv_buffer_bytes(buffer as pgx::pg_sys::StringInfo)
+                });
+                result
+            }
+        });
+    } else if pgx_args.custom_sendrecv || !(is_pgvarlena || pgx_args.fixed_size) {
+        let sendrecv_trait = if pgx_args.custom_sendrecv {
+            quote! { pgx::SendRecvFuncs }
+        } else {
+            let sendrecv_generics = if has_lifetimes.is_some() {
+                quote! {#generics}
+            } else {
+                quote! {<'_>}
+            };
+            stream.extend(quote! {
+                impl #generics CborSendRecvFuncs #sendrecv_generics for #name #generics {}
+            });
+            quote! { pgx::CborSendRecvFuncs }
+        };
+
+        stream.extend(quote! {
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe)]
+            pub fn #funcname_send #generics(input: #name #generics) -> Vec<u8> {
+                let mut buffer = StringInfo::new();
+                <#name #generics as #sendrecv_trait>::send(&input, &mut buffer);
+                buffer.as_bytes().to_vec()
+            }
+
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe)]
+            pub fn #funcname_recv #generics(input: pgx::Internal) -> #name #generics {
+                let buffer = input.unwrap().expect("no buffer given to a _recv function");
+                <#name #generics as #sendrecv_trait>::recv(unsafe {
+                    pgx::recv_buffer_bytes(buffer as pgx::pg_sys::StringInfo)
+                })
+            }
+        });
+    }
+
+    if pgx_args.typmod {
+        stream.extend(quote! {
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe)]
+            pub fn #funcname_typmod_in(input: pgx::Array<&pgx::cstr_core::CStr>) -> i32 {
+                let mut nmodifiers = 0;
+                let modifiers = unsafe {
+                    let modifiers = pgx::pg_sys::ArrayGetIntegerTypmods(input.into_array_type() as *mut _, &mut nmodifiers);
+                    std::slice::from_raw_parts(modifiers, nmodifiers as usize)
+                };
+                <#name #generics as pgx::TypmodInOutFuncs>::typmod_in(modifiers)
+            }
+
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe)]
+            pub fn #funcname_typmod_out(typmod: i32) -> &'static pgx::cstr_core::CStr {
+                let mut buffer = StringInfo::new();
+                <#name #generics as pgx::TypmodInOutFuncs>::typmod_out(typmod, &mut buffer);
+                buffer.into()
+            }
+        });
+
+        if is_pgvarlena {
+            stream.extend(quote! {
+                #[doc(hidden)]
+                #[pg_extern(immutable,parallel_safe)]
+                pub fn #funcname_typmod_coerce #generics(input: pgx::PgVarlena<#name #generics>, typmod: i32, explicit: bool) -> pgx::PgVarlena<#name #generics> {
+                    let mut result = pgx::PgVarlena::<#name #generics>::new();
+                    *result = <#name #generics as pgx::TypmodInOutFuncs>::apply_typmod(*input, typmod, explicit);
+                    result
+                }
+            });
+        } else {
+            stream.extend(quote! {
+                #[doc(hidden)]
+                #[pg_extern(immutable,parallel_safe)]
+                pub fn #funcname_typmod_coerce #generics(input: #name #generics, typmod: i32, explicit: bool) -> #name #generics {
+                    <#name #generics as pgx::TypmodInOutFuncs>::apply_typmod(input, typmod, explicit)
+                }
+            });
+        }
+    }
+
+    if pgx_args.subscript {
+        if is_pgvarlena || has_lifetimes.is_some() {
+            return syn::Error::new(
+                name.span(),
+                "`#[pgx(subscript)]` types can't be `#[pgvarlena_inoutfuncs]` or have lifetimes",
+            )
+            .to_compile_error();
+        }
+
+        stream.extend(quote! {
+            #[cfg(feature = "pg14")]
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe)]
+            pub fn #funcname_subscript_handler(_internal: pgx::Internal) -> pgx::Internal {
+                static ROUTINES: pgx::pg_sys::SubscriptRoutines = pgx::pg_sys::SubscriptRoutines {
+                    transform: Some(pgx::subscript_transform::<#name>),
+                    exec_setup: Some(pgx::subscript_exec_setup::<#name>),
+                    fetch_strict: false,
+                    fetch_leakproof: false,
+                    store_leakproof: false,
+                };
+                pgx::Internal::from(Some(&ROUTINES as *const _ as pgx::pg_sys::Datum))
+            }
+        });
+    }
+
     let sql_graph_entity_item = PostgresType::from_derive_input(ast).unwrap();
     sql_graph_entity_item.to_tokens(&mut stream);
 
@@ -1021,6 +1453,95 @@ pub fn pg_aggregate(_attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/**
+Declare a `pgx::TextSearchParser` implementation on a type as a Postgres text search parser.
+
+Generates the `internal` support functions and a `CREATE TEXT SEARCH PARSER` statement.
+The `HEADLINE` function is only declared if `headline` is implemented.
+*/
+#[proc_macro_attribute]
+pub fn pg_ts_parser(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    fn wrapped(item_impl: ItemImpl) -> Result<TokenStream, syn::Error> {
+        let sql_graph_entity_item = PgTextSearchParser::new(item_impl)?;
+
+        Ok(sql_graph_entity_item.to_token_stream().into())
+    }
+
+    let parsed_base = parse_macro_input!(item as syn::ItemImpl);
+    match wrapped(parsed_base) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let msg = e.to_string();
+            TokenStream::from(quote! {
+              compile_error!(#msg);
+            })
+        }
+    }
+}
+
+/**
+Declare a `pgx::TextSearchDictionary` implementation on a type as a Postgres text search template.
+
+Generates the `internal` support functions and a `CREATE TEXT SEARCH TEMPLATE` statement.
+*/
+#[proc_macro_attribute]
+pub fn pg_ts_template(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    fn wrapped(item_impl: ItemImpl) -> Result<TokenStream, syn::Error> {
+        let sql_graph_entity_item = PgTextSearchTemplate::new(item_impl)?;
+
+        Ok(sql_graph_entity_item.to_token_stream().into())
+    }
+
+    let parsed_base = parse_macro_input!(item as syn::ItemImpl);
+    match wrapped(parsed_base) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let msg = e.to_string();
+            TokenStream::from(quote! {
+              compile_error!(#msg);
+            })
+        }
+    }
+}
+
+/**
+Declare a `pgx::GistOpClass`, `pgx::GinOpClass`, `pgx::SpGistOpClass` or `pgx::BrinOpClass`
+implementation on a type as the default operator class of the type for that index access method.
+
+Generates the `internal` support functions and a `CREATE OPERATOR CLASS` statement, with the
+`OPERATORS` of the implementation.  The optional `distance` (GiST) and `tri_consistent` (GIN)
+support functions are only declared if they are implemented.
+
+```rust,ignore
+#[pg_opclass]
+impl GinOpClass for Tags {
+    const OPERATORS: &'static [(u16, &'static str)] = &[(1, "&&"), (2, "@>")];
+    type Key = String;
+    type Query = Tags;
+    // ...
+}
+```
+*/
+#[proc_macro_attribute]
+pub fn pg_opclass(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    fn wrapped(item_impl: ItemImpl) -> Result<TokenStream, syn::Error> {
+        let sql_graph_entity_item = PgOperatorClass::new(item_impl)?;
+
+        Ok(sql_graph_entity_item.to_token_stream().into())
+    }
+
+    let parsed_base = parse_macro_input!(item as syn::ItemImpl);
+    match wrapped(parsed_base) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let msg = e.to_string();
+            TokenStream::from(quote! {
+              compile_error!(#msg);
+            })
+        }
+    }
+}
+
 /**
 A helper attribute for various contexts.
 
@@ -13,6 +13,11 @@ Use of this source code is governed by the MIT license that can be found in the
 #define IS_PG_12 (PG_VERSION_NUM >= 120000 && PG_VERSION_NUM < 130000)
 #define IS_PG_13 (PG_VERSION_NUM >= 130000 && PG_VERSION_NUM < 140000)
 
+#if IS_PG_10 || IS_PG_11 || IS_PG_12
+#include "access/tuptoaster.h"
+#else
+#include "access/detoast.h"
+#endif
 #include "access/htup.h"
 #include "access/htup_details.h"
 #include "catalog/pg_type.h"
@@ -49,6 +54,27 @@ void pgx_ereport(int level, int code, char *message, char *file, int lineno, int
                     errmsg("%s", message), errcontext_msg("%s:%d:%d", file, lineno, colno)));
 }
 
+PGDLLEXPORT void pgx_ereport_full(int level, int code, char *message, char *detail, char *hint, char *context, int position,
+        char *schema_name, char *table_name, char *column_name, char *datatype_name, char *constraint_name,
+        char *file, int lineno, int colno);
+void pgx_ereport_full(int level, int code, char *message, char *detail, char *hint, char *context, int position,
+        char *schema_name, char *table_name, char *column_name, char *datatype_name, char *constraint_name,
+        char *file, int lineno, int colno) {
+    ereport(level,
+            (errcode(code),
+                    errmsg("%s", message),
+                    detail != NULL ? errdetail("%s", detail) : 0,
+                    hint != NULL ? errhint("%s", hint) : 0,
+                    position > 0 ? errposition(position) : 0,
+                    schema_name != NULL ? err_generic_string(PG_DIAG_SCHEMA_NAME, schema_name) : 0,
+                    table_name != NULL ? err_generic_string(PG_DIAG_TABLE_NAME, table_name) : 0,
+                    column_name != NULL ? err_generic_string(PG_DIAG_COLUMN_NAME, column_name) : 0,
+                    datatype_name != NULL ? err_generic_string(PG_DIAG_DATATYPE_NAME, datatype_name) : 0,
+                    constraint_name != NULL ? err_generic_string(PG_DIAG_CONSTRAINT_NAME, constraint_name) : 0,
+                    context != NULL ? errcontext_msg("%s", context) : 0,
+                    errcontext_msg("%s:%d:%d", file, lineno, colno)));
+}
+
 PGDLLEXPORT void pgx_SET_VARSIZE(struct varlena *ptr, int size);
 void pgx_SET_VARSIZE(struct varlena *ptr, int size) {
     SET_VARSIZE(ptr, size);
@@ -59,6 +85,11 @@ void pgx_SET_VARSIZE_SHORT(struct varlena *ptr, int size) {
     SET_VARSIZE_SHORT(ptr, size);
 }
 
+PGDLLEXPORT Size pgx_toast_raw_datum_size(Datum value);
+Size pgx_toast_raw_datum_size(Datum value) {
+    return toast_raw_datum_size(value);
+}
+
 PGDLLEXPORT Datum pgx_heap_getattr(HeapTupleData *tuple, int attnum, TupleDesc tupdesc, bool *isnull);
 Datum pgx_heap_getattr(HeapTupleData *tuple, int attnum, TupleDesc tupdesc, bool *isnull) {
     return heap_getattr(tuple, attnum, tupdesc, isnull);
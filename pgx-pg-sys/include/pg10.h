@@ -16,6 +16,8 @@ Use of this source code is governed by the MIT license that can be found in the
 #include "pgstat.h"
 
 #include "access/amapi.h"
+#include "access/brin_internal.h"
+#include "access/brin_tuple.h"
 #include "access/genam.h"
 #include "access/gin.h"
 #include "access/gist.h"
@@ -25,15 +27,18 @@ Use of this source code is governed by the MIT license that can be found in the
 #include "access/reloptions.h"
 #include "access/relscan.h"
 #include "access/skey.h"
+#include "access/spgist.h"
 #include "access/sysattr.h"
 #include "access/xact.h"
 #include "catalog/dependency.h"
 #include "catalog/index.h"
 #include "catalog/namespace.h"
+#include "catalog/objectaccess.h"
 #include "catalog/objectaddress.h"
 #include "catalog/pg_authid.h"
 #include "catalog/pg_class.h"
 #include "catalog/pg_enum.h"
+#include "catalog/pg_largeobject.h"
 #include "catalog/pg_operator.h"
 #include "catalog/pg_proc.h"
 #include "catalog/pg_trigger.h"
@@ -46,11 +51,15 @@ Use of this source code is governed by the MIT license that can be found in the
 #include "commands/proclang.h"
 #include "commands/tablecmds.h"
 #include "commands/trigger.h"
+#include "commands/user.h"
 #include "commands/vacuum.h"
 #include "executor/executor.h"
 #include "executor/spi.h"
 #include "foreign/fdwapi.h"
 #include "foreign/foreign.h"
+#include "libpq/auth.h"
+#include "libpq/crypt.h"
+#include "libpq/libpq-fs.h"
 #include "mb/pg_wchar.h"
 
 #define ScanKey struct ScanKeyData *
@@ -73,11 +82,14 @@ Use of this source code is governed by the MIT license that can be found in the
 #include "optimizer/planner.h"
 #include "optimizer/restrictinfo.h"
 #include "optimizer/tlist.h"
+#include "parser/analyze.h"
+#include "parser/parse_coerce.h"
 #include "parser/parse_func.h"
 #include "parser/parse_oper.h"
 #include "parser/parse_type.h"
 #include "parser/parser.h"
 #include "parser/parsetree.h"
+#include "parser/scanner.h"
 #include "postmaster/bgworker.h"
 #include "replication/output_plugin.h"
 #include "rewrite/rewriteHandler.h"
@@ -86,12 +98,14 @@ Use of this source code is governed by the MIT license that can be found in the
 #include "storage/buffile.h"
 #include "storage/ipc.h"
 #include "storage/itemptr.h"
+#include "storage/large_object.h"
 #include "storage/lwlock.h"
 #include "storage/procarray.h"
 #include "tcop/tcopprot.h"
 #include "tcop/utility.h"
 #include "tsearch/ts_public.h"
 #include "tsearch/ts_utils.h"
+#include "utils/acl.h"
 #include "utils/builtins.h"
 #include "utils/date.h"
 #include "utils/datetime.h"
@@ -113,3 +127,4 @@ Use of this source code is governed by the MIT license that can be found in the
 #include "utils/snapmgr.h"
 #include "utils/syscache.h"
 #include "utils/typcache.h"
+#include "windowapi.h"
@@ -92,12 +92,118 @@ pub fn register_pg_guard_panic_handler() {
     }))
 }
 
+/// A Postgres `ERROR` caught by `pg_try()`, copied out of Postgres' error state with
+/// `CopyErrorData()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorReport {
+    sqlerrcode: i32,
+    elevel: i32,
+    message: String,
+    detail: Option<String>,
+    hint: Option<String>,
+    context: Option<String>,
+}
+
+impl ErrorReport {
+    /// Copy the current error, which must be on Postgres' error stack
+    unsafe fn copy_current() -> Self {
+        unsafe fn string(ptr: *const std::os::raw::c_char) -> Option<String> {
+            ptr.as_ref()
+                .map(|ptr| std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned())
+        }
+
+        let edata = crate::CopyErrorData();
+        let report = {
+            let edata = edata.as_ref().unwrap();
+            ErrorReport {
+                sqlerrcode: edata.sqlerrcode,
+                elevel: edata.elevel,
+                message: string(edata.message).unwrap_or_default(),
+                detail: string(edata.detail),
+                hint: string(edata.hint),
+                context: string(edata.context),
+            }
+        };
+        crate::FreeErrorData(edata);
+        report
+    }
+
+    /// The error's SQLSTATE, as encoded by `MAKE_SQLSTATE()`
+    pub fn sqlerrcode(&self) -> i32 {
+        self.sqlerrcode
+    }
+
+    /// The error's five character SQLSTATE, such as `23505`
+    pub fn sqlstate(&self) -> String {
+        unsafe { std::ffi::CStr::from_ptr(crate::unpack_sql_state(self.sqlerrcode)) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// The level the error was raised at, such as `ERROR`
+    pub fn elevel(&self) -> i32 {
+        self.elevel
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+}
+
+/// The error caught by `pg_try()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaughtError {
+    /// A Rust `panic!()`, which is rethrown as an `ERROR` with its message and location
+    RustPanic {
+        message: String,
+        file: String,
+        line: u32,
+        col: u32,
+    },
+    /// An `ERROR` raised by Postgres, which remains Postgres' current error until it's caught or
+    /// rethrown
+    Postgres(ErrorReport),
+}
+
 /// A `std::result::Result`-type value returned from `pg_try()` that allows for performing cleanup
 /// work after a closure raised an error and before it is possibly rethrown
 #[must_use = "this `PgTryResult` may be be holding a Postgres ERROR.  It must be consumed or rethrown"]
-pub struct PgTryResult<T>(std::thread::Result<T>);
+pub struct PgTryResult<T> {
+    result: Result<T, CaughtError>,
+
+    /// A Postgres ERROR caught by `pg_try_subtransaction()` was flushed before its subtransaction
+    /// was rolled back, and is rethrown from this copy
+    flushed: Option<*mut crate::ErrorData>,
+}
 
 impl<T> PgTryResult<T> {
+    fn new(result: Result<T, CaughtError>) -> Self {
+        PgTryResult {
+            result,
+            flushed: None,
+        }
+    }
+
+    /// Forget about the caught error, which won't be rethrown
+    unsafe fn discard(flushed: Option<*mut crate::ErrorData>) {
+        match flushed {
+            Some(edata) => crate::FreeErrorData(edata),
+            None => FlushErrorState(),
+        }
+    }
+
     /// Retrieve the returned value or panic if the try block raised an error
     pub fn unwrap(self) -> T {
         self.unwrap_or_rethrow(|| {})
@@ -111,10 +217,10 @@ impl<T> PgTryResult<T> {
     /// Doing so can potentially leave Postgres in an undefined state and ultimately cause it
     /// to crash.
     pub unsafe fn unwrap_or(self, value: T) -> T {
-        match self.0 {
+        match self.result {
             Ok(result) => result,
             Err(_) => {
-                FlushErrorState();
+                Self::discard(self.flushed);
                 value
             }
         }
@@ -133,15 +239,93 @@ impl<T> PgTryResult<T> {
     where
         F: FnOnce() -> T,
     {
-        match self.0 {
+        match self.result {
             Ok(result) => result,
             Err(_) => {
-                FlushErrorState();
+                Self::discard(self.flushed);
                 cleanup()
             }
         }
     }
 
+    /// Recover from a Postgres ERROR with the SQLSTATE `sqlstate`, such as
+    /// `PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION`, by returning the value of `catch` instead.
+    ///
+    /// Other errors are left in the result, for another `catch_sqlstate()` or to be rethrown.
+    ///
+    /// Code that takes locks, pins buffers or runs queries needs to be run with
+    /// `pg_try_subtransaction()`, so that its work is rolled back when an ERROR is caught:
+    ///
+    /// ```rust,ignore
+    /// use pgx::*;
+    ///
+    /// let inserted = unsafe {
+    ///     pg_try_subtransaction(|| Spi::run("INSERT INTO users (name) VALUES ('alice')"))
+    ///         .map(|_| true)
+    ///         .catch_sqlstate(PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION, |_| false)
+    ///         .unwrap()
+    /// };
+    /// ```
+    ///
+    /// ## Safety
+    ///
+    /// The caught ERROR isn't rethrown.  Unless it was caught by `pg_try_subtransaction()`, the
+    /// work done before it was raised isn't rolled back, so you better know that the code which
+    /// raised it leaves Postgres in a consistent state.
+    pub unsafe fn catch_sqlstate<C, F>(self, sqlstate: C, catch: F) -> Self
+    where
+        C: Into<i32>,
+        F: FnOnce(ErrorReport) -> T,
+    {
+        let sqlstate = sqlstate.into();
+        match self.result {
+            Err(CaughtError::Postgres(report)) if report.sqlerrcode == sqlstate => {
+                Self::discard(self.flushed);
+                PgTryResult::new(Ok(catch(report)))
+            }
+            result => PgTryResult {
+                result,
+                flushed: self.flushed,
+            },
+        }
+    }
+
+    /// Recover from any caught error by returning the value of `catch` instead
+    ///
+    /// ## Safety
+    ///
+    /// The caught ERROR isn't rethrown.  Unless it was caught by `pg_try_subtransaction()`, the
+    /// work done before it was raised isn't rolled back, so you better know that the code which
+    /// raised it leaves Postgres in a consistent state.
+    pub unsafe fn catch_others<F>(self, catch: F) -> T
+    where
+        F: FnOnce(CaughtError) -> T,
+    {
+        match self.result {
+            Ok(result) => result,
+            Err(error) => {
+                Self::discard(self.flushed);
+                catch(error)
+            }
+        }
+    }
+
+    /// Transform the returned value, leaving a caught error as it is
+    pub fn map<U, F>(self, f: F) -> PgTryResult<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        PgTryResult {
+            result: self.result.map(f),
+            flushed: self.flushed,
+        }
+    }
+
+    /// The caught error, if there is one
+    pub fn caught(&self) -> Option<&CaughtError> {
+        self.result.as_ref().err()
+    }
+
     /// Perform some operation cleanup operation after the try block if an error was thrown.
     ///
     /// In the event an error was caught, it is rethrown.
@@ -149,10 +333,10 @@ impl<T> PgTryResult<T> {
     where
         F: FnOnce(),
     {
-        match self.0 {
+        match self.result {
             Ok(result) => result,
             Err(e) => {
-                catch_guard(e, cleanup);
+                catch_guard(e, self.flushed, cleanup);
                 unreachable!("failed to rethrow ERROR during pg_try().unwrap_or_rethrow()")
             }
         }
@@ -166,13 +350,13 @@ impl<T> PgTryResult<T> {
     where
         F: FnOnce(),
     {
-        match self.0 {
+        match self.result {
             Ok(result) => {
                 finally_block();
                 result
             }
             Err(e) => {
-                catch_guard(e, finally_block);
+                catch_guard(e, self.flushed, finally_block);
                 unreachable!("failed to rethrow ERROR during pg_try().finally_or_rethrow()")
             }
         }
@@ -203,20 +387,75 @@ where
     try_guard(try_func)
 }
 
+/// Like `pg_try`, but runs `try_func` in a subtransaction, as a PL/pgSQL `EXCEPTION` block does.
+///
+/// If `try_func` raises an error, the subtransaction is rolled back before `pg_try_subtransaction`
+/// returns, releasing the locks, buffer pins, SPI connections and other resources it acquired.
+/// A caught error can then be recovered from with `catch_sqlstate()` and friends.
+pub fn pg_try_subtransaction<Try, R>(try_func: Try) -> PgTryResult<R>
+where
+    Try: FnOnce() -> R + std::panic::UnwindSafe + std::panic::RefUnwindSafe,
+{
+    unsafe {
+        let context = crate::CurrentMemoryContext;
+        let owner = crate::CurrentResourceOwner;
+
+        crate::BeginInternalSubTransaction(std::ptr::null());
+        // `try_func` runs in the caller's memory context, so its results outlive the subtransaction
+        crate::CurrentMemoryContext = context;
+
+        let mut result = try_guard(try_func);
+        match &result.result {
+            Ok(_) => crate::ReleaseCurrentSubTransaction(),
+            Err(error) => {
+                if let CaughtError::Postgres(_) = error {
+                    // the subtransaction can only be rolled back once the error state is clean
+                    result.flushed = Some(crate::CopyErrorData());
+                    FlushErrorState();
+                }
+                crate::RollbackAndReleaseCurrentSubTransaction();
+            }
+        }
+
+        crate::CurrentMemoryContext = context;
+        crate::CurrentResourceOwner = owner;
+        result
+    }
+}
+
 fn try_guard<Try, R>(try_func: Try) -> PgTryResult<R>
 where
     Try: FnOnce() -> R + std::panic::UnwindSafe + std::panic::RefUnwindSafe,
 {
+    let context = unsafe { crate::CurrentMemoryContext };
+
     // run try_func() in a catch_unwind, as we never want a Rust panic! to leak
     // from this function.  It's imperative that we nevery try to panic! across
     // FFI (extern "C") function boundaries
-    let result = catch_unwind(try_func);
+    let result = catch_unwind(try_func).map_err(|error| match downcast_err(error) {
+        Ok(message) => {
+            let location = take_panic_location();
+            CaughtError::RustPanic {
+                message,
+                file: location.file,
+                line: location.line,
+                col: location.col,
+            }
+        }
+
+        // like a PG_CATCH() block, we leave the ErrorContext Postgres raised the error in
+        // before copying it
+        Err(_) => unsafe {
+            crate::CurrentMemoryContext = context;
+            CaughtError::Postgres(ErrorReport::copy_current())
+        },
+    });
 
     // return our result -- it could be Ok(), or it could be an Err()
-    PgTryResult(result)
+    PgTryResult::new(result)
 }
 
-fn catch_guard<Catch>(error: Box<dyn Any + std::marker::Send>, catch_func: Catch)
+fn catch_guard<Catch>(error: CaughtError, flushed: Option<*mut crate::ErrorData>, catch_func: Catch)
 where
     Catch: FnOnce(),
 {
@@ -225,14 +464,17 @@ where
     catch_func();
 
     // determine how to rethrow the error
-    match downcast_err(error) {
-        // the error is a String, which means it was originally a Rust panic!(), so
-        // translate it into an elog(ERROR), including the code location that caused
-        // the panic!()
-        Ok(message) => {
-            let location = take_panic_location();
+    match error {
+        // the error was originally a Rust panic!(), so translate it into an elog(ERROR),
+        // including the code location that caused the panic!()
+        CaughtError::RustPanic {
+            message,
+            file,
+            line,
+            col,
+        } => {
             let c_message = std::ffi::CString::new(message).unwrap();
-            let c_file = std::ffi::CString::new(location.file).unwrap();
+            let c_file = std::ffi::CString::new(file).unwrap();
 
             unsafe {
                 pgx_ereport(
@@ -240,16 +482,20 @@ where
                     2600, // ERRCODE_INTERNAL_ERROR
                     c_message.as_ptr(),
                     c_file.as_ptr(),
-                    location.line as i32,
-                    location.col as i32,
+                    line as i32,
+                    col as i32,
                 );
             }
             unreachable!("ereport() failed at depth==0");
         }
 
-        // the error is a JumpContext, so we need to longjmp back into Postgres
-        Err(_) => unsafe {
-            pg_re_throw();
+        // the error is still Postgres' current error, so we need to longjmp back into Postgres
+        CaughtError::Postgres(_) => unsafe {
+            match flushed {
+                // unless its subtransaction was rolled back, which flushed it
+                Some(edata) => crate::ReThrowError(edata),
+                None => pg_re_throw(),
+            }
             unreachable!("siglongjmp failed");
         },
     }
@@ -62,4 +62,63 @@ mod tests {
             .expect("SPI result was null");
         assert_eq!(vec.as_slice(), b"bcd")
     }
+
+    #[pg_extern]
+    fn bytea_reader_sum(mut input: PgByteaReader) -> i64 {
+        use std::io::Read;
+
+        let mut buf = [0u8; 1000];
+        let mut sum = 0i64;
+        loop {
+            let n = input.read(&mut buf).expect("failed to read bytea");
+            if n == 0 {
+                break;
+            }
+            sum += buf[..n].iter().map(|b| *b as i64).sum::<i64>();
+        }
+        sum
+    }
+
+    #[pg_extern]
+    fn text_reader_tail(mut input: PgTextReader, n: i64) -> String {
+        use std::io::{Read, Seek, SeekFrom};
+
+        input.seek(SeekFrom::End(-n)).expect("failed to seek text");
+        let mut tail = String::new();
+        input
+            .read_to_string(&mut tail)
+            .expect("failed to read text");
+        tail
+    }
+
+    #[pg_test]
+    fn test_bytea_reader_small() {
+        let sum = Spi::get_one::<i64>("SELECT tests.bytea_reader_sum('\\x010203'::bytea);")
+            .expect("SPI result was null");
+        assert_eq!(sum, 6)
+    }
+
+    #[pg_test]
+    fn test_bytea_reader_toasted() {
+        Spi::run(
+            "CREATE TABLE tests.bytea_reader_test (data bytea);
+             ALTER TABLE tests.bytea_reader_test ALTER COLUMN data SET STORAGE EXTERNAL;
+             INSERT INTO tests.bytea_reader_test
+                SELECT decode(repeat('01', 100000) || repeat('02', 50000), 'hex');",
+        );
+        let sum = Spi::get_one::<i64>(
+            "SELECT tests.bytea_reader_sum(data) FROM tests.bytea_reader_test;",
+        )
+        .expect("SPI result was null");
+        assert_eq!(sum, 200000)
+    }
+
+    #[pg_test]
+    fn test_text_reader_seek() {
+        let tail = Spi::get_one::<String>(
+            "SELECT tests.text_reader_tail(repeat('abc', 100000) || 'xyz', 4);",
+        )
+        .expect("SPI result was null");
+        assert_eq!(tail, "cxyz")
+    }
 }
@@ -0,0 +1,126 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct Temperature {
+    celsius: f64,
+}
+
+/// Stored just like a [`Temperature`], so it's binary coercible to one
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct LegacyTemperature {
+    celsius: f64,
+}
+
+#[pg_cast(implicit, immutable, parallel_safe)]
+fn temperature_from_float8(celsius: f64) -> Temperature {
+    Temperature { celsius }
+}
+
+#[pg_cast(assignment)]
+fn temperature_to_float8(temperature: Temperature) -> f64 {
+    temperature.celsius
+}
+
+#[pg_cast]
+fn temperature_to_int4(temperature: Temperature) -> i32 {
+    temperature.celsius.round() as i32
+}
+
+#[pg_cast(explicit, inout)]
+fn temperature_to_text(temperature: Temperature) -> String {
+    format!(r#"{{"celsius":{}}}"#, temperature.celsius)
+}
+
+#[pg_cast(without_function)]
+fn legacy_temperature_to_temperature(legacy: LegacyTemperature) -> Temperature {
+    Temperature {
+        celsius: legacy.celsius,
+    }
+}
+
+#[pg_extern]
+fn temperature_celsius(temperature: Temperature) -> f64 {
+    temperature.celsius
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    /// The `castcontext` and `castmethod` of the cast from `source` to `target`
+    fn cast_kind(source: &str, target: &str) -> (String, String) {
+        let (context, method) = Spi::get_two::<String, String>(&format!(
+            "SELECT castcontext::text, castmethod::text FROM pg_cast \
+             WHERE castsource = '{}'::regtype AND casttarget = '{}'::regtype",
+            source, target
+        ));
+        (
+            context.expect("no such cast"),
+            method.expect("no such cast"),
+        )
+    }
+
+    #[pg_test]
+    fn test_cast_catalog() {
+        let kind = |context: &str, method: &str| (context.to_string(), method.to_string());
+        assert_eq!(cast_kind("float8", "Temperature"), kind("i", "f"));
+        assert_eq!(cast_kind("Temperature", "float8"), kind("a", "f"));
+        assert_eq!(cast_kind("Temperature", "int4"), kind("e", "f"));
+        assert_eq!(cast_kind("Temperature", "text"), kind("e", "i"));
+        assert_eq!(
+            cast_kind("LegacyTemperature", "Temperature"),
+            kind("e", "b")
+        );
+    }
+
+    #[pg_test]
+    fn test_implicit_cast() {
+        let celsius = Spi::get_one::<f64>("SELECT temperature_celsius(21.5::float8)");
+        assert_eq!(celsius, Some(21.5));
+    }
+
+    #[pg_test]
+    fn test_assignment_cast() {
+        Spi::run(
+            r#"
+            CREATE TABLE readings (celsius float8);
+            INSERT INTO readings VALUES ('{"celsius": 18.25}'::Temperature);
+            "#,
+        );
+        let celsius = Spi::get_one::<f64>("SELECT celsius FROM readings");
+        assert_eq!(celsius, Some(18.25));
+    }
+
+    #[pg_test]
+    fn test_explicit_cast() {
+        let degrees = Spi::get_one::<i32>(r#"SELECT '{"celsius": 18.75}'::Temperature::int4"#);
+        assert_eq!(degrees, Some(19));
+    }
+
+    #[pg_test]
+    fn test_inout_cast() {
+        let text = Spi::get_one::<String>(r#"SELECT '{"celsius": 3.5}'::Temperature::text"#);
+        assert_eq!(text, Some(r#"{"celsius":3.5}"#.to_string()));
+    }
+
+    #[pg_test]
+    fn test_without_function_cast() {
+        let celsius = Spi::get_one::<f64>(
+            r#"SELECT temperature_celsius('{"celsius": 7.0}'::LegacyTemperature::Temperature)"#,
+        );
+        assert_eq!(celsius, Some(7.0));
+    }
+}
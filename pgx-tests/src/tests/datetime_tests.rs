@@ -131,6 +131,20 @@ mod serialization_tests {
             json
         );
     }
+
+    #[test]
+    fn test_infinity_serialization() {
+        let json = json!({
+            "date": Date::INFINITY,
+            "timestamp": Timestamp::NEG_INFINITY,
+            "timestamptz": TimestampWithTimeZone::INFINITY,
+        });
+
+        assert_eq!(
+            json!({"date": "infinity", "timestamp": "-infinity", "timestamptz": "infinity"}),
+            json
+        );
+    }
 }
 
 #[cfg(any(test, feature = "pg_test"))]
@@ -274,4 +288,111 @@ mod tests {
 
         assert_eq!(result, Duration::from_secs(60).as_micros() as i64);
     }
+
+    #[pg_test]
+    fn test_accept_infinite_dates() {
+        let result = Spi::get_one::<bool>(
+            "SELECT accept_date('infinity'::date) = 'infinity'::date
+                AND accept_date('-infinity'::date) = '-infinity'::date;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result);
+
+        let date = Spi::get_one::<Date>("SELECT '-infinity'::date").expect("failed to get date");
+        assert!(date.is_infinite());
+        assert_eq!(date.infinity(), Some(Infinity::Negative));
+        assert_eq!(date.as_time(), None);
+
+        let date = Spi::get_one::<Date>("SELECT '2020-04-07'::date").expect("failed to get date");
+        assert!(!date.is_infinite());
+        assert_eq!(date.as_time().map(|d| d.year()), Some(2020));
+    }
+
+    #[pg_test]
+    fn test_accept_infinite_timestamps() {
+        let result = Spi::get_one::<bool>(
+            "SELECT accept_timestamp('infinity'::timestamp) = 'infinity'::timestamp
+                AND accept_timestamp('-infinity'::timestamp) = '-infinity'::timestamp
+                AND accept_timestamp_with_time_zone('infinity') = 'infinity'::timestamptz
+                AND accept_timestamp_with_time_zone('-infinity') = '-infinity'::timestamptz;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result);
+
+        let ts = Spi::get_one::<Timestamp>("SELECT 'infinity'::timestamp")
+            .expect("failed to get timestamp");
+        assert_eq!(ts.infinity(), Some(Infinity::Positive));
+        assert_eq!(ts.to_char("YYYY"), None);
+        assert_eq!(ts.as_time(), None);
+
+        let ts = Spi::get_one::<TimestampWithTimeZone>("SELECT '-infinity'::timestamptz")
+            .expect("failed to get timestamptz");
+        assert_eq!(ts.infinity(), Some(Infinity::Negative));
+        assert_eq!(ts.to_session_time_zone(), None);
+        assert_eq!(ts.to_string(), "-infinity");
+    }
+
+    #[pg_test]
+    fn test_timestamp_with_time_zone_in_session_time_zone() {
+        Spi::run("SET LOCAL TIME ZONE 'America/Denver'");
+
+        let result = Spi::get_one::<bool>("SELECT accept_timestamp_with_time_zone('1990-01-23 03:45:00-07') = '1990-01-23 03:45:00-07'::timestamp with time zone;")
+            .expect("failed to get SPI result");
+        assert!(result);
+
+        let ts = Spi::get_one::<TimestampWithTimeZone>(
+            "SELECT '2020-02-18 14:08 -07'::timestamp with time zone",
+        )
+        .expect("failed to get SPI result");
+        // still read as UTC
+        assert_eq!(ts.hour(), 21);
+
+        let local = ts.to_session_time_zone().expect("timestamp is infinite");
+        assert_eq!(local.hour(), 14);
+        assert_eq!(local.offset().whole_hours(), -7);
+
+        let tokyo = ts
+            .at_time_zone("Asia/Tokyo")
+            .expect("timestamp is infinite");
+        assert_eq!(tokyo.hour(), 6);
+        assert_eq!(tokyo.offset().whole_hours(), 9);
+
+        assert_eq!(ts.to_string(), "2020-02-18 14:08:00-07");
+        assert_eq!(
+            ts.to_char("YYYY-MM-DD HH24:MI TZ"),
+            Some("2020-02-18 14:08 MST".to_string())
+        );
+    }
+
+    #[pg_test(error = "time zone \"Not/AZone\" not recognized")]
+    fn test_timestamp_with_time_zone_unknown_time_zone() {
+        TimestampWithTimeZone::from(0).at_time_zone("Not/AZone");
+    }
+
+    #[pg_test]
+    fn test_timestamp_is_not_shifted_by_session_time_zone() {
+        Spi::run("SET LOCAL TIME ZONE 'America/Denver'");
+
+        let ts = Spi::get_one::<Timestamp>("SELECT '2020-02-18 14:08'::timestamp")
+            .expect("failed to get SPI result");
+        assert_eq!(ts.hour(), 14);
+    }
+
+    #[pg_test]
+    fn test_datetime_display_honors_date_style() {
+        Spi::run("SET LOCAL DateStyle TO 'SQL, DMY'");
+
+        let date = Spi::get_one::<Date>("SELECT '2020-04-07'::date").expect("failed to get date");
+        assert_eq!(date.to_string(), "07/04/2020");
+
+        let ts = Spi::get_one::<Timestamp>("SELECT '2020-04-07 12:34:54'::timestamp")
+            .expect("failed to get timestamp");
+        assert_eq!(ts.to_string(), "07/04/2020 12:34:54");
+        assert_eq!(
+            ts.to_char("YYYY-MM-DD HH24:MI:SS"),
+            Some("2020-04-07 12:34:54".to_string())
+        );
+
+        assert_eq!(Date::INFINITY.to_string(), "infinity");
+    }
 }
@@ -0,0 +1,95 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Serialize, Deserialize, PostgresType)]
+pub struct ExpandedCounts {
+    values: Vec<i64>,
+}
+
+#[pg_extern]
+fn expanded_counts_new() -> PgExpanded<ExpandedCounts> {
+    PgExpanded::new(ExpandedCounts::default())
+}
+
+#[pg_extern]
+fn expanded_counts_push(
+    mut counts: PgExpanded<ExpandedCounts>,
+    value: i64,
+) -> PgExpanded<ExpandedCounts> {
+    counts.values.push(value);
+    counts
+}
+
+#[pg_extern]
+fn expanded_counts_sum(counts: PgExpanded<ExpandedCounts>) -> i64 {
+    counts.values.iter().sum()
+}
+
+#[pg_extern]
+fn expanded_counts_len(counts: ExpandedCounts) -> i64 {
+    counts.values.len() as i64
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_expanded_chained_calls() {
+        let sum = Spi::get_one::<i64>(
+            "SELECT expanded_counts_sum(expanded_counts_push(expanded_counts_push(expanded_counts_new(), 1), 2));",
+        );
+        assert_eq!(sum, Some(3));
+    }
+
+    #[pg_test]
+    fn test_expanded_is_flattened_when_stored() {
+        Spi::run("CREATE TABLE expanded_counts_table (c ExpandedCounts);");
+        Spi::run(
+            "INSERT INTO expanded_counts_table VALUES (expanded_counts_push(expanded_counts_push(expanded_counts_new(), 5), 6));",
+        );
+
+        let len = Spi::get_one::<i64>("SELECT expanded_counts_len(c) FROM expanded_counts_table;");
+        assert_eq!(len, Some(2));
+
+        let sum = Spi::get_one::<i64>(
+            "SELECT expanded_counts_sum(expanded_counts_push(c, 7)) FROM expanded_counts_table;",
+        );
+        assert_eq!(sum, Some(18));
+    }
+
+    #[pg_test]
+    fn test_expanded_plpgsql_variable() {
+        Spi::run(
+            "CREATE FUNCTION expanded_counts_plpgsql(n int) RETURNS bigint LANGUAGE plpgsql AS $$
+                DECLARE
+                    c ExpandedCounts := expanded_counts_new();
+                    d ExpandedCounts;
+                BEGIN
+                    FOR i IN 1..n LOOP
+                        c := expanded_counts_push(c, i);
+                    END LOOP;
+
+                    -- a read-only copy of c is pushed to, so c itself is unchanged
+                    d := expanded_counts_push(c, 1000);
+                    RETURN expanded_counts_sum(c) + expanded_counts_sum(d);
+                END;
+            $$;",
+        );
+
+        let sum = Spi::get_one::<i64>("SELECT expanded_counts_plpgsql(100);");
+        assert_eq!(sum, Some(5050 + 5050 + 1000));
+    }
+}
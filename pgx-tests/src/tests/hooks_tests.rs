@@ -7,6 +7,57 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 
+use pgx::*;
+
+/// Records what the hooks `_PG_init()` registers have seen in this backend
+pub(crate) struct AuthHook {
+    connections: Vec<String>,
+    passwords: Vec<String>,
+}
+
+impl PgAuthHooks for AuthHook {
+    fn client_authentication(
+        &mut self,
+        port: PgPort,
+        prev_hook: fn(PgPort) -> HookResult<()>,
+    ) -> HookResult<()> {
+        self.connections.push(format!(
+            "{}|{}|{:?}",
+            port.user_name().unwrap_or_default(),
+            port.database_name().unwrap_or_default(),
+            port.status()
+        ));
+        prev_hook(port)
+    }
+
+    fn check_password(
+        &mut self,
+        username: &str,
+        shadow_pass: &str,
+        password_type: PasswordType,
+        valid_until: Option<TimestampWithTimeZone>,
+        prev_hook: fn(&str, &str, PasswordType, Option<TimestampWithTimeZone>) -> HookResult<()>,
+    ) -> HookResult<()> {
+        if username.starts_with("pgx_auth_") {
+            if password_type == PasswordType::Plaintext && shadow_pass.len() < 8 {
+                error!("password is too short", hint = "use at least 8 characters");
+            }
+            self.passwords.push(format!(
+                "{}|{:?}|{}",
+                username,
+                password_type,
+                valid_until.is_some()
+            ));
+        }
+        prev_hook(username, shadow_pass, password_type, valid_until)
+    }
+}
+
+pub(crate) static mut AUTH_HOOK: AuthHook = AuthHook {
+    connections: Vec::new(),
+    passwords: Vec::new(),
+};
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -99,4 +150,272 @@ mod tests {
         // TODO:  it'd be nice to also test that .commit() and .abort() also get called
         //    but I don't see how to do that since we're running *inside* a transaction here
     }
+
+    #[pg_test]
+    unsafe fn test_emit_log() {
+        struct LogHook {
+            emitted: Vec<String>,
+        }
+        impl PgHooks for LogHook {
+            fn emit_log(&mut self, error_data: &mut ErrorReportMut) {
+                let message = error_data.message().unwrap_or_default().into_owned();
+                if message.starts_with("emit_log") {
+                    self.emitted.push(format!(
+                        "{}|{}|{}",
+                        error_data.sqlstate(),
+                        message,
+                        error_data.hint().unwrap_or_default()
+                    ));
+                    error_data.set_output_to_client(false);
+
+                    // this doesn't come back to the hook
+                    log!("emit_log from the hook");
+                }
+            }
+        }
+
+        static mut HOOK: LogHook = LogHook {
+            emitted: Vec::new(),
+        };
+        pgx::hooks::register_hook(&mut HOOK);
+        warning!("emit_log warning", hint = "emit_log hint");
+        notice!("not emit_log");
+        assert_eq!(
+            HOOK.emitted,
+            vec!["01000|emit_log warning|emit_log hint".to_string()]
+        );
+    }
+
+    #[pg_test]
+    unsafe fn test_emit_log_panic() {
+        struct PanicHook {
+            calls: usize,
+        }
+        impl PgHooks for PanicHook {
+            fn emit_log(&mut self, error_data: &mut ErrorReportMut) {
+                if error_data.message().unwrap_or_default() == "emit_log panic" {
+                    self.calls += 1;
+                    panic!("panic in emit_log");
+                }
+            }
+        }
+
+        static mut HOOK: PanicHook = PanicHook { calls: 0 };
+        pgx::hooks::register_hook(&mut HOOK);
+
+        // the panic doesn't turn the warning into an ERROR
+        warning!("emit_log panic");
+        warning!("emit_log panic");
+        assert_eq!(HOOK.calls, 2);
+        assert_eq!(Spi::get_one::<i32>("SELECT 1"), Some(1));
+    }
+
+    #[pg_test]
+    unsafe fn test_client_authentication() {
+        let user = Spi::get_one::<String>("SELECT current_user::text").unwrap();
+        let database = Spi::get_one::<String>("SELECT current_database()::text").unwrap();
+        assert_eq!(
+            super::AUTH_HOOK.connections,
+            vec![format!("{}|{}|Ok", user, database)]
+        );
+    }
+
+    #[pg_test]
+    unsafe fn test_check_password() {
+        Spi::run("CREATE ROLE pgx_auth_plaintext PASSWORD 'long enough' VALID UNTIL '2100-01-01'");
+        Spi::run("CREATE ROLE pgx_auth_md5 PASSWORD 'md5d41d8cd98f00b204e9800998ecf8427e'");
+        assert_eq!(
+            super::AUTH_HOOK.passwords,
+            vec![
+                "pgx_auth_plaintext|Plaintext|true".to_string(),
+                "pgx_auth_md5|Md5|false".to_string()
+            ]
+        );
+    }
+
+    #[pg_test]
+    #[should_panic(expected = "password is too short")]
+    fn test_check_password_rejects() {
+        Spi::run("CREATE ROLE pgx_auth_short PASSWORD 'short'");
+    }
+
+    #[pg_test]
+    unsafe fn test_object_access() {
+        struct AccessHook {
+            events: Vec<String>,
+        }
+        impl PgHooks for AccessHook {
+            fn object_access(
+                &mut self,
+                event: ObjectAccessEvent,
+                object: PgObjectAddress,
+                prev_hook: fn(
+                    ObjectAccessEvent,
+                    PgObjectAddress,
+                ) -> HookResult<Result<(), PgError>>,
+            ) -> HookResult<Result<(), PgError>> {
+                if object.class() == pg_sys::ObjectClass_OCLASS_CLASS {
+                    match event {
+                        // the table isn't visible until the next command
+                        ObjectAccessEvent::PostCreate { .. } => self
+                            .events
+                            .push(format!("PostCreate|{}", object.object_id())),
+                        ObjectAccessEvent::Drop { .. } => self.events.push(format!(
+                            "Drop|{}|{}",
+                            object.object_type().unwrap(),
+                            object.identity().unwrap()
+                        )),
+                        _ => {}
+                    }
+                }
+                prev_hook(event, object)
+            }
+        }
+
+        static mut HOOK: AccessHook = AccessHook { events: Vec::new() };
+        pgx::hooks::register_hook(&mut HOOK);
+        Spi::run("CREATE TABLE public.object_access_table ()");
+        let oid = Spi::get_one::<pg_sys::Oid>("SELECT 'public.object_access_table'::regclass::oid")
+            .unwrap();
+        Spi::run("DROP TABLE public.object_access_table");
+        assert_eq!(
+            HOOK.events,
+            vec![
+                format!("PostCreate|{}", oid),
+                "Drop|table|public.object_access_table".to_string()
+            ]
+        );
+    }
+
+    #[pg_test]
+    #[should_panic(expected = "lower() is sandboxed")]
+    unsafe fn test_object_access_denied() {
+        struct SandboxHook;
+        impl PgHooks for SandboxHook {
+            fn object_access(
+                &mut self,
+                event: ObjectAccessEvent,
+                object: PgObjectAddress,
+                prev_hook: fn(
+                    ObjectAccessEvent,
+                    PgObjectAddress,
+                ) -> HookResult<Result<(), PgError>>,
+            ) -> HookResult<Result<(), PgError>> {
+                if event == ObjectAccessEvent::FunctionExecute
+                    && object.identity().as_deref() == Some("pg_catalog.lower(text)")
+                {
+                    return HookResult::new(Err(PgError::new(
+                        PgSqlErrorCode::ERRCODE_INSUFFICIENT_PRIVILEGE,
+                        "lower() is sandboxed",
+                    )));
+                }
+                prev_hook(event, object)
+            }
+        }
+
+        static mut HOOK: SandboxHook = SandboxHook;
+        pgx::hooks::register_hook(&mut HOOK);
+        Spi::get_one::<String>("SELECT lower('PGX')");
+    }
+
+    #[pg_test]
+    unsafe fn test_object_access_namespace_search() {
+        struct HiddenSchemaHook;
+        impl PgHooks for HiddenSchemaHook {
+            fn object_access(
+                &mut self,
+                event: ObjectAccessEvent,
+                object: PgObjectAddress,
+                prev_hook: fn(
+                    ObjectAccessEvent,
+                    PgObjectAddress,
+                ) -> HookResult<Result<(), PgError>>,
+            ) -> HookResult<Result<(), PgError>> {
+                if let ObjectAccessEvent::NamespaceSearch { .. } = event {
+                    if object.identity().as_deref() == Some("object_access_hidden") {
+                        return HookResult::new(Err(PgError::new(
+                            PgSqlErrorCode::ERRCODE_INSUFFICIENT_PRIVILEGE,
+                            "object_access_hidden is hidden",
+                        )));
+                    }
+                }
+                prev_hook(event, object)
+            }
+        }
+
+        Spi::run("CREATE SCHEMA object_access_hidden");
+        Spi::run("CREATE TABLE object_access_hidden.hidden_table ()");
+        Spi::run("SET LOCAL search_path TO object_access_hidden, public");
+        static mut HOOK: HiddenSchemaHook = HiddenSchemaHook;
+        pgx::hooks::register_hook(&mut HOOK);
+
+        // the search path quietly skips the schema
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT to_regclass('hidden_table') IS NULL"),
+            Some(true)
+        );
+    }
+
+    #[pg_test]
+    unsafe fn test_post_parse_analyze() {
+        struct AnalyzeHook {
+            queries: Vec<(u64, String)>,
+        }
+        impl PgHooks for AnalyzeHook {
+            fn post_parse_analyze(
+                &mut self,
+                parse_state: PgBox<pg_sys::ParseState>,
+                mut query: PgBox<pg_sys::Query>,
+                jumbled_query: Option<JumbledQuery>,
+                prev_hook: fn(
+                    PgBox<pg_sys::ParseState>,
+                    PgBox<pg_sys::Query>,
+                    Option<JumbledQuery>,
+                ) -> HookResult<()>,
+            ) -> HookResult<()> {
+                let source_text = unsafe { std::ffi::CStr::from_ptr(parse_state.p_sourcetext) };
+                if source_text
+                    .to_bytes()
+                    .starts_with(b"/* post_parse_analyze */")
+                {
+                    // Postgres 14 jumbles it as `_PG_init()` enabled query IDs
+                    let query_id = query.queryId;
+                    let jumbled = jumbled_query
+                        .clone()
+                        .or_else(|| jumble_query(&mut query, source_text))
+                        .unwrap();
+                    // setting the query ID is up to the caller
+                    assert_eq!(query.queryId, query_id);
+                    self.queries
+                        .push((jumbled.query_id(), jumbled.normalize(source_text)));
+                }
+                prev_hook(parse_state, query, jumbled_query)
+            }
+        }
+
+        static mut HOOK: AnalyzeHook = AnalyzeHook {
+            queries: Vec::new(),
+        };
+        pgx::hooks::register_hook(&mut HOOK);
+        Spi::run("/* post_parse_analyze */ SELECT 1 + 2");
+        Spi::run("/* post_parse_analyze */ SELECT 40 + 2 ");
+        Spi::run("/* post_parse_analyze */ SELECT 1 - 2");
+        Spi::get_one_with_args::<String>(
+            "/* post_parse_analyze */ SELECT $1 || 'b' || -1",
+            vec![(PgBuiltInOids::TEXTOID.oid(), "a".into_datum())],
+        );
+
+        let (query_ids, normalized): (Vec<_>, Vec<_>) = HOOK.queries.iter().cloned().unzip();
+        assert_eq!(query_ids[0], query_ids[1]);
+        assert_ne!(query_ids[0], query_ids[2]);
+        assert_eq!(
+            normalized,
+            vec![
+                "/* post_parse_analyze */ SELECT $1 + $2",
+                "/* post_parse_analyze */ SELECT $1 + $2",
+                "/* post_parse_analyze */ SELECT $1 - $2",
+                "/* post_parse_analyze */ SELECT $1 || $2 || $3",
+            ]
+        );
+    }
 }
@@ -6,6 +6,99 @@ All rights reserved.
 
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
+use pgx::*;
+
+#[pg_extern]
+fn jsonb_ref_get_str(input: JsonBRef, key: &str) -> Option<String> {
+    input.get(key)?.as_str().map(|s| s.to_string())
+}
+
+#[pg_extern]
+fn jsonb_ref_sum(input: JsonBRef) -> f64 {
+    input.iter().filter_map(|v| v.as_f64()).sum()
+}
+
+#[pg_extern]
+fn jsonb_ref_keys(input: JsonBRef) -> Vec<String> {
+    input.keys().map(|k| k.to_string()).collect()
+}
+
+#[pg_extern]
+fn jsonb_ref_build(name: &str, count: i64) -> JsonbBuilder {
+    let mut builder = JsonbBuilder::new();
+    builder
+        .begin_object()
+        .key("name")
+        .string(name)
+        .key("counts")
+        .begin_array()
+        .i64(count)
+        .f64(1.5)
+        .null()
+        .end_array()
+        .key("ok")
+        .bool(true)
+        .end_object();
+    assert_eq!(
+        builder.build().get("name").and_then(|v| v.as_str()),
+        Some(name)
+    );
+    builder
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct JsonUser {
+    username: String,
+    age: i32,
+}
+
+#[pg_extern]
+fn jsonb_of_username(user: JsonBOf<JsonUser>) -> String {
+    user.0.username
+}
+
+#[pg_extern]
+fn jsonb_of_birthday(user: JsonBOf<JsonUser>) -> JsonBOf<JsonUser> {
+    JsonBOf(JsonUser {
+        username: user.0.username,
+        age: user.0.age + 1,
+    })
+}
+
+#[derive(serde::Deserialize)]
+pub enum JsonRole {
+    Admin,
+    Guest { since: i64 },
+}
+
+#[derive(serde::Deserialize)]
+pub struct JsonProfile {
+    tags: Vec<String>,
+    nickname: Option<String>,
+    role: JsonRole,
+    score: f64,
+}
+
+#[pg_extern]
+fn jsonb_of_profile(profile: JsonBOf<JsonProfile>) -> String {
+    let profile = profile.0;
+    let role = match profile.role {
+        JsonRole::Admin => "admin".to_string(),
+        JsonRole::Guest { since } => format!("guest since {}", since),
+    };
+    format!(
+        "{} {:?} {} {}",
+        profile.tags.join(","),
+        profile.nickname,
+        role,
+        profile.score
+    )
+}
+
+#[pg_extern]
+fn json_of_age(user: Option<JsonOf<JsonUser>>) -> Option<i32> {
+    user.map(|user| user.0.age)
+}
 
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
@@ -60,4 +153,79 @@ mod tests {
         assert_eq!(user.first_name, "Blah");
         assert_eq!(user.last_name, "McBlahFace");
     }
+
+    #[pg_test]
+    fn test_jsonb_ref_navigation() {
+        let name = Spi::get_one::<String>(
+            r#"SELECT jsonb_ref_get_str('{"a": 1, "name": "pgx"}'::jsonb, 'name');"#,
+        );
+        assert_eq!(name, Some("pgx".to_string()));
+
+        let missing =
+            Spi::get_one::<String>(r#"SELECT jsonb_ref_get_str('{"a": 1}'::jsonb, 'name');"#);
+        assert_eq!(missing, None);
+
+        let sum = Spi::get_one::<f64>(r#"SELECT jsonb_ref_sum('[1, 2.5, "x", [4]]'::jsonb);"#);
+        assert_eq!(sum, Some(3.5));
+
+        let keys = Spi::get_one::<Vec<String>>(
+            r#"SELECT jsonb_ref_keys('{"b": {"nested": true}, "a": [1]}'::jsonb);"#,
+        );
+        assert_eq!(keys, Some(vec!["a".to_string(), "b".to_string()]));
+    }
+
+    #[pg_test]
+    fn test_jsonb_ref_scalar() {
+        let sum = Spi::get_one::<f64>("SELECT jsonb_ref_sum('42'::jsonb);");
+        assert_eq!(sum, Some(0.0));
+
+        let name = Spi::get_one::<String>(r#"SELECT jsonb_ref_get_str('"a"'::jsonb, 'a');"#);
+        assert_eq!(name, None);
+    }
+
+    #[pg_test]
+    fn test_jsonb_builder() {
+        let json = Spi::get_one::<String>("SELECT jsonb_ref_build('pgx', 7)::text;");
+        assert_eq!(
+            json,
+            Some(r#"{"ok": true, "name": "pgx", "counts": [7, 1.5, null]}"#.to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_jsonb_of() {
+        let username = Spi::get_one::<String>(
+            r#"SELECT jsonb_of_username('{"username": "pgx", "age": 3}'::jsonb);"#,
+        );
+        assert_eq!(username, Some("pgx".to_string()));
+
+        let json = Spi::get_one::<String>(
+            r#"SELECT jsonb_of_birthday('{"username": "pgx", "age": 3}'::jsonb)::text;"#,
+        );
+        assert_eq!(json, Some(r#"{"age": 4, "username": "pgx"}"#.to_string()));
+
+        let age = Spi::get_one::<i32>(r#"SELECT json_of_age('{"username": "pgx", "age": 3}');"#);
+        assert_eq!(age, Some(3));
+    }
+
+    #[pg_test]
+    fn test_jsonb_of_nested() {
+        let summary = Spi::get_one::<String>(
+            r#"SELECT jsonb_of_profile('{"tags": ["a", "b"], "nickname": null, "role": "Admin", "score": 1.5}');"#,
+        );
+        assert_eq!(summary, Some("a,b None admin 1.5".to_string()));
+
+        let summary = Spi::get_one::<String>(
+            r#"SELECT jsonb_of_profile('{"tags": [], "nickname": "x", "role": {"Guest": {"since": 2020}}, "score": 2}');"#,
+        );
+        assert_eq!(
+            summary,
+            Some(r#" Some("x") guest since 2020 2"#.to_string())
+        );
+    }
+
+    #[pg_test(error = "invalid input syntax for type jsonb: missing field `age`")]
+    fn test_jsonb_of_invalid() {
+        Spi::get_one::<String>(r#"SELECT jsonb_of_username('{"username": "pgx"}'::jsonb);"#);
+    }
 }
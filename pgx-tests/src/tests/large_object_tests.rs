@@ -0,0 +1,104 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+use std::io::Read;
+
+#[pg_extern]
+fn large_object_to_string(id: LargeObjectId) -> String {
+    let mut lo = LargeObject::open(id, LargeObjectMode::Read);
+    let mut s = String::new();
+    lo.read_to_string(&mut s).unwrap();
+    s
+}
+
+#[pg_extern]
+fn large_object_with(contents: &str) -> LargeObjectId {
+    use std::io::Write;
+
+    let id = LargeObject::create();
+    LargeObject::open(id, LargeObjectMode::Write)
+        .write_all(contents.as_bytes())
+        .unwrap();
+    id
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    #[pg_test]
+    fn test_large_object_roundtrip() {
+        let id = LargeObject::create();
+
+        let mut lo = LargeObject::open(id, LargeObjectMode::Write);
+        lo.write_all(b"hello, world").unwrap();
+        assert_eq!(lo.stream_position().unwrap(), 12);
+
+        assert_eq!(lo.seek(SeekFrom::Start(7)).unwrap(), 7);
+        let mut buf = Vec::new();
+        lo.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, b"world");
+
+        assert_eq!(lo.seek(SeekFrom::End(-12)).unwrap(), 0);
+        lo.write_all(b"HELLO").unwrap();
+        assert_eq!(lo.seek(SeekFrom::Current(-5)).unwrap(), 0);
+        let mut s = String::new();
+        lo.read_to_string(&mut s).unwrap();
+        assert_eq!(s, "HELLO, world");
+    }
+
+    #[pg_test]
+    fn test_large_object_truncate() {
+        let id = LargeObject::create();
+
+        let mut lo = LargeObject::open(id, LargeObjectMode::Write);
+        lo.write_all(b"hello, world").unwrap();
+        lo.truncate(5);
+        assert_eq!(lo.seek(SeekFrom::End(0)).unwrap(), 5);
+    }
+
+    #[pg_test]
+    fn test_large_object_id_datum() {
+        let s = Spi::get_one::<String>("SELECT large_object_to_string(lo_from_bytea(0, 'abc'));");
+        assert_eq!(s, Some("abc".to_string()));
+
+        let s = Spi::get_one::<Vec<u8>>("SELECT lo_get(large_object_with('xyz'));");
+        assert_eq!(s, Some(b"xyz".to_vec()));
+    }
+
+    #[pg_test]
+    fn test_large_object_unlink() {
+        let id = LargeObject::create();
+        LargeObject::unlink(id);
+
+        let exists = Spi::get_one::<bool>(&format!(
+            "SELECT EXISTS(SELECT 1 FROM pg_largeobject_metadata WHERE oid = {});",
+            id.0
+        ));
+        assert_eq!(exists, Some(false));
+    }
+
+    #[pg_test(error = "large object 4294967295 does not exist")]
+    fn test_large_object_unlink_missing() {
+        LargeObject::unlink(LargeObjectId(u32::MAX));
+    }
+
+    #[pg_test(error = "permission denied for large object 424242")]
+    fn test_large_object_read_only() {
+        let id = Spi::get_one::<LargeObjectId>("SELECT lo_create(424242);").unwrap();
+        LargeObject::open(id, LargeObjectMode::Read)
+            .write_all(b"nope")
+            .unwrap();
+    }
+}
@@ -6,6 +6,73 @@ All rights reserved.
 
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
+use pgx::*;
+
+extension_sql!(
+    r#"
+CREATE FUNCTION log_error_fields(query text) RETURNS text[] LANGUAGE plpgsql AS $$
+DECLARE
+    fields text[];
+BEGIN
+    EXECUTE query;
+    RETURN NULL;
+EXCEPTION WHEN OTHERS THEN
+    GET STACKED DIAGNOSTICS
+        fields[1] = RETURNED_SQLSTATE,
+        fields[2] = MESSAGE_TEXT,
+        fields[3] = PG_EXCEPTION_DETAIL,
+        fields[4] = PG_EXCEPTION_HINT,
+        fields[5] = SCHEMA_NAME,
+        fields[6] = TABLE_NAME,
+        fields[7] = COLUMN_NAME,
+        fields[8] = PG_DATATYPE_NAME,
+        fields[9] = CONSTRAINT_NAME,
+        fields[10] = PG_EXCEPTION_CONTEXT;
+    RETURN fields;
+END;
+$$;
+"#,
+    name = "create_log_error_fields"
+);
+
+#[pg_extern]
+fn log_error_report() {
+    PgErrorReport::new(PgSqlErrorCode::ERRCODE_CHECK_VIOLATION, "report error")
+        .with_detail("report detail")
+        .with_hint("report hint")
+        .with_context("report context")
+        .with_schema_name("report_schema")
+        .with_table_name("report_table")
+        .with_column_name("report_column")
+        .with_datatype_name("report_type")
+        .with_constraint_name("report_constraint")
+        .report(PgLogLevel::ERROR);
+}
+
+#[pg_extern]
+fn log_error_detail_and_hint(value: i32) {
+    error!(
+        "error {}",
+        value,
+        detail = format!("detail {}", value),
+        hint = "error hint",
+    );
+}
+
+#[pg_extern]
+fn log_error_hint_and_detail(value: i32) {
+    error!(
+        "error {}",
+        value,
+        hint = "error hint",
+        detail = "error detail"
+    );
+}
+
+#[pg_extern]
+fn log_error_hint(value: i32) {
+    error!("error {value}", value = value, hint = "error hint");
+}
 
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
@@ -86,4 +153,120 @@ mod tests {
     fn test_panic() {
         panic!("panic message")
     }
+
+    #[pg_test]
+    fn test_warning_detail_and_hint() {
+        warning!(
+            "warn {}",
+            "message",
+            detail = "warn detail",
+            hint = "warn hint"
+        );
+        warning!("warn message", hint = "warn hint");
+        warning!("warn {}", 1, hint = "warn hint", detail = "warn detail");
+    }
+
+    #[pg_test]
+    fn test_warning_long_argument_list() {
+        let v = [1, 2, 3, 4];
+        warning!(
+            "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {w}",
+            v[0] + v[1] * v[2] - v[3],
+            v[1] + v[2] * v[3] - v[0],
+            v[2] + v[3] * v[0] - v[1],
+            v[3] + v[0] * v[1] - v[2],
+            v[0] + v[1] * v[2] - v[3],
+            v[1] + v[2] * v[3] - v[0],
+            v[2] + v[3] * v[0] - v[1],
+            v[3] + v[0] * v[1] - v[2],
+            v[0] + v[1] * v[2] - v[3],
+            v[1] + v[2] * v[3] - v[0],
+            v[2] + v[3] * v[0] - v[1],
+            v[3] + v[0] * v[1] - v[2],
+            v[0] + v[1] * v[2] - v[3],
+            v[1] + v[2] * v[3] - v[0],
+            v[2] + v[3] * v[0] - v[1],
+            v[3] + v[0] * v[1] - v[2],
+            v[0] + v[1] * v[2] - v[3],
+            v[1] + v[2] * v[3] - v[0],
+            v[2] + v[3] * v[0] - v[1],
+            v[3] + v[0] * v[1] - v[2],
+            v[0] + v[1] * v[2] - v[3],
+            v[1] + v[2] * v[3] - v[0],
+            v[2] + v[3] * v[0] - v[1],
+            v[3] + v[0] * v[1] - v[2],
+            w = v.iter().map(|i| i * 2).sum::<i32>(),
+            detail = format!("{} values", v.len()),
+            hint = "warn hint",
+        );
+    }
+
+    #[pg_test]
+    fn test_warning_named_detail_argument() {
+        // not the last argument, so it's a format argument rather than the DETAIL
+        warning!("{detail} {n}", detail = "named detail", n = 1);
+    }
+
+    #[pg_test]
+    fn test_report_warning() {
+        PgErrorReport::new(PgSqlErrorCode::ERRCODE_WARNING, "report warning")
+            .with_detail("report detail")
+            .report(PgLogLevel::WARNING);
+    }
+
+    #[pg_test]
+    fn test_report_fields() {
+        let fields =
+            Spi::get_one::<Vec<String>>("SELECT log_error_fields('SELECT log_error_report()')")
+                .unwrap();
+        assert_eq!(
+            &fields[..9],
+            &[
+                "23514",
+                "report error",
+                "report detail",
+                "report hint",
+                "report_schema",
+                "report_table",
+                "report_column",
+                "report_type",
+                "report_constraint"
+            ]
+        );
+        assert!(fields[9].contains("report context"), "{}", fields[9]);
+    }
+
+    #[pg_test]
+    fn test_error_detail_and_hint() {
+        let fields = Spi::get_one::<Vec<String>>(
+            "SELECT log_error_fields('SELECT log_error_detail_and_hint(42)')",
+        )
+        .unwrap();
+        assert_eq!(
+            &fields[..4],
+            &["XX000", "error 42", "detail 42", "error hint"]
+        );
+
+        let fields =
+            Spi::get_one::<Vec<String>>("SELECT log_error_fields('SELECT log_error_hint(42)')")
+                .unwrap();
+        assert_eq!(&fields[..4], &["XX000", "error 42", "", "error hint"]);
+    }
+
+    #[pg_test]
+    fn test_error_hint_and_detail() {
+        let fields = Spi::get_one::<Vec<String>>(
+            "SELECT log_error_fields('SELECT log_error_hint_and_detail(42)')",
+        )
+        .unwrap();
+        assert_eq!(
+            &fields[..4],
+            &["XX000", "error 42", "error detail", "error hint"]
+        );
+    }
+
+    #[pg_test(error = "error 42")]
+    fn test_error_with_hint_aborts() {
+        error!("error {}", 42, hint = "error hint");
+    }
 }
@@ -11,33 +11,53 @@ mod aggregate_tests;
 mod anyarray_tests;
 mod array_tests;
 mod bytea_tests;
+mod cast_tests;
 mod cfg_tests;
 mod datetime_tests;
 mod default_arg_value_tests;
 mod derive_pgtype_lifetimes;
 mod enum_type_tests;
+mod expanded_tests;
 mod fcinfo_tests;
 mod guc_tests;
 mod hooks_tests;
 mod inet_tests;
 mod internal_tests;
 mod json_tests;
+mod large_object_tests;
 mod lifetime_tests;
 mod log_tests;
 mod memcxt_tests;
 mod name_tests;
 mod numeric_tests;
+mod opclass_tests;
 mod pg_extern_tests;
 mod pg_try_tests;
 mod pgbox_tests;
 mod postgres_type_tests;
+mod record_tests;
+mod result_tests;
 mod schema_tests;
 mod spi_tests;
 mod srf_tests;
+mod storage_tests;
 mod struct_type_tests;
+#[cfg(feature = "pg14")]
+mod subscript_tests;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+mod support_tests;
+mod tsearch_tests;
 mod uuid_tests;
 mod variadic_tests;
+mod window_tests;
 mod xact_callback_tests;
 mod xid64_tests;
 
 pgx::pg_magic_func!();
+
+#[pgx::pg_guard]
+pub extern "C" fn _PG_init() {
+    // `pg_test::postgresql_conf_options()` preloads us, so this is before clients connect
+    unsafe { pgx::hooks::register_auth_hook(&mut hooks_tests::AUTH_HOOK) }
+    pgx::jumble::enable_query_id();
+}
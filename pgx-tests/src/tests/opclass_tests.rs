@@ -0,0 +1,517 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, PostgresType)]
+pub struct Span {
+    start: i32,
+    end: i32,
+}
+
+impl Span {
+    fn overlaps(&self, other: &Span) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    fn contains(&self, other: &Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    fn gap(&self, other: &Span) -> f64 {
+        if self.end < other.start {
+            (other.start - self.end) as f64
+        } else if other.end < self.start {
+            (self.start - other.end) as f64
+        } else {
+            0.0
+        }
+    }
+
+    fn len(&self) -> i32 {
+        self.end - self.start
+    }
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn span(start: i32, end: i32) -> Span {
+    Span { start, end }
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn span_start(span: Span) -> i32 {
+    span.start
+}
+
+#[pg_operator(immutable, parallel_safe)]
+#[opname(&&)]
+fn span_overlaps(left: Span, right: Span) -> bool {
+    left.overlaps(&right)
+}
+
+#[pg_operator(immutable, parallel_safe)]
+#[opname(@>)]
+fn span_contains(left: Span, right: Span) -> bool {
+    left.contains(&right)
+}
+
+#[pg_operator(immutable, parallel_safe)]
+#[opname(<->)]
+fn span_distance(left: Span, right: Span) -> f64 {
+    left.gap(&right)
+}
+
+#[pg_opclass]
+impl GistOpClass for Span {
+    const OPERATORS: &'static [(u16, &'static str)] = &[
+        (3, "&&"),
+        (7, "@>"),
+        (15, "<-> (Span, Span) FOR ORDER BY float_ops"),
+    ];
+    type Key = Span;
+    type Query = Span;
+
+    fn compress(self) -> Span {
+        self
+    }
+
+    fn consistent(key: &Span, query: Span, strategy: u16, _is_leaf: bool) -> IndexMatch {
+        // a key made by `union` covers its subtree, so the same tests work for inner keys
+        match strategy {
+            3 => key.overlaps(&query).into(),
+            7 => key.contains(&query).into(),
+            _ => error!("unsupported Span strategy: {}", strategy),
+        }
+    }
+
+    fn union(keys: &[Span]) -> Span {
+        Span {
+            start: keys.iter().map(|key| key.start).min().unwrap(),
+            end: keys.iter().map(|key| key.end).max().unwrap(),
+        }
+    }
+
+    fn penalty(original: &Span, new: &Span) -> f32 {
+        let grown = Span::union(&[original.clone(), new.clone()]);
+        (grown.len() - original.len()) as f32
+    }
+
+    fn picksplit(keys: &[Span]) -> (Vec<usize>, Vec<usize>) {
+        let mut left = (0..keys.len()).collect::<Vec<_>>();
+        left.sort_by_key(|&i| keys[i].start);
+        let right = left.split_off(left.len() / 2);
+        (left, right)
+    }
+
+    fn same(a: &Span, b: &Span) -> bool {
+        a.start == b.start && a.end == b.end
+    }
+
+    fn distance(key: &Span, query: Span, _strategy: u16, _is_leaf: bool) -> f64 {
+        key.gap(&query)
+    }
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct Tags(Vec<String>);
+
+#[pg_extern(immutable, parallel_safe)]
+fn tags(tags: Vec<String>) -> Tags {
+    Tags(tags)
+}
+
+#[pg_operator(immutable, parallel_safe)]
+#[opname(&&)]
+fn tags_overlaps(left: Tags, right: Tags) -> bool {
+    right.0.iter().any(|tag| left.0.contains(tag))
+}
+
+#[pg_operator(immutable, parallel_safe)]
+#[opname(@>)]
+fn tags_contains(left: Tags, right: Tags) -> bool {
+    right.0.iter().all(|tag| left.0.contains(tag))
+}
+
+#[pg_opclass]
+impl GinOpClass for Tags {
+    const OPERATORS: &'static [(u16, &'static str)] = &[(1, "&&"), (2, "@>")];
+    type Key = String;
+    type Query = Tags;
+
+    fn extract_value(self) -> Vec<String> {
+        let mut tags = self.0;
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    fn extract_query(query: Tags, strategy: u16) -> (Vec<String>, GinSearchMode) {
+        let mode = match strategy {
+            // everything contains no tags
+            2 if query.0.is_empty() => GinSearchMode::All,
+            _ => GinSearchMode::Default,
+        };
+        (query.extract_value(), mode)
+    }
+
+    fn consistent(check: &[bool], strategy: u16) -> IndexMatch {
+        match strategy {
+            1 => check.iter().any(|&found| found).into(),
+            2 => check.iter().all(|&found| found).into(),
+            _ => error!("unsupported Tags strategy: {}", strategy),
+        }
+    }
+
+    fn tri_consistent(check: &[IndexMatch], strategy: u16) -> IndexMatch {
+        let (decisive, otherwise) = match strategy {
+            1 => (IndexMatch::True, IndexMatch::False),
+            2 => (IndexMatch::False, IndexMatch::True),
+            _ => error!("unsupported Tags strategy: {}", strategy),
+        };
+        if check.contains(&decisive) {
+            decisive
+        } else if check.contains(&IndexMatch::Maybe) {
+            IndexMatch::Maybe
+        } else {
+            otherwise
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct Measurement {
+    value: i64,
+}
+
+/// The smallest and largest [`Measurement`] of a BRIN block range
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct MeasurementRange {
+    min: i64,
+    max: i64,
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn measurement(value: i64) -> Measurement {
+    Measurement { value }
+}
+
+#[pg_operator(immutable, parallel_safe)]
+#[opname(<)]
+fn measurement_lt(left: Measurement, right: Measurement) -> bool {
+    left.value < right.value
+}
+
+#[pg_operator(immutable, parallel_safe)]
+#[opname(=)]
+fn measurement_eq(left: Measurement, right: Measurement) -> bool {
+    left.value == right.value
+}
+
+#[pg_operator(immutable, parallel_safe)]
+#[opname(>)]
+fn measurement_gt(left: Measurement, right: Measurement) -> bool {
+    left.value > right.value
+}
+
+#[pg_opclass]
+impl BrinOpClass for Measurement {
+    const OPERATORS: &'static [(u16, &'static str)] = &[(1, "<"), (3, "="), (5, ">")];
+    type Summary = MeasurementRange;
+    type Query = Measurement;
+
+    fn add_value(
+        summary: Option<&MeasurementRange>,
+        value: Measurement,
+    ) -> Option<MeasurementRange> {
+        match summary {
+            Some(summary) if summary.min <= value.value && value.value <= summary.max => None,
+            Some(summary) => Some(MeasurementRange {
+                min: summary.min.min(value.value),
+                max: summary.max.max(value.value),
+            }),
+            None => Some(MeasurementRange {
+                min: value.value,
+                max: value.value,
+            }),
+        }
+    }
+
+    fn consistent(summary: &MeasurementRange, query: Measurement, strategy: u16) -> bool {
+        match strategy {
+            1 => summary.min < query.value,
+            3 => summary.min <= query.value && query.value <= summary.max,
+            5 => summary.max > query.value,
+            _ => error!("unsupported Measurement strategy: {}", strategy),
+        }
+    }
+
+    fn union(a: MeasurementRange, b: MeasurementRange) -> MeasurementRange {
+        MeasurementRange {
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        }
+    }
+}
+
+fn measurement_value(datum: pg_sys::Datum) -> i64 {
+    unsafe { Measurement::from_datum(datum, false, Measurement::type_oid()) }
+        .expect("measurement was null")
+        .value
+}
+
+/// A one-dimensional k-d tree: each inner tuple splits its measurements at their median, into a
+/// node with the smaller and a node with the larger measurements
+#[pg_opclass]
+impl SpGistOpClass for Measurement {
+    const OPERATORS: &'static [(u16, &'static str)] = &[(3, "=")];
+
+    fn config(_input: &pg_sys::spgConfigIn, output: &mut pg_sys::spgConfigOut) {
+        output.prefixType = pg_sys::INT8OID;
+        output.labelType = pg_sys::VOIDOID;
+        output.canReturnData = false;
+        output.longValuesOK = false;
+    }
+
+    fn choose(input: &pg_sys::spgChooseIn, output: &mut pg_sys::spgChooseOut) {
+        let node = if input.allTheSame {
+            0
+        } else {
+            let median = unsafe { i64::from_datum(input.prefixDatum, false, pg_sys::INT8OID) }
+                .expect("median was null");
+            (measurement_value(input.datum) >= median) as i32
+        };
+        output.resultType = pg_sys::spgChooseResultType_spgMatchNode;
+        unsafe {
+            output.result.matchNode.nodeN = node;
+            output.result.matchNode.levelAdd = 0;
+            output.result.matchNode.restDatum = input.datum;
+        }
+    }
+
+    fn picksplit(input: &pg_sys::spgPickSplitIn, output: &mut pg_sys::spgPickSplitOut) {
+        let datums = unsafe { std::slice::from_raw_parts(input.datums, input.nTuples as usize) };
+        let mut values = datums
+            .iter()
+            .map(|&datum| measurement_value(datum))
+            .collect::<Vec<_>>();
+        values.sort_unstable();
+        let median = values[values.len() / 2];
+
+        let mut context = PgMemoryContexts::CurrentMemoryContext;
+        let nodes = context.palloc_slice::<i32>(datums.len());
+        let leaves = context.palloc_slice::<pg_sys::Datum>(datums.len());
+        for ((node, leaf), &datum) in nodes.iter_mut().zip(leaves.iter_mut()).zip(datums) {
+            *node = (measurement_value(datum) >= median) as i32;
+            *leaf = datum;
+        }
+
+        output.hasPrefix = true;
+        output.prefixDatum = median.into_datum().unwrap();
+        output.nNodes = 2;
+        output.nodeLabels = std::ptr::null_mut();
+        output.mapTuplesToNodes = nodes.as_mut_ptr();
+        output.leafTupleDatums = leaves.as_mut_ptr();
+    }
+
+    fn inner_consistent(
+        input: &pg_sys::spgInnerConsistentIn,
+        output: &mut pg_sys::spgInnerConsistentOut,
+    ) {
+        let mut visit = vec![true; input.nNodes as usize];
+        if !input.allTheSame {
+            let median = unsafe { i64::from_datum(input.prefixDatum, false, pg_sys::INT8OID) }
+                .expect("median was null");
+            let keys = unsafe { std::slice::from_raw_parts(input.scankeys, input.nkeys as usize) };
+            for key in keys {
+                let node = (measurement_value(key.sk_argument) >= median) as usize;
+                visit[1 - node] = false;
+            }
+        }
+
+        let nodes = (0..input.nNodes)
+            .filter(|&node| visit[node as usize])
+            .collect::<Vec<_>>();
+        let node_numbers =
+            PgMemoryContexts::CurrentMemoryContext.palloc_slice::<i32>(nodes.len().max(1));
+        node_numbers[..nodes.len()].copy_from_slice(&nodes);
+        output.nNodes = nodes.len() as i32;
+        output.nodeNumbers = node_numbers.as_mut_ptr();
+    }
+
+    fn leaf_consistent(
+        input: &pg_sys::spgLeafConsistentIn,
+        output: &mut pg_sys::spgLeafConsistentOut,
+    ) -> bool {
+        output.recheck = false;
+        let value = measurement_value(input.leafDatum);
+        let keys = unsafe { std::slice::from_raw_parts(input.scankeys, input.nkeys as usize) };
+        keys.iter()
+            .all(|key| measurement_value(key.sk_argument) == value)
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    /// The `EXPLAIN` of `query`
+    fn explain(query: &str) -> String {
+        Spi::connect(|client| {
+            let plan = client
+                .select(&format!("EXPLAIN (COSTS OFF) {}", query), None, None)
+                .filter_map(|row| row.get_datum::<String>(1))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Ok(Some(plan))
+        })
+        .unwrap()
+    }
+
+    #[pg_test]
+    fn test_opclass_catalog() {
+        let opclasses = Spi::get_one::<i64>(
+            "SELECT count(*) FROM pg_opclass WHERE opcdefault AND opcname IN \
+             ('span_gist_ops', 'tags_gin_ops', 'measurement_brin_ops', 'measurement_spgist_ops')",
+        );
+        assert_eq!(opclasses, Some(4));
+
+        // only the implemented optional support functions are declared
+        let support_fns = |opclass: &str| {
+            Spi::get_one::<i64>(&format!(
+                "SELECT count(*) FROM pg_amproc JOIN pg_opclass ON amprocfamily = opcfamily \
+                 WHERE opcname = '{}'",
+                opclass
+            ))
+        };
+        assert_eq!(support_fns("span_gist_ops"), Some(8));
+        assert_eq!(support_fns("tags_gin_ops"), Some(5));
+        assert_eq!(support_fns("measurement_spgist_ops"), Some(5));
+        assert_eq!(support_fns("measurement_brin_ops"), Some(4));
+
+        let storage = |opclass: &str| {
+            Spi::get_one::<String>(&format!(
+                "SELECT opckeytype::regtype::text FROM pg_opclass WHERE opcname = '{}'",
+                opclass
+            ))
+        };
+        assert_eq!(storage("tags_gin_ops"), Some("text".to_string()));
+        assert_eq!(
+            storage("measurement_brin_ops"),
+            Some("measurementrange".to_string())
+        );
+        assert_eq!(storage("span_gist_ops"), Some("-".to_string()));
+    }
+
+    #[pg_test]
+    fn test_gist_opclass() {
+        Spi::run(
+            "
+            CREATE TABLE spans (span Span);
+            INSERT INTO spans SELECT span(i, i + 5) FROM generate_series(1, 2000) i;
+            CREATE INDEX spans_idx ON spans USING gist (span);
+            ANALYZE spans;
+            SET LOCAL enable_seqscan = off;
+            ",
+        );
+
+        let overlapping = "SELECT count(*) FROM spans WHERE span && span(100, 110)";
+        assert!(explain(overlapping).contains("spans_idx"));
+        assert_eq!(Spi::get_one::<i64>(overlapping), Some(16));
+
+        let containing = "SELECT count(*) FROM spans WHERE span @> span(100, 102)";
+        assert!(explain(containing).contains("spans_idx"));
+        assert_eq!(Spi::get_one::<i64>(containing), Some(4));
+
+        let nearest =
+            "SELECT span_start(span) FROM spans ORDER BY span <-> span(3000, 3000) LIMIT 1";
+        assert!(explain(nearest).contains("spans_idx"));
+        assert_eq!(Spi::get_one::<i32>(nearest), Some(2000));
+    }
+
+    #[pg_test]
+    fn test_gin_opclass() {
+        Spi::run(
+            "
+            CREATE TABLE tagged (tags Tags);
+            INSERT INTO tagged
+                SELECT tags(ARRAY['t' || (i % 10), 'u' || (i % 7)])
+                FROM generate_series(1, 1000) i;
+            CREATE INDEX tagged_idx ON tagged USING gin (tags);
+            ANALYZE tagged;
+            SET LOCAL enable_seqscan = off;
+            ",
+        );
+
+        let containing = "SELECT count(*) FROM tagged WHERE tags @> tags(ARRAY['t3'])";
+        assert!(explain(containing).contains("tagged_idx"));
+        assert_eq!(Spi::get_one::<i64>(containing), Some(100));
+
+        let overlapping = "SELECT count(*) FROM tagged WHERE tags && tags(ARRAY['t3', 'u2'])";
+        assert!(explain(overlapping).contains("tagged_idx"));
+        assert_eq!(Spi::get_one::<i64>(overlapping), Some(229));
+
+        let both = "SELECT count(*) FROM tagged WHERE tags @> tags(ARRAY['t3', 'u2'])";
+        assert_eq!(Spi::get_one::<i64>(both), Some(14));
+
+        let everything = "SELECT count(*) FROM tagged WHERE tags @> tags(ARRAY[]::text[])";
+        assert_eq!(Spi::get_one::<i64>(everything), Some(1000));
+    }
+
+    #[pg_test]
+    fn test_spgist_opclass() {
+        Spi::run(
+            "
+            CREATE TABLE measurements (m Measurement);
+            INSERT INTO measurements SELECT measurement(i % 500) FROM generate_series(1, 2000) i;
+            CREATE INDEX measurements_idx ON measurements USING spgist (m);
+            ANALYZE measurements;
+            SET LOCAL enable_seqscan = off;
+            ",
+        );
+
+        let equal = "SELECT count(*) FROM measurements WHERE m = measurement(123)";
+        assert!(explain(equal).contains("measurements_idx"));
+        assert_eq!(Spi::get_one::<i64>(equal), Some(4));
+
+        let missing = "SELECT count(*) FROM measurements WHERE m = measurement(500)";
+        assert_eq!(Spi::get_one::<i64>(missing), Some(0));
+    }
+
+    #[pg_test]
+    fn test_brin_opclass() {
+        Spi::run(
+            "
+            CREATE TABLE measurements (m Measurement);
+            INSERT INTO measurements SELECT measurement(i) FROM generate_series(1, 10000) i;
+            INSERT INTO measurements VALUES (NULL);
+            CREATE INDEX measurements_idx ON measurements
+                USING brin (m) WITH (pages_per_range = 1);
+            ANALYZE measurements;
+            SET LOCAL enable_seqscan = off;
+            ",
+        );
+
+        let equal = "SELECT count(*) FROM measurements WHERE m = measurement(4242)";
+        assert!(explain(equal).contains("measurements_idx"));
+        assert_eq!(Spi::get_one::<i64>(equal), Some(1));
+
+        let less = "SELECT count(*) FROM measurements WHERE m < measurement(11)";
+        assert_eq!(Spi::get_one::<i64>(less), Some(10));
+
+        let greater = "SELECT count(*) FROM measurements WHERE m > measurement(9990)";
+        assert_eq!(Spi::get_one::<i64>(greater), Some(10));
+
+        let null = "SELECT count(*) FROM measurements WHERE m IS NULL";
+        assert_eq!(Spi::get_one::<i64>(null), Some(1));
+    }
+}
@@ -100,4 +100,99 @@ mod tests {
     fn test_pg_try_unwrap_or_rethrow_with_error_in_rethrow() {
         pg_try(|| panic!("rethrow a panic")).unwrap_or_rethrow(|| panic!("panic in rethrow"));
     }
+
+    fn no_such_role() -> pg_sys::Oid {
+        unsafe { pg_sys::get_role_oid("pgx_no_such_role\0".as_ptr() as _, false) }
+    }
+
+    #[pg_test]
+    fn test_pg_try_catch_sqlstate() {
+        let report = unsafe {
+            pg_try(no_such_role)
+                .map(|_| None)
+                .catch_sqlstate(PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION, |_| None)
+                .catch_sqlstate(PgSqlErrorCode::ERRCODE_UNDEFINED_OBJECT, Some)
+                .unwrap()
+        }
+        .expect("role lookup didn't raise an ERROR");
+
+        assert_eq!(report.sqlstate(), "42704");
+        assert_eq!(
+            report.sqlerrcode(),
+            PgSqlErrorCode::ERRCODE_UNDEFINED_OBJECT as i32
+        );
+        assert_eq!(report.elevel(), pg_sys::ERROR as i32);
+        assert_eq!(report.message(), "role \"pgx_no_such_role\" does not exist");
+        assert_eq!(report.detail(), None);
+
+        // the ERROR was flushed, so Postgres carries on
+        assert_eq!(Spi::get_one::<i32>("SELECT 42"), Some(42));
+    }
+
+    #[pg_test(error = "role \"pgx_no_such_role\" does not exist")]
+    fn test_pg_try_catch_sqlstate_rethrows_others() {
+        unsafe {
+            pg_try(no_such_role)
+                .catch_sqlstate(PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION, |_| 0)
+                .unwrap();
+        }
+    }
+
+    #[pg_test]
+    fn test_pg_try_subtransaction_catch_sqlstate() {
+        Spi::run("CREATE TABLE pg_try_users (name text PRIMARY KEY)");
+        let insert = || Spi::run("INSERT INTO pg_try_users (name) VALUES ('alice')");
+
+        let inserted = unsafe {
+            pg_try_subtransaction(insert)
+                .map(|_| true)
+                .catch_sqlstate(PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION, |_| false)
+                .unwrap()
+        };
+        assert!(inserted);
+
+        let report = unsafe {
+            pg_try_subtransaction(insert)
+                .map(|_| None)
+                .catch_sqlstate(PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION, Some)
+                .unwrap()
+        }
+        .expect("duplicate INSERT didn't raise an ERROR");
+        assert_eq!(report.sqlstate(), "23505");
+
+        // the failed INSERT was rolled back, and the transaction carries on
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM pg_try_users"),
+            Some(1)
+        );
+    }
+
+    #[pg_test(error = "role \"pgx_no_such_role\" does not exist")]
+    fn test_pg_try_subtransaction_rethrows_others() {
+        unsafe {
+            pg_try_subtransaction(no_such_role)
+                .catch_sqlstate(PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION, |_| 0)
+                .unwrap();
+        }
+    }
+
+    #[pg_test]
+    fn test_pg_try_subtransaction_no_error() {
+        let result = pg_try_subtransaction(|| Spi::get_one::<i32>("SELECT 42")).unwrap();
+        assert_eq!(result, Some(42));
+    }
+
+    #[pg_test]
+    fn test_pg_try_catch_others() {
+        let caught = unsafe { pg_try(|| panic!("caught a panic")).catch_others(Some) };
+        match caught {
+            Some(CaughtError::RustPanic { message, .. }) => assert_eq!(message, "caught a panic"),
+            other => panic!("caught the wrong error: {:?}", other),
+        }
+
+        let caught = pg_try(no_such_role);
+        assert!(matches!(caught.caught(), Some(CaughtError::Postgres(_))));
+        let oid = unsafe { caught.catch_others(|_| pg_sys::InvalidOid) };
+        assert_eq!(oid, pg_sys::InvalidOid);
+    }
 }
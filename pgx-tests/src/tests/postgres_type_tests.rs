@@ -68,6 +68,138 @@ pub struct JsonType {
     c: i64,
 }
 
+#[derive(Serialize, Deserialize, PostgresType)]
+#[pgx(sendrecv = custom)]
+pub struct CustomBinaryFormatType {
+    a: i32,
+    b: i64,
+}
+
+impl SendRecvFuncs for CustomBinaryFormatType {
+    fn send(&self, buffer: &mut StringInfo) {
+        buffer.push_bytes(&self.a.to_be_bytes());
+        buffer.push_bytes(&self.b.to_be_bytes());
+    }
+
+    fn recv(input: &[u8]) -> Self {
+        if input.len() != 12 {
+            error!(
+                "invalid binary CustomBinaryFormatType of {} bytes",
+                input.len()
+            );
+        }
+        CustomBinaryFormatType {
+            a: i32::from_be_bytes(input[..4].try_into().unwrap()),
+            b: i64::from_be_bytes(input[4..].try_into().unwrap()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+#[inoutfuncs]
+#[pgx(typmod)]
+pub struct TypmodVector {
+    values: Vec<i32>,
+}
+
+impl InOutFuncs for TypmodVector {
+    fn input(input: &CStr) -> Self {
+        TypmodVector {
+            values: input
+                .to_str()
+                .unwrap()
+                .split(',')
+                .map(|v| i32::from_str(v).expect("value is not a valid i32"))
+                .collect(),
+        }
+    }
+
+    fn output(&self, buffer: &mut StringInfo) {
+        let values: Vec<String> = self.values.iter().map(|v| v.to_string()).collect();
+        buffer.push_str(&values.join(","))
+    }
+}
+
+impl TypmodInOutFuncs for TypmodVector {
+    fn typmod_in(modifiers: &[i32]) -> i32 {
+        match modifiers {
+            [dimensions] if *dimensions >= 1 => *dimensions,
+            [_] => error!("dimensions must be at least 1"),
+            _ => error!("invalid type modifier"),
+        }
+    }
+
+    fn typmod_out(typmod: i32, buffer: &mut StringInfo) {
+        buffer.push_str(&format!("({})", typmod))
+    }
+
+    fn apply_typmod(mut self, typmod: i32, explicit: bool) -> Self {
+        let dimensions = typmod as usize;
+        if self.values.len() > dimensions && explicit {
+            self.values.truncate(dimensions);
+        } else if self.values.len() != dimensions {
+            error!(
+                "expected {} dimensions, not {}",
+                dimensions,
+                self.values.len()
+            );
+        }
+        self
+    }
+}
+
+#[derive(Copy, Clone, PostgresType)]
+#[inoutfuncs]
+#[pgx(fixed_size, sendrecv = custom)]
+pub struct FixedSizeId {
+    bytes: [u8; 16],
+}
+
+impl InOutFuncs for FixedSizeId {
+    fn input(input: &CStr) -> Self {
+        let input = input.to_str().unwrap();
+        if input.len() != 32 {
+            error!("invalid FixedSizeId: {}", input);
+        }
+        let mut bytes = [0u8; 16];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&input[i * 2..i * 2 + 2], 16).expect("invalid hex digit");
+        }
+        FixedSizeId { bytes }
+    }
+
+    fn output(&self, buffer: &mut StringInfo) {
+        for byte in self.bytes {
+            buffer.push_str(&format!("{:02x}", byte));
+        }
+    }
+}
+
+impl SendRecvFuncs for FixedSizeId {
+    fn send(&self, buffer: &mut StringInfo) {
+        buffer.push_bytes(&self.bytes);
+    }
+
+    fn recv(input: &[u8]) -> Self {
+        FixedSizeId {
+            bytes: input.try_into().expect("invalid binary FixedSizeId"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize, PostgresType)]
+#[pgx(by_value)]
+pub struct PackedTimestamp {
+    micros: i64,
+}
+
+#[pg_extern]
+fn packedtimestamp_add(timestamp: PackedTimestamp, micros: i64) -> PackedTimestamp {
+    PackedTimestamp {
+        micros: timestamp.micros + micros,
+    }
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -75,7 +207,8 @@ mod tests {
     use crate as pgx_tests;
 
     use crate::tests::postgres_type_tests::{
-        CustomTextFormatSerializedType, JsonType, VarlenaType,
+        CustomBinaryFormatType, CustomTextFormatSerializedType, FixedSizeId, JsonType,
+        PackedTimestamp, TypmodVector, VarlenaType,
     };
     use pgx::*;
 
@@ -107,4 +240,147 @@ mod tests {
         assert_eq!(result.b, 2.0);
         assert_eq!(result.c, 3);
     }
+
+    #[pg_test]
+    fn test_sendrecv_functions_are_declared() {
+        let declared = Spi::get_one::<bool>(
+            "SELECT send.proname = 'jsontype_send' AND recv.proname = 'jsontype_recv' \
+             FROM pg_type \
+             JOIN pg_proc send ON send.oid = typsend \
+             JOIN pg_proc recv ON recv.oid = typreceive \
+             WHERE typname = 'jsontype'",
+        );
+        assert_eq!(declared, Some(true));
+
+        let declared = Spi::get_one::<bool>(
+            "SELECT typsend = 0 AND typreceive = 0 FROM pg_type WHERE typname = 'varlenatype'",
+        );
+        assert_eq!(declared, Some(true));
+    }
+
+    #[pg_test]
+    fn test_custom_send() {
+        let sent = Spi::get_one::<Vec<u8>>(
+            r#"SELECT customBinaryFormatType_send('{"a": 1, "b": 2}'::CustomBinaryFormatType)"#,
+        );
+        assert_eq!(sent, Some(vec![0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2]));
+    }
+
+    #[pg_test]
+    fn test_binary_copy_roundtrip() {
+        let path = std::env::temp_dir().join(format!("pgx_sendrecv_{}.bin", std::process::id()));
+        let path = path.display();
+        Spi::run(&format!(
+            r#"
+            CREATE TABLE sendrecv_source (j JsonType, c CustomBinaryFormatType);
+            INSERT INTO sendrecv_source VALUES ('{{"a": 1.5, "b": 2.5, "c": 3}}', '{{"a": 4, "b": 5}}');
+            COPY sendrecv_source TO '{path}' (FORMAT binary);
+            CREATE TABLE sendrecv_target (j JsonType, c CustomBinaryFormatType);
+            COPY sendrecv_target FROM '{path}' (FORMAT binary);
+            "#,
+            path = path
+        ));
+        let _ = std::fs::remove_file(path.to_string());
+
+        let json =
+            Spi::get_one::<JsonType>("SELECT j FROM sendrecv_target").expect("SPI returned NULL");
+        assert_eq!(json.a, 1.5);
+        assert_eq!(json.b, 2.5);
+        assert_eq!(json.c, 3);
+
+        let binary = Spi::get_one::<CustomBinaryFormatType>("SELECT c FROM sendrecv_target")
+            .expect("SPI returned NULL");
+        assert_eq!(binary.a, 4);
+        assert_eq!(binary.b, 5);
+    }
+
+    #[pg_test]
+    fn test_typmod_is_declared() {
+        Spi::run("CREATE TABLE typmod_vectors (v TypmodVector(3));");
+        let declared = Spi::get_one::<String>(
+            "SELECT format_type(atttypid, atttypmod) FROM pg_attribute \
+             WHERE attrelid = 'typmod_vectors'::regclass AND attname = 'v'",
+        );
+        assert_eq!(declared, Some("typmodvector(3)".to_string()));
+    }
+
+    #[pg_test]
+    fn test_typmod_is_applied_to_input() {
+        Spi::run("CREATE TABLE typmod_vectors (v TypmodVector(3));");
+        Spi::run("INSERT INTO typmod_vectors VALUES ('1,2,3');");
+        let result = Spi::get_one::<TypmodVector>("SELECT v FROM typmod_vectors")
+            .expect("SPI returned NULL");
+        assert_eq!(result.values, vec![1, 2, 3]);
+    }
+
+    #[pg_test(error = "expected 3 dimensions, not 2")]
+    fn test_typmod_rejects_input() {
+        Spi::run("CREATE TABLE typmod_vectors (v TypmodVector(3));");
+        Spi::run("INSERT INTO typmod_vectors VALUES ('1,2');");
+    }
+
+    #[pg_test(error = "expected 2 dimensions, not 4")]
+    fn test_typmod_implicit_coercion() {
+        Spi::run("CREATE TABLE typmod_vectors (v TypmodVector(2));");
+        Spi::run("INSERT INTO typmod_vectors SELECT '1,2,3,4'::TypmodVector;");
+    }
+
+    #[pg_test]
+    fn test_typmod_explicit_coercion() {
+        let result =
+            Spi::get_one::<TypmodVector>("SELECT '1,2,3,4'::TypmodVector::TypmodVector(2)")
+                .expect("SPI returned NULL");
+        assert_eq!(result.values, vec![1, 2]);
+    }
+
+    #[pg_test(error = "dimensions must be at least 1")]
+    fn test_typmod_invalid_modifier() {
+        Spi::run("CREATE TABLE typmod_vectors (v TypmodVector(0));");
+    }
+
+    #[pg_test]
+    fn test_fixed_size_type_is_declared() {
+        let declared = Spi::get_one::<bool>(
+            "SELECT typlen = 16 AND NOT typbyval AND typalign = 'c' AND typstorage = 'p' \
+             FROM pg_type WHERE typname = 'fixedsizeid'",
+        );
+        assert_eq!(declared, Some(true));
+
+        let declared = Spi::get_one::<bool>(
+            "SELECT typlen = 8 AND typbyval AND typalign = 'd' AND typstorage = 'p' \
+             FROM pg_type WHERE typname = 'packedtimestamp'",
+        );
+        assert_eq!(declared, Some(true));
+    }
+
+    #[pg_test]
+    fn test_fixed_size_roundtrip() {
+        Spi::run(
+            "CREATE TABLE fixed_size_ids (id FixedSizeId);
+             INSERT INTO fixed_size_ids VALUES ('000102030405060708090a0b0c0d0e0f');",
+        );
+        let id = Spi::get_one::<FixedSizeId>("SELECT id FROM fixed_size_ids")
+            .expect("SPI returned NULL");
+        assert_eq!(
+            id.bytes,
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+        );
+
+        let text = Spi::get_one::<String>("SELECT id::text FROM fixed_size_ids");
+        assert_eq!(text, Some("000102030405060708090a0b0c0d0e0f".to_string()));
+    }
+
+    #[pg_test]
+    fn test_by_value_roundtrip() {
+        Spi::run(
+            r#"CREATE TABLE packed_timestamps (ts PackedTimestamp);
+               INSERT INTO packed_timestamps VALUES (packedtimestamp_add('{"micros": 40}', 2));"#,
+        );
+        let ts = Spi::get_one::<PackedTimestamp>("SELECT ts FROM packed_timestamps")
+            .expect("SPI returned NULL");
+        assert_eq!(ts.micros, 42);
+
+        let datum = PackedTimestamp { micros: 42 }.into_datum();
+        assert_eq!(datum, Some(42));
+    }
 }
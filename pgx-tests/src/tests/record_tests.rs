@@ -0,0 +1,121 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+
+#[pg_extern]
+fn record_pair(fcinfo: pg_sys::FunctionCallInfo) -> PgRecord {
+    let mut record = PgRecord::from_fcinfo(fcinfo);
+    record.set(0, 42i32).set(1, "hello");
+    record
+}
+
+#[pg_extern]
+fn record_of_len(fcinfo: pg_sys::FunctionCallInfo) -> PgRecord {
+    let mut record = PgRecord::from_fcinfo(fcinfo);
+    let len = record.len() as i64;
+    record.set(0, len);
+    record
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_record_with_column_definition_list() {
+        let a = Spi::get_one::<i32>("SELECT a FROM record_pair() AS (a int, b text);");
+        assert_eq!(a, Some(42));
+
+        let b = Spi::get_one::<String>("SELECT b FROM record_pair() AS (a int, b text);");
+        assert_eq!(b, Some("hello".to_string()));
+    }
+
+    #[pg_test]
+    fn test_record_unset_columns_are_null() {
+        let len =
+            Spi::get_one::<i64>("SELECT len FROM record_of_len() AS (len bigint, x text, y int);");
+        assert_eq!(len, Some(3));
+
+        let is_null = Spi::get_one::<bool>(
+            "SELECT x IS NULL AND y IS NULL FROM record_of_len() AS (len bigint, x text, y int);",
+        );
+        assert_eq!(is_null, Some(true));
+    }
+
+    #[pg_test]
+    fn test_record_binary_coercible_column() {
+        let b = Spi::get_one::<String>("SELECT b FROM record_pair() AS (a int, b varchar);");
+        assert_eq!(b, Some("hello".to_string()));
+    }
+
+    #[pg_test(
+        error = "column 1 of the returned record is of type text, but the function returned integer"
+    )]
+    fn test_record_type_mismatch() {
+        Spi::get_one::<String>("SELECT b FROM record_pair() AS (a text, b text);");
+    }
+
+    #[pg_test(error = "function returning record called in context that cannot accept type record")]
+    fn test_record_without_column_definition_list() {
+        Spi::run("SELECT record_pair();");
+    }
+
+    #[pg_test]
+    fn test_sixteen_element_tuple() {
+        let tuple = (
+            Some(1i32),
+            None::<i32>,
+            Some(3i32),
+            Some(4i32),
+            Some(5i32),
+            Some(6i32),
+            Some(7i32),
+            Some(8i32),
+            Some(9i32),
+            Some(10i32),
+            Some(11i32),
+            Some(12i32),
+            Some(13i32),
+            Some(14i32),
+            Some(15i32),
+            Some(16i32),
+        );
+        let datum = tuple.into_datum().expect("tuple datum was NULL");
+        let array_oid = Vec::<Option<pg_sys::Datum>>::type_oid();
+        let round_tripped = unsafe {
+            <(
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+                Option<i32>,
+            )>::from_datum(datum, false, array_oid)
+        }
+        .expect("tuple was NULL");
+        assert_eq!(round_tripped.0, Some(1));
+        assert_eq!(round_tripped.1, None);
+        assert_eq!(round_tripped.2, Some(3));
+        assert_eq!(round_tripped.15, Some(16));
+    }
+}
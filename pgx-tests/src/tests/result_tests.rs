@@ -0,0 +1,178 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use eyre::WrapErr;
+use pgx::*;
+
+extension_sql!(
+    r#"
+CREATE FUNCTION result_error(query text) RETURNS text LANGUAGE plpgsql AS $$
+DECLARE
+    state text;
+    message text;
+    detail text;
+    hint text;
+BEGIN
+    EXECUTE query;
+    RETURN NULL;
+EXCEPTION WHEN OTHERS THEN
+    GET STACKED DIAGNOSTICS
+        state = RETURNED_SQLSTATE,
+        message = MESSAGE_TEXT,
+        detail = PG_EXCEPTION_DETAIL,
+        hint = PG_EXCEPTION_HINT;
+    RETURN concat_ws('|', state, message, coalesce(detail, ''), coalesce(hint, ''));
+END;
+$$;
+"#,
+    name = "create_result_error"
+);
+
+#[pg_extern(immutable)]
+fn result_divide(dividend: i32, divisor: i32) -> Result<i32, PgError> {
+    if divisor == 0 {
+        return Err(
+            PgError::new(PgSqlErrorCode::ERRCODE_DIVISION_BY_ZERO, "division by zero")
+                .with_detail(format!("{} can't be divided by 0", dividend))
+                .with_hint("divide by something else"),
+        );
+    }
+    Ok(dividend / divisor)
+}
+
+#[pg_extern]
+fn result_read_file(path: &str) -> Result<String, std::io::Error> {
+    std::fs::read_to_string(path)
+}
+
+#[pg_extern(immutable)]
+fn result_parse_json(json: &str) -> Result<Option<i64>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+#[pg_extern(immutable)]
+fn result_eyre(wrapped: bool) -> eyre::Result<i32> {
+    if wrapped {
+        Err(eyre::Report::new(PgError::new(
+            PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+            "wrapped PgError",
+        )))
+    } else {
+        Err(eyre::eyre!("the cause")).wrap_err("the failure")
+    }
+}
+
+#[pg_extern(immutable)]
+fn result_unit(fail: bool) -> Result<(), PgError> {
+    if fail {
+        Err(PgError::new(
+            PgSqlErrorCode::ERRCODE_RAISE_EXCEPTION,
+            "failed",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[pg_extern(immutable)]
+fn result_series(count: i32) -> Result<impl Iterator<Item = i32>, PgError> {
+    if count < 0 {
+        return Err(PgError::new(
+            PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+            "count is negative",
+        ));
+    }
+    Ok(0..count)
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    /// The `sqlstate|message|detail|hint` of the error raised by `query`
+    fn raised(query: &str) -> Option<String> {
+        Spi::get_one_with_args::<String>(
+            "SELECT result_error($1)",
+            vec![(PgBuiltInOids::TEXTOID.oid(), query.into_datum())],
+        )
+    }
+
+    #[pg_test]
+    fn test_result_ok() {
+        assert_eq!(Spi::get_one::<i32>("SELECT result_divide(42, 2)"), Some(21));
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT result_parse_json('42')"),
+            Some(42)
+        );
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT result_parse_json('null')"),
+            None
+        );
+        assert_eq!(raised("SELECT result_unit(false)"), None);
+    }
+
+    #[pg_test]
+    fn test_result_err_detail_and_hint() {
+        assert_eq!(
+            raised("SELECT result_divide(42, 0)").as_deref(),
+            Some("22012|division by zero|42 can't be divided by 0|divide by something else")
+        );
+    }
+
+    #[pg_test]
+    #[should_panic(expected = "division by zero")]
+    fn test_result_err_aborts() {
+        Spi::get_one::<i32>("SELECT result_divide(42, 0)");
+    }
+
+    #[pg_test]
+    fn test_result_io_error() {
+        let error = raised("SELECT result_read_file('/pgx/does/not/exist')").unwrap();
+        assert!(error.starts_with("58P01|"), "{}", error);
+    }
+
+    #[pg_test]
+    fn test_result_serde_json_error() {
+        let error = raised("SELECT result_parse_json('forty-two')").unwrap();
+        assert!(error.starts_with("22P02|"), "{}", error);
+    }
+
+    #[pg_test]
+    fn test_result_eyre_report() {
+        assert_eq!(
+            raised("SELECT result_eyre(false)").as_deref(),
+            Some("XX000|the failure|the cause|")
+        );
+        assert_eq!(
+            raised("SELECT result_eyre(true)").as_deref(),
+            Some("22023|wrapped PgError||")
+        );
+    }
+
+    #[pg_test]
+    fn test_result_unit_err() {
+        assert_eq!(
+            raised("SELECT result_unit(true)").as_deref(),
+            Some("P0001|failed||")
+        );
+    }
+
+    #[pg_test]
+    fn test_result_setof() {
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM result_series(5)"),
+            Some(5)
+        );
+        let error = raised("SELECT * FROM result_series(-1)").unwrap();
+        assert!(error.starts_with("22023|count is negative"), "{}", error);
+    }
+}
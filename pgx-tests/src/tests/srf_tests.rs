@@ -67,6 +67,34 @@ fn return_none_setof_iterator() -> Option<impl std::iter::Iterator<Item = i32>>
     }
 }
 
+#[pg_extern(materialize)]
+fn materialized_words<'a>(text: &'a str) -> impl std::iter::Iterator<Item = &'a str> {
+    text.split_whitespace()
+}
+
+#[pg_extern(materialize)]
+fn materialized_composite_set<'a>(
+    text: &'a str,
+) -> impl std::iter::Iterator<Item = (name!(idx, i32), name!(word, Option<&'a str>))> {
+    text.split(',')
+        .enumerate()
+        .map(|(idx, word)| ((idx + 1) as i32, Some(word).filter(|word| !word.is_empty())))
+}
+
+#[pg_extern(materialize)]
+fn materialized_none_iterator() -> Option<impl std::iter::Iterator<Item = i64>> {
+    if true {
+        None
+    } else {
+        Some(0..10)
+    }
+}
+
+#[pg_extern(materialize)]
+fn materialized_series(end: i64) -> impl std::iter::Iterator<Item = String> {
+    (1..=end).map(|i| format!("row {}", i))
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -165,4 +193,68 @@ mod tests {
 
         assert_eq!(cnt, Some(0))
     }
+
+    #[pg_test]
+    fn test_materialized_borrowing_setof() {
+        let words = Spi::get_one::<Vec<String>>(
+            "SELECT array_agg(word) FROM materialized_words('materialize  these words') word",
+        );
+        assert_eq!(
+            words,
+            Some(vec![
+                "materialize".to_string(),
+                "these".to_string(),
+                "words".to_string()
+            ])
+        );
+    }
+
+    #[pg_test]
+    fn test_materialized_composite_set() {
+        let rows = Spi::connect(|client| {
+            let rows = client
+                .select(
+                    "SELECT idx, word FROM materialized_composite_set('a,,c') ORDER BY idx",
+                    None,
+                    None,
+                )
+                .map(|row| (row.get_datum::<i32>(1), row.get_datum::<String>(2)))
+                .collect::<Vec<_>>();
+            Ok(Some(rows))
+        });
+        assert_eq!(
+            rows,
+            Some(vec![
+                (Some(1), Some("a".to_string())),
+                (Some(2), None),
+                (Some(3), Some("c".to_string()))
+            ])
+        );
+    }
+
+    #[pg_test]
+    fn test_materialized_none_iterator() {
+        let cnt = Spi::get_one::<i64>("SELECT count(*) FROM materialized_none_iterator()");
+        assert_eq!(cnt, Some(0));
+    }
+
+    #[pg_test]
+    fn test_materialized_in_target_list_and_lateral() {
+        let cnt =
+            Spi::get_one::<i64>("SELECT count(*) FROM (SELECT materialized_words('a b c')) words");
+        assert_eq!(cnt, Some(3));
+
+        let cnt = Spi::get_one::<i64>(
+            "SELECT count(*) FROM generate_series(1, 3) n, LATERAL materialized_series(n)",
+        );
+        assert_eq!(cnt, Some(6));
+    }
+
+    #[pg_test]
+    fn test_materialized_large_set() {
+        let last = Spi::get_one::<String>(
+            "SELECT value FROM materialized_series(100000) value OFFSET 99999",
+        );
+        assert_eq!(last, Some("row 100000".to_string()));
+    }
 }
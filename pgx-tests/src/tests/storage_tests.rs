@@ -0,0 +1,230 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PostgresType)]
+#[pgx(storage = "bincode")]
+pub struct BincodeType {
+    a: i32,
+    b: String,
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+#[pgx(storage = "messagepack")]
+pub struct MessagePackType {
+    a: i32,
+    b: String,
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+#[pgx(storage = "bincode", version = 2)]
+pub struct VersionedType {
+    name: String,
+    count: i64,
+}
+
+impl<'de> MigrateStorage<'de> for VersionedType {
+    fn migrate_from(version: u8, bytes: &'de [u8]) -> Self {
+        match version {
+            // version 1 didn't have a count
+            1 => VersionedType {
+                name: StorageFormat::Bincode.decode(bytes),
+                count: 0,
+            },
+            _ => error!("cannot migrate VersionedType from version {}", version),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct UnversionedType {
+    name: String,
+}
+
+/// [`UnversionedType`], after it started versioning
+#[derive(Serialize, Deserialize, PostgresType)]
+#[pgx(version = 1, legacy_untagged)]
+pub struct LegacyVersionedType {
+    name: String,
+    count: i64,
+}
+
+impl<'de> MigrateStorage<'de> for LegacyVersionedType {
+    fn migrate_from(version: u8, bytes: &'de [u8]) -> Self {
+        match version {
+            0 => {
+                let unversioned: UnversionedType = StorageFormat::Cbor.decode(bytes);
+                LegacyVersionedType {
+                    name: unversioned.name,
+                    count: 0,
+                }
+            }
+            _ => error!(
+                "cannot migrate LegacyVersionedType from version {}",
+                version
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+#[pgx(storage = custom)]
+pub struct CustomStorageType {
+    value: i64,
+}
+
+impl<'de> PostgresTypeStorage<'de> for CustomStorageType {
+    const VERSION: Option<u8> = Some(1);
+
+    fn encode(&self, buffer: &mut StringInfo) {
+        buffer.push_bytes(&self.value.to_be_bytes());
+    }
+
+    fn decode(bytes: &'de [u8]) -> Self {
+        CustomStorageType {
+            value: i64::from_be_bytes(bytes.try_into().expect("invalid CustomStorageType")),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use crate::tests::storage_tests::{
+        BincodeType, CustomStorageType, LegacyVersionedType, MessagePackType, VersionedType,
+    };
+    use pgx::*;
+
+    fn stored_bytes<T: IntoDatum>(value: T) -> Vec<u8> {
+        let datum = value.into_datum().expect("datum was NULL");
+        unsafe { varlena_to_byte_slice(datum as *const pg_sys::varlena) }.to_vec()
+    }
+
+    fn from_stored_bytes<T: FromDatum>(bytes: &[u8]) -> T {
+        let varlena = rust_byte_slice_to_bytea(bytes).into_pg();
+        unsafe { T::from_datum(varlena as pg_sys::Datum, false, pg_sys::InvalidOid) }
+            .expect("datum was NULL")
+    }
+
+    #[pg_test]
+    fn test_bincode_storage_roundtrip() {
+        Spi::run(
+            r#"
+            CREATE TABLE bincode_values (v BincodeType);
+            INSERT INTO bincode_values VALUES ('{"a": 1, "b": "hello"}');
+            "#,
+        );
+        let result =
+            Spi::get_one::<BincodeType>("SELECT v FROM bincode_values").expect("SPI returned NULL");
+        assert_eq!(result.a, 1);
+        assert_eq!(result.b, "hello");
+    }
+
+    #[pg_test]
+    fn test_messagepack_storage_roundtrip() {
+        Spi::run(
+            r#"
+            CREATE TABLE messagepack_values (v MessagePackType);
+            INSERT INTO messagepack_values VALUES ('{"a": 2, "b": "world"}');
+            "#,
+        );
+        let result = Spi::get_one::<MessagePackType>("SELECT v FROM messagepack_values")
+            .expect("SPI returned NULL");
+        assert_eq!(result.a, 2);
+        assert_eq!(result.b, "world");
+    }
+
+    #[pg_test]
+    fn test_storage_has_version_tag() {
+        let bytes = stored_bytes(BincodeType {
+            a: 1,
+            b: "hello".to_string(),
+        });
+        assert_eq!(bytes[0], 0);
+
+        let bytes = stored_bytes(VersionedType {
+            name: "hello".to_string(),
+            count: 3,
+        });
+        assert_eq!(bytes[0], 2);
+    }
+
+    #[pg_test]
+    fn test_storage_migrates_older_version() {
+        // bincode stores a String as its u64 length followed by its bytes
+        let mut bytes = vec![1u8];
+        bytes.extend_from_slice(&5u64.to_le_bytes());
+        bytes.extend_from_slice(b"hello");
+
+        let migrated = from_stored_bytes::<VersionedType>(&bytes);
+        assert_eq!(migrated.name, "hello");
+        assert_eq!(migrated.count, 0);
+    }
+
+    #[pg_test(error = "cannot migrate VersionedType from version 0")]
+    fn test_storage_unmigratable_version() {
+        from_stored_bytes::<VersionedType>(&[0u8]);
+    }
+
+    #[pg_test(
+        error = "stored pgx_tests::tests::storage_tests::VersionedType is version 3, but only versions up to 2 can be read"
+    )]
+    fn test_storage_newer_version() {
+        from_stored_bytes::<VersionedType>(&[3u8]);
+    }
+
+    #[pg_test]
+    fn test_storage_migrates_legacy_untagged() {
+        // the cast reinterprets the stored bytes, as if the type's definition had changed
+        Spi::run(
+            r#"
+            CREATE TABLE legacy_values (v UnversionedType);
+            INSERT INTO legacy_values VALUES ('{"name": "hello"}');
+            CREATE CAST (UnversionedType AS LegacyVersionedType) WITHOUT FUNCTION;
+            ALTER TABLE legacy_values ALTER COLUMN v TYPE LegacyVersionedType;
+            "#,
+        );
+        let migrated = Spi::get_one::<LegacyVersionedType>("SELECT v FROM legacy_values")
+            .expect("SPI returned NULL");
+        assert_eq!(migrated.name, "hello");
+        assert_eq!(migrated.count, 0);
+
+        let bytes = stored_bytes(LegacyVersionedType {
+            name: "hello".to_string(),
+            count: 3,
+        });
+        assert_eq!(bytes[..2], [LEGACY_UNTAGGED_MARKER, 1]);
+
+        let result = from_stored_bytes::<LegacyVersionedType>(&bytes);
+        assert_eq!(result.name, "hello");
+        assert_eq!(result.count, 3);
+    }
+
+    #[pg_test]
+    fn test_custom_storage() {
+        let bytes = stored_bytes(CustomStorageType { value: 42 });
+        assert_eq!(bytes, vec![1, 0, 0, 0, 0, 0, 0, 0, 42]);
+
+        let result =
+            Spi::get_one::<CustomStorageType>(r#"SELECT '{"value": 42}'::CustomStorageType"#)
+                .expect("SPI returned NULL");
+        assert_eq!(result.value, 42);
+    }
+
+    #[pg_test(
+        error = "pgx_tests::tests::storage_tests::CustomStorageType has no migration from stored version 0"
+    )]
+    fn test_custom_storage_default_migration() {
+        from_stored_bytes::<CustomStorageType>(&[0u8, 0, 0, 0, 0, 0, 0, 0, 42]);
+    }
+}
@@ -0,0 +1,163 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Default, Serialize, Deserialize, PostgresType)]
+#[pgx(subscript)]
+pub struct Counters(BTreeMap<String, i32>);
+
+impl Subscriptable for Counters {
+    type Subscript = String;
+    type Element = i32;
+
+    fn fetch(&self, name: String) -> Option<i32> {
+        self.0.get(&name).copied()
+    }
+
+    fn assign(&mut self, name: String, count: Option<i32>) {
+        match count {
+            Some(count) => self.0.insert(name, count),
+            None => self.0.remove(&name),
+        };
+    }
+
+    fn assign_to_null() -> Self {
+        Counters::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+#[pgx(subscript)]
+pub struct Readings(Vec<f64>);
+
+impl Subscriptable for Readings {
+    type Subscript = i32;
+    type Element = f64;
+
+    fn fetch(&self, index: i32) -> Option<f64> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.0.get(index).copied())
+    }
+
+    fn assign(&mut self, index: i32, reading: Option<f64>) {
+        let reading = reading.unwrap_or_else(|| error!("a reading can't be NULL"));
+        match usize::try_from(index)
+            .ok()
+            .and_then(|index| self.0.get_mut(index))
+        {
+            Some(slot) => *slot = reading,
+            None => error!("no reading at index {}", index),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_subscript_fetch() {
+        let count = Spi::get_one::<i32>(r#"SELECT ('{"a": 1, "b": 2}'::Counters)['b']"#);
+        assert_eq!(count, Some(2));
+    }
+
+    #[pg_test]
+    fn test_subscript_fetch_missing() {
+        let count = Spi::get_one::<i32>(r#"SELECT ('{"a": 1}'::Counters)['b']"#);
+        assert_eq!(count, None);
+    }
+
+    #[pg_test]
+    fn test_subscript_fetch_null() {
+        let count = Spi::get_one::<i32>(r#"SELECT ('{"a": 1}'::Counters)[NULL]"#);
+        assert_eq!(count, None);
+
+        let count = Spi::get_one::<i32>("SELECT (NULL::Counters)['a']");
+        assert_eq!(count, None);
+    }
+
+    #[pg_test]
+    fn test_subscript_coerces_subscript() {
+        let reading = Spi::get_one::<f64>("SELECT ('[1.5, 2.5]'::Readings)['1']");
+        assert_eq!(reading, Some(2.5));
+    }
+
+    #[pg_test]
+    fn test_subscript_assign() {
+        Spi::run(
+            r#"
+            CREATE TABLE counters (v Counters);
+            INSERT INTO counters VALUES ('{"a": 1}');
+            UPDATE counters SET v['a'] = 3, v['b'] = 4;
+            "#,
+        );
+        let counts = Spi::get_two::<i32, i32>("SELECT v['a'], v['b'] FROM counters");
+        assert_eq!(counts, (Some(3), Some(4)));
+    }
+
+    #[pg_test]
+    fn test_subscript_assign_null_element() {
+        Spi::run(
+            r#"
+            CREATE TABLE counters (v Counters);
+            INSERT INTO counters VALUES ('{"a": 1, "b": 2}');
+            UPDATE counters SET v['a'] = NULL;
+            "#,
+        );
+        let counts = Spi::get_two::<i32, i32>("SELECT v['a'], v['b'] FROM counters");
+        assert_eq!(counts, (None, Some(2)));
+    }
+
+    #[pg_test]
+    fn test_subscript_assign_to_null() {
+        Spi::run(
+            r#"
+            CREATE TABLE counters (v Counters);
+            INSERT INTO counters VALUES (NULL);
+            UPDATE counters SET v['a'] = 1;
+            "#,
+        );
+        let count = Spi::get_one::<i32>("SELECT v['a'] FROM counters");
+        assert_eq!(count, Some(1));
+    }
+
+    #[pg_test(error = "cannot assign to a subscript of a NULL readings")]
+    fn test_subscript_assign_to_null_default() {
+        Spi::run(
+            r#"
+            CREATE TABLE readings (v Readings);
+            INSERT INTO readings VALUES (NULL);
+            UPDATE readings SET v[0] = 1.0;
+            "#,
+        );
+    }
+
+    #[pg_test(error = "counters subscript in assignment must not be null")]
+    fn test_subscript_assign_null_subscript() {
+        Spi::run(
+            r#"
+            CREATE TABLE counters (v Counters);
+            INSERT INTO counters VALUES ('{}');
+            UPDATE counters SET v[NULL] = 1;
+            "#,
+        );
+    }
+
+    #[pg_test(error = "counters allows only one subscript, and not slices")]
+    fn test_subscript_rejects_slices() {
+        Spi::get_one::<i32>(r#"SELECT ('{"a": 1}'::Counters)['a':'b']"#);
+    }
+}
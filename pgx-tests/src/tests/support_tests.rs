@@ -0,0 +1,137 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+
+#[pg_extern(immutable, support = support_repeat_rows)]
+fn support_repeat(word: &str, times: i32) -> impl Iterator<Item = String> + '_ {
+    (0..times).map(move |_| word.to_string())
+}
+
+fn support_repeat_rows(request: SupportRequest) -> SupportResponse {
+    match request {
+        SupportRequest::Rows(rows) => match unsafe { const_argument::<i32>(rows.node, 1) } {
+            Some(times) => {
+                rows.rows = times.max(0) as f64;
+                SupportResponse::Handled
+            }
+            None => SupportResponse::Unhandled,
+        },
+        _ => SupportResponse::Unhandled,
+    }
+}
+
+#[pg_extern(immutable, support = support_identity_simplify)]
+fn support_identity(value: i32) -> i32 {
+    value
+}
+
+fn support_identity_simplify(request: SupportRequest) -> SupportResponse {
+    let value = request.arguments().head();
+    match (request, value) {
+        (SupportRequest::Simplify(_), Some(value)) => SupportResponse::Simplified(value),
+        _ => SupportResponse::Unhandled,
+    }
+}
+
+#[pg_extern(immutable, support = support_rarely_selectivity)]
+fn support_rarely(value: i32) -> bool {
+    value % 100 == 0
+}
+
+#[pg_extern(immutable, support = support_rarely_selectivity)]
+fn support_rarely_bigint(value: i64) -> bool {
+    value % 100 == 0
+}
+
+fn support_rarely_selectivity(request: SupportRequest) -> SupportResponse {
+    match request {
+        SupportRequest::Selectivity(selectivity) => {
+            selectivity.selectivity = 0.01;
+            SupportResponse::Handled
+        }
+        _ => SupportResponse::Unhandled,
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    /// The `EXPLAIN (VERBOSE)` of `query`
+    fn explain(query: &str) -> String {
+        Spi::connect(|client| {
+            let plan = client
+                .select(&format!("EXPLAIN (VERBOSE) {}", query), None, None)
+                .filter_map(|row| row.get_datum::<String>(1))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Ok(Some(plan))
+        })
+        .unwrap()
+    }
+
+    #[pg_test]
+    fn test_support_catalog() {
+        let supported = Spi::get_one::<i64>(
+            "SELECT count(*) FROM pg_proc WHERE \
+             (proname = 'support_repeat' AND prosupport = 'support_repeat_support_wrapper'::regproc) OR \
+             (proname = 'support_identity' AND prosupport = 'support_identity_support_wrapper'::regproc) OR \
+             (proname = 'support_rarely' AND prosupport = 'support_rarely_support_wrapper'::regproc) OR \
+             (proname = 'support_rarely_bigint' AND prosupport = 'support_rarely_bigint_support_wrapper'::regproc)",
+        );
+        assert_eq!(supported, Some(4));
+    }
+
+    #[pg_test]
+    fn test_support_rows() {
+        let plan = explain("SELECT * FROM support_repeat('a', 42)");
+        assert!(plan.contains("rows=42 "), "{}", plan);
+
+        let repeated = Spi::get_one::<i64>("SELECT count(*) FROM support_repeat('a', 42)");
+        assert_eq!(repeated, Some(42));
+    }
+
+    #[pg_test]
+    fn test_support_simplify() {
+        let plan = explain("SELECT support_identity(i) FROM generate_series(1, 3) i");
+        assert!(!plan.contains("support_identity"), "{}", plan);
+
+        let sum =
+            Spi::get_one::<i64>("SELECT sum(support_identity(i)) FROM generate_series(1, 3) i");
+        assert_eq!(sum, Some(6));
+    }
+
+    #[pg_test]
+    fn test_support_selectivity() {
+        let plan = explain("SELECT i FROM generate_series(1, 1000) i WHERE support_rarely(i)");
+        assert!(plan.contains("rows=10 "), "{}", plan);
+
+        let rarely = Spi::get_one::<i64>(
+            "SELECT count(*) FROM generate_series(1, 1000) i WHERE support_rarely(i)",
+        );
+        assert_eq!(rarely, Some(10));
+
+        // shares its support function with `support_rarely`
+        let plan = explain(
+            "SELECT i FROM generate_series(1, 1000::bigint) i WHERE support_rarely_bigint(i)",
+        );
+        assert!(plan.contains("rows=10 "), "{}", plan);
+    }
+
+    #[pg_test]
+    fn test_support_unhandled() {
+        // the planner sends a `SupportRequestSimplify` that `support_rarely_selectivity` ignores
+        let rarely = Spi::get_one::<bool>("SELECT support_rarely(100)");
+        assert_eq!(rarely, Some(true));
+    }
+}
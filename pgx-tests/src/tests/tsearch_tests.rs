@@ -0,0 +1,121 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+
+pub struct CommaParser {
+    position: usize,
+}
+
+#[pg_ts_parser]
+impl TextSearchParser for CommaParser {
+    const NAME: &'static str = "comma_parser";
+
+    fn start(_input: &[u8]) -> Self {
+        CommaParser { position: 0 }
+    }
+
+    fn get_token(&mut self, input: &[u8]) -> Option<TextSearchToken> {
+        if self.position >= input.len() {
+            return None;
+        }
+
+        let start = self.position;
+        if input[start] == b',' {
+            self.position += 1;
+            return Some(TextSearchToken::new(2, start, 1));
+        }
+        while self.position < input.len() && input[self.position] != b',' {
+            self.position += 1;
+        }
+        Some(TextSearchToken::new(1, start, self.position - start))
+    }
+
+    fn lextypes() -> Vec<TextSearchLexType> {
+        vec![
+            TextSearchLexType::new(1, "item", "Comma separated item"),
+            TextSearchLexType::new(2, "comma", "Comma"),
+        ]
+    }
+}
+
+pub struct ShoutDictionary {
+    stopword: Option<String>,
+}
+
+#[pg_ts_template]
+impl TextSearchDictionary for ShoutDictionary {
+    const NAME: &'static str = "shout_template";
+
+    fn init(options: Vec<(String, String)>) -> Self {
+        ShoutDictionary {
+            stopword: options
+                .into_iter()
+                .find(|(name, _)| name == "stopword")
+                .map(|(_, value)| value),
+        }
+    }
+
+    fn lexize(&mut self, token: &str) -> Option<Vec<TextSearchLexeme>> {
+        if self.stopword.as_deref() == Some(token) {
+            Some(vec![])
+        } else {
+            Some(vec![TextSearchLexeme::new(token.to_uppercase())])
+        }
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+    use pgx::*;
+
+    #[pg_test]
+    fn test_ts_parser_tokens() {
+        Spi::run(
+            "CREATE TEXT SEARCH CONFIGURATION comma_parser_config (PARSER = comma_parser);
+             ALTER TEXT SEARCH CONFIGURATION comma_parser_config ADD MAPPING FOR item WITH simple;",
+        );
+        let retval = Spi::get_one::<String>(
+            "SELECT string_agg(token, '|') FROM ts_parse('comma_parser', 'one two,three');",
+        )
+        .expect("SQL select failed");
+        assert_eq!(retval, "one two|,|three");
+
+        let retval = Spi::get_one::<String>(
+            "SELECT to_tsvector('comma_parser_config', 'Hello World,foo')::text;",
+        )
+        .expect("SQL select failed");
+        assert_eq!(retval, "'foo':2 'hello world':1");
+    }
+
+    #[pg_test]
+    fn test_ts_parser_lextypes() {
+        let retval = Spi::get_one::<String>(
+            "SELECT string_agg(alias, ',' ORDER BY tokid) FROM ts_token_type('comma_parser');",
+        )
+        .expect("SQL select failed");
+        assert_eq!(retval, "item,comma");
+    }
+
+    #[pg_test]
+    fn test_ts_template_lexize() {
+        Spi::run(
+            "CREATE TEXT SEARCH DICTIONARY shout_dict (TEMPLATE = shout_template, stopword = 'the');",
+        );
+        let retval = Spi::get_one::<Vec<String>>("SELECT ts_lexize('shout_dict', 'hello');")
+            .expect("SQL select failed");
+        assert_eq!(retval, vec!["HELLO".to_string()]);
+
+        let retval = Spi::get_one::<Vec<String>>("SELECT ts_lexize('shout_dict', 'the');")
+            .expect("SQL select failed");
+        assert!(retval.is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use pgx::*;
+
+#[pg_window(immutable)]
+fn window_previous_or(window: WindowContext, _value: Option<i32>, default: i32) -> Option<i32> {
+    window
+        .arg_in_partition::<i32>(0, -1, WindowSeek::Current, false)
+        .unwrap_or(Some(default))
+}
+
+#[pg_window(immutable)]
+fn window_first_in_frame(window: WindowContext, _value: Option<i32>) -> Option<i32> {
+    window
+        .arg_in_frame::<i32>(0, 0, WindowSeek::Head, false)
+        .flatten()
+}
+
+#[pg_window(immutable)]
+fn window_running_count(mut window: WindowContext) -> i64 {
+    let count = window.partition_local::<i64>();
+    *count += 1;
+    *count
+}
+
+#[pg_window(immutable)]
+fn window_position(window: WindowContext) -> String {
+    format!(
+        "{}/{}",
+        window.current_position() + 1,
+        window.partition_row_count()
+    )
+}
+
+#[pg_window(immutable)]
+fn window_scaled(window: WindowContext, value: i32, factor: Option<i32>) -> i32 {
+    window.set_mark_position(window.current_position());
+    value * factor.unwrap_or(1)
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_window_arg_in_partition() {
+        let previous = Spi::get_one::<Vec<Option<i32>>>(
+            "SELECT array_agg(previous ORDER BY i) FROM (
+                SELECT i, window_previous_or(i, 0) OVER (ORDER BY i) previous
+                FROM generate_series(1, 4) i
+            ) windowed",
+        );
+        assert_eq!(previous, Some(vec![Some(0), Some(1), Some(2), Some(3)]));
+    }
+
+    #[pg_test]
+    fn test_window_arg_in_frame() {
+        let first = Spi::get_one::<Vec<Option<i32>>>(
+            "SELECT array_agg(first ORDER BY i) FROM (
+                SELECT i, window_first_in_frame(i) OVER (ORDER BY i ROWS BETWEEN 1 PRECEDING AND CURRENT ROW) first
+                FROM generate_series(1, 4) i
+            ) windowed",
+        );
+        assert_eq!(first, Some(vec![Some(1), Some(1), Some(2), Some(3)]));
+    }
+
+    #[pg_test]
+    fn test_window_partition_local() {
+        let counts = Spi::get_one::<Vec<i64>>(
+            "SELECT array_agg(count ORDER BY i) FROM (
+                SELECT i, window_running_count() OVER (PARTITION BY i % 2 ORDER BY i) count
+                FROM generate_series(1, 6) i
+            ) windowed",
+        );
+        assert_eq!(counts, Some(vec![1, 1, 2, 2, 3, 3]));
+    }
+
+    #[pg_test]
+    fn test_window_position() {
+        let positions = Spi::get_one::<Vec<String>>(
+            "SELECT array_agg(position ORDER BY i) FROM (
+                SELECT i, window_position() OVER (PARTITION BY i > 2 ORDER BY i) position
+                FROM generate_series(1, 5) i
+            ) windowed",
+        );
+        assert_eq!(
+            positions,
+            Some(vec![
+                "1/2".to_string(),
+                "2/2".to_string(),
+                "1/3".to_string(),
+                "2/3".to_string(),
+                "3/3".to_string()
+            ])
+        );
+    }
+
+    #[pg_test]
+    fn test_window_current_row_args() {
+        let scaled = Spi::get_one::<Vec<i32>>(
+            "SELECT array_agg(scaled ORDER BY i) FROM (
+                SELECT i, window_scaled(i, CASE WHEN i > 2 THEN 10 END) OVER (ORDER BY i) scaled
+                FROM generate_series(1, 4) i
+            ) windowed",
+        );
+        assert_eq!(scaled, Some(vec![1, 2, 30, 40]));
+    }
+
+    #[pg_test]
+    #[should_panic(expected = "requires an OVER clause")]
+    fn test_window_requires_over() {
+        Spi::get_one::<i64>("SELECT window_running_count()");
+    }
+}
@@ -183,6 +183,7 @@ pub enum ExternArgs {
     Volatile,
     Raw,
     NoGuard,
+    Materialize,
     ParallelSafe,
     ParallelUnsafe,
     ParallelRestricted,
@@ -190,6 +191,7 @@ pub enum ExternArgs {
     Schema(String),
     Name(String),
     Cost(String),
+    Support(String),
     Requires(Vec<PositioningRef>),
 }
 
@@ -206,9 +208,11 @@ impl core::fmt::Display for ExternArgs {
             ExternArgs::ParallelRestricted => write!(f, "PARALLEL RESTRICTED"),
             ExternArgs::Error(_) => Ok(()),
             ExternArgs::NoGuard => Ok(()),
+            ExternArgs::Materialize => Ok(()),
             ExternArgs::Schema(_) => Ok(()),
             ExternArgs::Name(_) => Ok(()),
             ExternArgs::Cost(cost) => write!(f, "COST {}", cost),
+            ExternArgs::Support(_) => Ok(()),
             ExternArgs::Requires(_) => Ok(()),
         }
     }
@@ -223,6 +227,7 @@ impl ToTokens for ExternArgs {
             ExternArgs::Volatile => tokens.append(format_ident!("Volatile")),
             ExternArgs::Raw => tokens.append(format_ident!("Raw")),
             ExternArgs::NoGuard => tokens.append(format_ident!("NoGuard")),
+            ExternArgs::Materialize => tokens.append(format_ident!("Materialize")),
             ExternArgs::ParallelSafe => tokens.append(format_ident!("ParallelSafe")),
             ExternArgs::ParallelUnsafe => tokens.append(format_ident!("ParallelUnsafe")),
             ExternArgs::ParallelRestricted => tokens.append(format_ident!("ParallelRestricted")),
@@ -258,6 +263,14 @@ impl ToTokens for ExternArgs {
                     .to_token_stream(),
                 );
             }
+            ExternArgs::Support(_s) => {
+                tokens.append_all(
+                    quote! {
+                        Support(String::from("#_s"))
+                    }
+                    .to_token_stream(),
+                );
+            }
             ExternArgs::Requires(items) => {
                 tokens.append_all(
                     quote! {
@@ -302,6 +315,7 @@ pub fn parse_extern_attributes(attr: TokenStream) -> HashSet<ExternArgs> {
                     "volatile" => args.insert(ExternArgs::Volatile),
                     "raw" => args.insert(ExternArgs::Raw),
                     "no_guard" => args.insert(ExternArgs::NoGuard),
+                    "materialize" => args.insert(ExternArgs::Materialize),
                     "parallel_safe" => args.insert(ExternArgs::ParallelSafe),
                     "parallel_unsafe" => args.insert(ExternArgs::ParallelUnsafe),
                     "parallel_restricted" => args.insert(ExternArgs::ParallelRestricted),
@@ -336,7 +350,7 @@ pub fn parse_extern_attributes(attr: TokenStream) -> HashSet<ExternArgs> {
                         args.insert(ExternArgs::Name(name.to_string()))
                     }
                     // Recognized, but not handled as an extern argument
-                    "sql" => {
+                    "sql" | "support" => {
                         let _punc = itr.next().unwrap();
                         let _value = itr.next().unwrap();
                         false
@@ -360,7 +374,30 @@ pub fn categorize_return_type(func: &ItemFn) -> CategorizedType {
     }
 }
 
+/// The `T` of a `Result<T, E>` type, which a `#[pg_extern]` returns as its `Ok`
+pub fn result_ok_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(ty) => {
+            let segment = ty.path.segments.last()?;
+            if segment.ident != "Result" {
+                return None;
+            }
+            match &segment.arguments {
+                PathArguments::AngleBracketed(a) => match a.args.first()? {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 pub fn categorize_type(ty: &Type) -> CategorizedType {
+    if let Some(ty) = result_ok_type(ty) {
+        return categorize_type(ty);
+    }
     match ty {
         Type::Path(ty) => {
             let segments = &ty.path.segments;
@@ -10,7 +10,7 @@ Use of this source code is governed by the MIT license that can be found in the
 extern crate proc_macro;
 
 use crate::sql_entity_graph::PgExtern;
-use crate::{categorize_return_type, CategorizedType};
+use crate::{categorize_return_type, result_ok_type, CategorizedType};
 use proc_macro2::{Ident, Span};
 use quote::{quote, quote_spanned, ToTokens};
 use std::ops::Deref;
@@ -76,7 +76,17 @@ impl PgGuardRewriter {
         let func_name = &func.sig.ident;
         let func_span = func.span();
         let rewritten_args = self.rewrite_args(func.clone(), is_raw);
-        let rewritten_return_type = self.rewrite_return_type(func.clone());
+
+        // the `Err` of a returned `Result` is raised, so the datum is made from its `Ok` type
+        let mut returned = func.clone();
+        let mut returns_result = false;
+        if let ReturnType::Type(_, ty) = &mut returned.sig.output {
+            if let Some(ok_type) = result_ok_type(ty).cloned() {
+                **ty = ok_type;
+                returns_result = true;
+            }
+        }
+        let rewritten_return_type = self.rewrite_return_type(returned);
         let generics = &func.sig.generics;
         let func_name_wrapper = Ident::new(
             &format!("{}_wrapper", &func.sig.ident.to_string()),
@@ -92,11 +102,16 @@ impl PgGuardRewriter {
             Ident::new("result", Span::call_site())
         };
 
+        let call = if returns_result {
+            quote! { pgx::unwrap_or_report(#func_name(#arg_list)) }
+        } else {
+            quote! { #func_name(#arg_list) }
+        };
         let func_call = quote! {
             let #result_var_name = {
                 #rewritten_args
 
-                #func_name(#arg_list)
+                #call
             };
         };
 
@@ -106,7 +121,38 @@ impl PgGuardRewriter {
             #[doc(hidden)]
             #[allow(unused_variables)]
         };
+        let materialize = entity_submission.map_or(false, |entity| entity.materialize());
         match categorize_return_type(&func) {
+            CategorizedType::Iterator(types) if materialize => (
+                PgGuardRewriter::impl_materialize_srf(
+                    types,
+                    func_span,
+                    prolog,
+                    vis,
+                    func_name_wrapper,
+                    generics,
+                    func_call,
+                    entity_submission,
+                    false,
+                ),
+                true,
+            ),
+
+            CategorizedType::OptionalIterator(types) if materialize => (
+                PgGuardRewriter::impl_materialize_srf(
+                    types,
+                    func_span,
+                    prolog,
+                    vis,
+                    func_name_wrapper,
+                    generics,
+                    func_call,
+                    entity_submission,
+                    true,
+                ),
+                true,
+            ),
+
             CategorizedType::Default => (
                 PgGuardRewriter::impl_standard_udf(
                     func_span,
@@ -457,6 +503,64 @@ impl PgGuardRewriter {
         }
     }
 
+    fn impl_materialize_srf(
+        types: Vec<String>,
+        func_span: Span,
+        prolog: proc_macro2::TokenStream,
+        vis: Visibility,
+        func_name_wrapper: Ident,
+        generics: &Generics,
+        func_call: proc_macro2::TokenStream,
+        entity_submission: Option<&PgExtern>,
+        optional: bool,
+    ) -> proc_macro2::TokenStream {
+        let fill_row = if types.len() == 1 {
+            quote! {
+                match result.into_datum() {
+                    Some(datum) => { datums[0] = datum; },
+                    None => { nulls[0] = true; }
+                }
+            }
+        } else {
+            let i = (0..types.len()).map(syn::Index::from);
+            quote! {
+                #(
+                    match result.#i.into_datum() {
+                        Some(datum) => { datums[#i] = datum; },
+                        None => { nulls[#i] = true; }
+                    }
+                )*
+            }
+        };
+
+        let rows = if optional {
+            quote! { result.into_iter().flatten() }
+        } else {
+            quote! { result }
+        };
+        let sql_graph_entity_submission = entity_submission.cloned().into_iter();
+
+        quote_spanned! {func_span=>
+            #prolog
+            #[pg_guard]
+            #vis unsafe extern "C" fn #func_name_wrapper #generics(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+                // every row is put into the store during this one call, so the iterator doesn't
+                // have to outlive it
+                let mut store = pgx::TableStore::materialize(fcinfo);
+
+                #func_call
+
+                for result in #rows {
+                    store.put_row(|datums, nulls| { #fill_row });
+                }
+
+                0 as pgx::pg_sys::Datum
+            }
+
+            #(#sql_graph_entity_submission)*
+        }
+    }
+
     fn item_fn_without_rewrite(
         &self,
         mut func: ItemFn,
@@ -707,6 +811,13 @@ impl FunctionSignatureRewriter {
         let mut i = 0usize;
         let fcinfo_ident: syn::Ident = syn::parse_quote! { fcinfo };
 
+        // the arguments of a window function aren't in `fcinfo`, but are evaluated for the current
+        // row through its `WindowObject`
+        let is_window = self.func.sig.inputs.iter().any(|arg| match arg {
+            FnArg::Typed(ty) => is_window_context(&ty.ty),
+            FnArg::Receiver(_) => false,
+        });
+
         for arg in &self.func.sig.inputs {
             match arg {
                 FnArg::Receiver(_) => panic!("Functions that take self are not supported"),
@@ -716,7 +827,26 @@ impl FunctionSignatureRewriter {
                         let mut type_ = ty.ty.clone();
                         let is_option = type_matches(&type_, "Option");
 
-                        let ts = if is_option {
+                        let ts = if is_window_context(&type_) {
+                            // not an SQL argument, so `i` isn't incremented
+                            stream.extend(quote_spanned! {ident.span()=>
+                                let #name = pgx::WindowContext::from_fcinfo(#fcinfo_ident);
+                            });
+                            continue;
+                        } else if is_window && is_option {
+                            let option_type = extract_option_type(&type_);
+                            let mut option_type = syn::parse2::<syn::Type>(option_type).unwrap();
+                            crate::anonymonize_lifetimes(&mut option_type);
+
+                            quote_spanned! {ident.span()=>
+                                let #name = pgx::WindowContext::from_fcinfo(#fcinfo_ident).arg_current::<#option_type>(#i);
+                            }
+                        } else if is_window {
+                            crate::anonymonize_lifetimes(&mut type_);
+                            quote_spanned! {ident.span()=>
+                                let #name = pgx::WindowContext::from_fcinfo(#fcinfo_ident).arg_current::<#type_>(#i).unwrap_or_else(|| panic!("{} is null", stringify!{#ident}));
+                            }
+                        } else if is_option {
                             let option_type = extract_option_type(&type_);
                             let mut option_type = syn::parse2::<syn::Type>(option_type).unwrap();
                             crate::anonymonize_lifetimes(&mut option_type);
@@ -764,6 +894,10 @@ impl FunctionSignatureRewriter {
     }
 }
 
+fn is_window_context(ty: &Type) -> bool {
+    type_matches(ty, "WindowContext") || type_matches(ty, "pgx :: WindowContext")
+}
+
 fn type_matches(ty: &Type, pattern: &str) -> bool {
     let type_string = format!("{}", quote! {#ty});
     type_string.starts_with(pattern)
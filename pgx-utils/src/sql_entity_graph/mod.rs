@@ -10,6 +10,7 @@ pub(crate) mod aggregate;
 pub(crate) mod control_file;
 pub(crate) mod extension_sql;
 pub(crate) mod mapping;
+pub(crate) mod opclass;
 pub(crate) mod pg_extern;
 pub(crate) mod pgx_attribute;
 pub(crate) mod pgx_sql;
@@ -19,6 +20,8 @@ pub(crate) mod postgres_hash;
 pub(crate) mod postgres_ord;
 pub(crate) mod postgres_type;
 pub(crate) mod schema;
+pub(crate) mod text_search_parser;
+pub(crate) mod text_search_template;
 pub(crate) mod to_sql;
 
 pub use aggregate::{
@@ -31,17 +34,26 @@ pub use extension_sql::{
     ExtensionSql, ExtensionSqlFile, SqlDeclared,
 };
 pub use mapping::{RustSourceOnlySqlMapping, RustSqlMapping};
+pub use opclass::{entity::PgOperatorClassEntity, PgOperatorClass};
 pub use pg_extern::{
-    entity::{PgExternArgumentEntity, PgExternEntity, PgExternReturnEntity, PgOperatorEntity},
-    NameMacro, PgExtern, PgExternArgument, PgOperator,
+    entity::{
+        PgCastContext, PgCastEntity, PgCastMethod, PgExternArgumentEntity, PgExternEntity,
+        PgExternReturnEntity, PgOperatorEntity,
+    },
+    NameMacro, PgCast, PgExtern, PgExternArgument, PgOperator,
 };
 pub use pgx_sql::PgxSql;
 pub use positioning_ref::PositioningRef;
 pub use postgres_enum::{entity::PostgresEnumEntity, PostgresEnum};
 pub use postgres_hash::{entity::PostgresHashEntity, PostgresHash};
 pub use postgres_ord::{entity::PostgresOrdEntity, PostgresOrd};
-pub use postgres_type::{entity::PostgresTypeEntity, PostgresType};
+pub use postgres_type::{
+    entity::{PostgresTypeEntity, PostgresTypeFixedSize, PostgresTypeTypmodFns},
+    PostgresType, PostgresTypeArgs, PostgresTypeStorageArg,
+};
 pub use schema::{entity::SchemaEntity, Schema};
+pub use text_search_parser::{entity::PgTextSearchParserEntity, PgTextSearchParser};
+pub use text_search_template::{entity::PgTextSearchTemplateEntity, PgTextSearchTemplate};
 pub use to_sql::{entity::ToSqlConfigEntity, ToSql, ToSqlConfig};
 
 pub use crate::ExternArgs;
@@ -78,7 +90,10 @@ pub enum SqlGraphEntity {
     Enum(PostgresEnumEntity),
     Ord(PostgresOrdEntity),
     Hash(PostgresHashEntity),
+    OperatorClass(PgOperatorClassEntity),
     Aggregate(PgAggregateEntity),
+    TextSearchParser(PgTextSearchParserEntity),
+    TextSearchTemplate(PgTextSearchTemplateEntity),
 }
 
 impl SqlGraphEntity {
@@ -110,7 +125,10 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.dot_identifier(),
             SqlGraphEntity::Ord(item) => item.dot_identifier(),
             SqlGraphEntity::Hash(item) => item.dot_identifier(),
+            SqlGraphEntity::OperatorClass(item) => item.dot_identifier(),
             SqlGraphEntity::Aggregate(item) => item.dot_identifier(),
+            SqlGraphEntity::TextSearchParser(item) => item.dot_identifier(),
+            SqlGraphEntity::TextSearchTemplate(item) => item.dot_identifier(),
             SqlGraphEntity::ExtensionRoot(item) => item.dot_identifier(),
         }
     }
@@ -125,7 +143,10 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.rust_identifier(),
             SqlGraphEntity::Ord(item) => item.rust_identifier(),
             SqlGraphEntity::Hash(item) => item.rust_identifier(),
+            SqlGraphEntity::OperatorClass(item) => item.rust_identifier(),
             SqlGraphEntity::Aggregate(item) => item.rust_identifier(),
+            SqlGraphEntity::TextSearchParser(item) => item.rust_identifier(),
+            SqlGraphEntity::TextSearchTemplate(item) => item.rust_identifier(),
             SqlGraphEntity::ExtensionRoot(item) => item.rust_identifier(),
         }
     }
@@ -140,7 +161,10 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.file(),
             SqlGraphEntity::Ord(item) => item.file(),
             SqlGraphEntity::Hash(item) => item.file(),
+            SqlGraphEntity::OperatorClass(item) => item.file(),
             SqlGraphEntity::Aggregate(item) => item.file(),
+            SqlGraphEntity::TextSearchParser(item) => item.file(),
+            SqlGraphEntity::TextSearchTemplate(item) => item.file(),
             SqlGraphEntity::ExtensionRoot(item) => item.file(),
         }
     }
@@ -155,7 +179,10 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.line(),
             SqlGraphEntity::Ord(item) => item.line(),
             SqlGraphEntity::Hash(item) => item.line(),
+            SqlGraphEntity::OperatorClass(item) => item.line(),
             SqlGraphEntity::Aggregate(item) => item.line(),
+            SqlGraphEntity::TextSearchParser(item) => item.line(),
+            SqlGraphEntity::TextSearchTemplate(item) => item.line(),
             SqlGraphEntity::ExtensionRoot(item) => item.line(),
         }
     }
@@ -180,7 +207,7 @@ impl ToSql for SqlGraphEntity {
                 if context.graph.neighbors_undirected(context.externs.get(item).unwrap().clone()).any(|neighbor| {
                     let neighbor_item = &context.graph[neighbor];
                     match neighbor_item {
-                        SqlGraphEntity::Type(PostgresTypeEntity { in_fn, in_fn_module_path, out_fn, out_fn_module_path, .. }) => {
+                        SqlGraphEntity::Type(ty @ PostgresTypeEntity { in_fn, in_fn_module_path, out_fn, out_fn_module_path, .. }) => {
                             let is_in_fn = item.full_path.starts_with(in_fn_module_path) && item.full_path.ends_with(in_fn);
                             if is_in_fn {
                                 tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an in_fn.");
@@ -189,7 +216,11 @@ impl ToSql for SqlGraphEntity {
                             if is_out_fn {
                                 tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an out_fn.");
                             }
-                            is_in_fn || is_out_fn
+                            let is_owned_fn = ty.owned_fns().iter().any(|owned_fn| item.full_path == owned_fn.as_str());
+                            if is_owned_fn {
+                                tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is a send, recv, typmod or subscript fn.");
+                            }
+                            is_in_fn || is_out_fn || is_owned_fn
                         },
                         _ => false,
                     }
@@ -216,10 +247,22 @@ impl ToSql for SqlGraphEntity {
                 .to_sql_config
                 .to_sql(self, context)
                 .unwrap_or_else(|| item.to_sql(context)),
+            SqlGraphEntity::OperatorClass(item) => item
+                .to_sql_config
+                .to_sql(self, context)
+                .unwrap_or_else(|| item.to_sql(context)),
             SqlGraphEntity::Aggregate(item) => item
                 .to_sql_config
                 .to_sql(self, context)
                 .unwrap_or_else(|| item.to_sql(context)),
+            SqlGraphEntity::TextSearchParser(item) => item
+                .to_sql_config
+                .to_sql(self, context)
+                .unwrap_or_else(|| item.to_sql(context)),
+            SqlGraphEntity::TextSearchTemplate(item) => item
+                .to_sql_config
+                .to_sql(self, context)
+                .unwrap_or_else(|| item.to_sql(context)),
             SqlGraphEntity::ExtensionRoot(item) => item.to_sql(context),
         }
     }
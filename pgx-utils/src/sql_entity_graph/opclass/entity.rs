@@ -0,0 +1,136 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use crate::sql_entity_graph::{
+    pgx_sql::PgxSql,
+    to_sql::{entity::ToSqlConfigEntity, ToSql},
+    SqlGraphEntity, SqlGraphIdentifier,
+};
+use core::any::TypeId;
+use eyre::eyre;
+use std::cmp::Ordering;
+
+/// The output of a [`PgOperatorClass`](crate::sql_entity_graph::opclass::PgOperatorClass) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PgOperatorClassEntity {
+    pub name: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub id: TypeId,
+    /// The type stored in the index, the class's `STORAGE` if it isn't `id`
+    pub storage_id: Option<TypeId>,
+    pub access_method: &'static str,
+    /// `(strategy number, operator)`
+    pub operators: &'static [(u16, &'static str)],
+    /// `(support number, function name)`
+    pub functions: Vec<(u16, &'static str)>,
+    pub to_sql_config: ToSqlConfigEntity,
+}
+
+impl PgOperatorClassEntity {
+    /// The name of each operator, without its argument types or `FOR ORDER BY`/`FOR SEARCH`.
+    pub(crate) fn operator_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.operators
+            .iter()
+            .filter_map(|(_, operator)| operator.split_whitespace().next())
+    }
+
+    /// The `STORAGE` type, if it differs from the indexed type.
+    pub(crate) fn storage(&self) -> Option<TypeId> {
+        self.storage_id.filter(|storage_id| *storage_id != self.id)
+    }
+}
+
+impl Ord for PgOperatorClassEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.line.cmp(&other.line))
+    }
+}
+
+impl PartialOrd for PgOperatorClassEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Into<SqlGraphEntity> for PgOperatorClassEntity {
+    fn into(self) -> SqlGraphEntity {
+        SqlGraphEntity::OperatorClass(self)
+    }
+}
+
+impl SqlGraphIdentifier for PgOperatorClassEntity {
+    fn dot_identifier(&self) -> String {
+        format!("opclass {} {}", self.access_method, self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        format!("{}::{}_ops", self.full_path, self.access_method)
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PgOperatorClassEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &PgxSql) -> eyre::Result<String> {
+        let self_index = context.operator_classes[self];
+        let schema = context.schema_prefix_for(&self_index);
+        let ty = context
+            .type_id_to_sql_type(self.id)
+            .unwrap_or_else(|| self.name.to_string());
+
+        let mut items = Vec::new();
+        for (number, operator) in self.operators {
+            items.push(format!("\tOPERATOR {} {}", number, operator));
+        }
+        for (number, function) in &self.functions {
+            items.push(format!(
+                "\tFUNCTION {} {}\"{}\" /* {}::{} */",
+                number, schema, function, self.module_path, function
+            ));
+        }
+        if let Some(storage_id) = self.storage() {
+            let storage = context.type_id_to_sql_type(storage_id).ok_or_else(|| {
+                eyre!(
+                    "Could not find the SQL type of the `{}` operator class's storage",
+                    self.full_path
+                )
+            })?;
+            items.push(format!("\tSTORAGE {}", storage));
+        }
+
+        let sql = format!(
+            "\n\
+                            -- {file}:{line}\n\
+                            -- {full_path}\n\
+                            CREATE OPERATOR CLASS {schema}{name}_{access_method}_ops DEFAULT FOR TYPE {ty} USING {access_method} AS\n\
+                                {items};\
+                            ",
+            schema = schema,
+            name = self.name,
+            access_method = self.access_method,
+            ty = ty,
+            items = items.join(",\n"),
+            full_path = self.full_path,
+            file = self.file,
+            line = self.line,
+        );
+        tracing::trace!(%sql);
+        Ok(sql)
+    }
+}
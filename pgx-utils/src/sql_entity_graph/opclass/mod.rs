@@ -0,0 +1,398 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+pub mod entity;
+
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    spanned::Spanned,
+    ItemFn, ItemImpl, Path,
+};
+
+use crate::sql_entity_graph::{
+    text_search_parser::{get_target_path, has_impl_func_by_name},
+    ToSqlConfig,
+};
+
+/// The index access methods `#[pg_opclass]` supports, by the trait implemented for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccessMethod {
+    Gist,
+    Gin,
+    SpGist,
+    Brin,
+}
+
+impl AccessMethod {
+    fn from_trait(name: &str) -> Option<Self> {
+        match name {
+            "GistOpClass" => Some(AccessMethod::Gist),
+            "GinOpClass" => Some(AccessMethod::Gin),
+            "SpGistOpClass" => Some(AccessMethod::SpGist),
+            "BrinOpClass" => Some(AccessMethod::Brin),
+            _ => None,
+        }
+    }
+
+    fn trait_ident(&self) -> Ident {
+        let name = match self {
+            AccessMethod::Gist => "GistOpClass",
+            AccessMethod::Gin => "GinOpClass",
+            AccessMethod::SpGist => "SpGistOpClass",
+            AccessMethod::Brin => "BrinOpClass",
+        };
+        Ident::new(name, Span::call_site())
+    }
+
+    fn sql(&self) -> &'static str {
+        match self {
+            AccessMethod::Gist => "gist",
+            AccessMethod::Gin => "gin",
+            AccessMethod::SpGist => "spgist",
+            AccessMethod::Brin => "brin",
+        }
+    }
+
+    /// The associated type stored in the index, if it can differ from the indexed type.
+    fn storage_type(&self) -> Option<Ident> {
+        match self {
+            AccessMethod::Gist | AccessMethod::Gin => Some(Ident::new("Key", Span::call_site())),
+            AccessMethod::Brin => Some(Ident::new("Summary", Span::call_site())),
+            AccessMethod::SpGist => None,
+        }
+    }
+
+    /// The support functions, by support number.
+    fn support_functions(&self) -> Vec<SupportFunction> {
+        match self {
+            AccessMethod::Gist => vec![
+                SupportFunction::new(
+                    1,
+                    "consistent",
+                    &["entry", "query", "strategy", "subtype", "recheck"],
+                    "bool",
+                ),
+                SupportFunction::new(2, "union", &["entryvec", "size"], "pgx::Internal"),
+                SupportFunction::new(3, "compress", &["entry"], "pgx::Internal"),
+                SupportFunction::new(4, "decompress", &["entry"], "pgx::Internal"),
+                SupportFunction::new(
+                    5,
+                    "penalty",
+                    &["original", "new", "penalty"],
+                    "pgx::Internal",
+                ),
+                SupportFunction::new(6, "picksplit", &["entryvec", "split"], "pgx::Internal"),
+                SupportFunction::new(7, "same", &["a", "b", "result"], "pgx::Internal"),
+                SupportFunction::new(
+                    8,
+                    "distance",
+                    &["entry", "query", "strategy", "subtype", "recheck"],
+                    "f64",
+                )
+                .optional(),
+            ],
+            AccessMethod::Gin => vec![
+                SupportFunction::new(1, "compare", &["a", "b"], "i32"),
+                SupportFunction::new(
+                    2,
+                    "extract_value",
+                    &["value", "nkeys", "null_flags"],
+                    "pgx::Internal",
+                ),
+                SupportFunction::new(
+                    3,
+                    "extract_query",
+                    &[
+                        "query",
+                        "nkeys",
+                        "strategy",
+                        "partial_match",
+                        "extra_data",
+                        "null_flags",
+                        "search_mode",
+                    ],
+                    "pgx::Internal",
+                ),
+                SupportFunction::new(
+                    4,
+                    "consistent",
+                    &[
+                        "check",
+                        "strategy",
+                        "query",
+                        "nkeys",
+                        "extra_data",
+                        "recheck",
+                        "query_keys",
+                        "null_flags",
+                    ],
+                    "bool",
+                ),
+                SupportFunction::new(
+                    6,
+                    "tri_consistent",
+                    &[
+                        "check",
+                        "strategy",
+                        "query",
+                        "nkeys",
+                        "extra_data",
+                        "query_keys",
+                        "null_flags",
+                    ],
+                    "i8",
+                )
+                .optional(),
+            ],
+            AccessMethod::SpGist => vec![
+                SupportFunction::new(1, "config", &["input", "output"], "()"),
+                SupportFunction::new(2, "choose", &["input", "output"], "()"),
+                SupportFunction::new(3, "picksplit", &["input", "output"], "()"),
+                SupportFunction::new(4, "inner_consistent", &["input", "output"], "()"),
+                SupportFunction::new(5, "leaf_consistent", &["input", "output"], "bool"),
+            ],
+            AccessMethod::Brin => vec![
+                SupportFunction::new(1, "opcinfo", &["typoid"], "pgx::Internal"),
+                SupportFunction::new(
+                    2,
+                    "add_value",
+                    &["desc", "column", "value", "is_null"],
+                    "bool",
+                ),
+                SupportFunction::new(3, "consistent", &["desc", "column", "key"], "bool"),
+                SupportFunction::new(4, "union", &["desc", "a", "b"], "bool"),
+            ],
+        }
+    }
+}
+
+/// A support function of an [`AccessMethod`], whose arguments are all `internal`.
+///
+/// The `name` is both the suffix of the generated `#[pg_extern]` and the name of the `pgx` helper
+/// it calls, minus the access method prefix.
+struct SupportFunction {
+    number: u16,
+    name: &'static str,
+    /// Only declared if implemented in the `#[pg_opclass]` block
+    optional: bool,
+    args: &'static [&'static str],
+    returns: &'static str,
+}
+
+impl SupportFunction {
+    fn new(
+        number: u16,
+        name: &'static str,
+        args: &'static [&'static str],
+        returns: &'static str,
+    ) -> Self {
+        SupportFunction {
+            number,
+            name,
+            optional: false,
+            args,
+            returns,
+        }
+    }
+
+    fn optional(self) -> Self {
+        SupportFunction {
+            optional: true,
+            ..self
+        }
+    }
+}
+
+/// A parsed `#[pg_opclass]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the declaration for a [`PgOperatorClassEntity`][crate::sql_entity_graph::PgOperatorClassEntity].
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgOperatorClass;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PgOperatorClass = parse_quote! {
+///     impl BrinOpClass for Example {
+///         const OPERATORS: &'static [(u16, &'static str)] = &[(3, "=")];
+///         type Summary = Example;
+///         type Query = Example;
+///         fn add_value(summary: Option<&Example>, value: Example) -> Option<Example> { None }
+///         fn consistent(summary: &Example, query: Example, strategy: u16) -> bool { true }
+///         fn union(a: Example, b: Example) -> Example { a }
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgOperatorClass {
+    item_impl: ItemImpl,
+    target_path: Path,
+    target_ident: Ident,
+    access_method: AccessMethod,
+    pg_externs: Vec<ItemFn>,
+    functions: Vec<(u16, Ident)>,
+    to_sql_config: ToSqlConfig,
+}
+
+impl PgOperatorClass {
+    pub fn new(item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let to_sql_config =
+            ToSqlConfig::from_attributes(item_impl.attrs.as_slice())?.unwrap_or_default();
+        let target_path = get_target_path(&item_impl, "#[pg_opclass]")?;
+        let target_ident = target_path
+            .segments
+            .last()
+            .map(|segment| segment.ident.clone())
+            .ok_or_else(|| {
+                syn::Error::new(
+                    target_path.span(),
+                    "`#[pg_opclass]` only works with types whose path have a final segment.",
+                )
+            })?;
+
+        let access_method = item_impl
+            .trait_
+            .as_ref()
+            .and_then(|(_, path, _)| path.segments.last())
+            .and_then(|last| AccessMethod::from_trait(&last.ident.to_string()))
+            .ok_or_else(|| {
+                syn::Error::new(
+                    item_impl.span(),
+                    "`#[pg_opclass]` only works with the `GistOpClass`, `GinOpClass`, `SpGistOpClass` or `BrinOpClass` traits.",
+                )
+            })?;
+
+        let fn_prefix = format!(
+            "{}_{}",
+            target_ident.to_string().to_case(Case::Snake),
+            access_method.sql()
+        );
+        let mut pg_externs = Vec::new();
+        let mut functions = Vec::new();
+        for function in access_method.support_functions() {
+            if function.optional && !has_impl_func_by_name(&item_impl, function.name) {
+                continue;
+            }
+            let fn_name = Ident::new(
+                &format!("{}_{}", fn_prefix, function.name),
+                target_ident.span(),
+            );
+            let helper = Ident::new(
+                &format!("{}_{}", access_method.sql(), function.name),
+                target_ident.span(),
+            );
+            let args = function
+                .args
+                .iter()
+                .map(|arg| Ident::new(arg, target_ident.span()))
+                .collect::<Vec<_>>();
+            let returns = match function.returns {
+                "()" => quote! {},
+                returns => {
+                    let returns: syn::Type = syn::parse_str(returns)?;
+                    quote! { -> #returns }
+                }
+            };
+            pg_externs.push(parse_quote! {
+                #[allow(non_snake_case)]
+                #[pg_extern(immutable, parallel_safe)]
+                fn #fn_name(#(#args: pgx::Internal),*) #returns {
+                    unsafe { pgx::#helper::<#target_path>(#(#args),*) }
+                }
+            });
+            functions.push((function.number, fn_name));
+        }
+
+        Ok(Self {
+            item_impl,
+            target_path,
+            target_ident,
+            access_method,
+            pg_externs,
+            functions,
+            to_sql_config,
+        })
+    }
+
+    fn entity_tokens(&self) -> ItemFn {
+        let target_path = &self.target_path;
+        let target_ident = &self.target_ident;
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!(
+                "__pgx_internals_opclass_{}_{}",
+                self.target_ident.to_string().to_case(Case::Snake),
+                self.access_method.sql(),
+            ),
+            self.target_ident.span(),
+        );
+        let trait_ident = self.access_method.trait_ident();
+        let access_method = self.access_method.sql();
+        let storage_id = match self.access_method.storage_type() {
+            Some(storage) => quote! {
+                Some(core::any::TypeId::of::<<#target_path as pgx::#trait_ident>::#storage>())
+            },
+            None => quote! { None },
+        };
+        let function_numbers = self.functions.iter().map(|(number, _)| number);
+        let function_names = self.functions.iter().map(|(_, name)| name);
+        let to_sql_config = &self.to_sql_config;
+
+        parse_quote! {
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> ::pgx::utils::sql_entity_graph::SqlGraphEntity {
+                let submission = ::pgx::utils::sql_entity_graph::PgOperatorClassEntity {
+                    name: stringify!(#target_ident),
+                    file: file!(),
+                    line: line!(),
+                    full_path: ::core::any::type_name::<#target_path>(),
+                    module_path: module_path!(),
+                    id: core::any::TypeId::of::<#target_path>(),
+                    storage_id: #storage_id,
+                    access_method: #access_method,
+                    operators: <#target_path as pgx::#trait_ident>::OPERATORS,
+                    functions: vec![#( (#function_numbers, stringify!(#function_names)) ),*],
+                    to_sql_config: #to_sql_config,
+                };
+                ::pgx::utils::sql_entity_graph::SqlGraphEntity::OperatorClass(submission)
+            }
+        }
+    }
+}
+
+impl Parse for PgOperatorClass {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgOperatorClass {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let entity_fn = self.entity_tokens();
+        let impl_item = &self.item_impl;
+        let pg_externs = self.pg_externs.iter();
+        let inv = quote! {
+            #impl_item
+
+            #(#pg_externs)*
+
+            #entity_fn
+        };
+        tokens.append_all(inv);
+    }
+}
@@ -86,7 +86,7 @@ impl PgExternArgument {
             _ => None,
         };
 
-        // We special case ignore `*mut pg_sys::FunctionCallInfoData`
+        // We special case ignore `*mut pg_sys::FunctionCallInfoData` and a window function's `WindowContext`
         match true_ty {
             syn::Type::Reference(ref mut ty_ref) => {
                 if let Some(ref mut lifetime) = &mut ty_ref.lifetime {
@@ -97,17 +97,20 @@ impl PgExternArgument {
                 let segments = &mut path.path;
                 let mut saw_pg_sys = false;
                 let mut saw_functioncallinfobasedata = false;
+                let mut saw_windowcontext = false;
 
                 for segment in &mut segments.segments {
                     let ident_string = segment.ident.to_string();
                     match ident_string.as_str() {
                         "pg_sys" => saw_pg_sys = true,
                         "FunctionCallInfo" => saw_functioncallinfobasedata = true,
+                        "WindowContext" => saw_windowcontext = true,
                         _ => (),
                     }
                 }
                 if (saw_pg_sys && saw_functioncallinfobasedata)
                     || (saw_functioncallinfobasedata && segments.segments.len() == 1)
+                    || saw_windowcontext
                 {
                     return Ok(None);
                 } else {
@@ -23,6 +23,7 @@ pub enum Attribute {
     Volatile,
     Raw,
     NoGuard,
+    Materialize,
     ParallelSafe,
     ParallelUnsafe,
     ParallelRestricted,
@@ -30,12 +31,14 @@ pub enum Attribute {
     Schema(syn::LitStr),
     Name(syn::LitStr),
     Cost(syn::Expr),
+    Support(syn::Path),
     Requires(Punctuated<PositioningRef, Token![,]>),
     Sql(ToSqlConfig),
 }
 
 impl Attribute {
-    pub(crate) fn to_sql_entity_graph_tokens(&self) -> TokenStream2 {
+    /// `func` is the function the attribute is applied to
+    pub(crate) fn to_sql_entity_graph_tokens(&self, func: &syn::Ident) -> TokenStream2 {
         match self {
             Attribute::Immutable => quote! { ::pgx::utils::ExternArgs::Immutable },
             Attribute::Strict => quote! { ::pgx::utils::ExternArgs::Strict },
@@ -43,6 +46,7 @@ impl Attribute {
             Attribute::Volatile => quote! { ::pgx::utils::ExternArgs::Volatile },
             Attribute::Raw => quote! { ::pgx::utils::ExternArgs::Raw },
             Attribute::NoGuard => quote! { ::pgx::utils::ExternArgs::NoGuard },
+            Attribute::Materialize => quote! { ::pgx::utils::ExternArgs::Materialize },
             Attribute::ParallelSafe => {
                 quote! { ::pgx::utils::ExternArgs::ParallelSafe }
             }
@@ -64,6 +68,10 @@ impl Attribute {
             Attribute::Cost(s) => {
                 quote! { ::pgx::utils::ExternArgs::Cost(format!("{}", #s)) }
             }
+            Attribute::Support(path) => {
+                let wrapper = support_wrapper_name(func, path).to_string();
+                quote! { ::pgx::utils::ExternArgs::Support(String::from(#wrapper)) }
+            }
             Attribute::Requires(items) => {
                 let items_iter = items
                     .iter()
@@ -88,6 +96,7 @@ impl ToTokens for Attribute {
             Attribute::Volatile => quote! { volatile },
             Attribute::Raw => quote! { raw },
             Attribute::NoGuard => quote! { no_guard },
+            Attribute::Materialize => quote! { materialize },
             Attribute::ParallelSafe => {
                 quote! { parallel_safe }
             }
@@ -109,6 +118,9 @@ impl ToTokens for Attribute {
             Attribute::Cost(s) => {
                 quote! { cost = #s }
             }
+            Attribute::Support(path) => {
+                quote! { support = #path }
+            }
             Attribute::Requires(items) => {
                 let items_iter = items
                     .iter()
@@ -135,6 +147,7 @@ impl Parse for Attribute {
             "volatile" => Self::Volatile,
             "raw" => Self::Raw,
             "no_guard" => Self::NoGuard,
+            "materialize" => Self::Materialize,
             "parallel_safe" => Self::ParallelSafe,
             "parallel_unsafe" => Self::ParallelUnsafe,
             "parallel_restricted" => Self::ParallelRestricted,
@@ -158,6 +171,11 @@ impl Parse for Attribute {
                 let literal: syn::Expr = input.parse()?;
                 Self::Cost(literal)
             }
+            "support" => {
+                let _eq: Token![=] = input.parse()?;
+                let path: syn::Path = input.parse()?;
+                Self::Support(path)
+            }
             "requires" => {
                 let _eq: syn::token::Eq = input.parse()?;
                 let content;
@@ -196,3 +214,16 @@ impl Parse for Attribute {
         Ok(found)
     }
 }
+
+/// The name of the `internal -> internal` function generated for the `support = path` attribute
+/// of `func`, which calls the Rust function at `path` and is `func`'s `SUPPORT` in SQL.
+///
+/// Each function gets its own wrapper, so several functions can share one support function.
+pub(crate) fn support_wrapper_name(func: &syn::Ident, path: &syn::Path) -> syn::Ident {
+    let support = &path
+        .segments
+        .last()
+        .expect("a support function path has at least one segment")
+        .ident;
+    syn::Ident::new(&format!("{}_support_wrapper", func), support.span())
+}
@@ -0,0 +1,98 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use crate::sql_entity_graph::{PgCastContext, PgCastMethod};
+
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens, TokenStreamExt};
+
+/// A parsed `#[pg_cast]` cast.
+///
+/// It is created from the arguments of `#[pg_cast]`, which also takes the arguments of
+/// `#[pg_extern]`, and attached to the [`PgExtern`](crate::sql_entity_graph::PgExtern) of its
+/// function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PgCast {
+    pub context: PgCastContext,
+    pub method: PgCastMethod,
+}
+
+impl PgCast {
+    /// Split the arguments of a `#[pg_cast]` into the cast and the remaining `#[pg_extern]`
+    /// arguments.
+    pub fn from_attr(attr: TokenStream2) -> Result<(Self, TokenStream2), syn::Error> {
+        let mut context = None;
+        let mut method = None;
+        let mut extern_attrs = Vec::new();
+
+        let mut args = vec![Vec::new()];
+        for token in attr {
+            match token {
+                TokenTree::Punct(ref punct) if punct.as_char() == ',' => args.push(Vec::new()),
+                token => args.last_mut().unwrap().push(token),
+            }
+        }
+
+        for arg in args.into_iter().filter(|arg| !arg.is_empty()) {
+            let ident = match arg.as_slice() {
+                [TokenTree::Ident(ident)] => ident.to_string(),
+                _ => String::new(),
+            };
+            let (new_context, new_method) = match ident.as_str() {
+                "explicit" => (Some(PgCastContext::Explicit), None),
+                "assignment" => (Some(PgCastContext::Assignment), None),
+                "implicit" => (Some(PgCastContext::Implicit), None),
+                "inout" => (None, Some(PgCastMethod::InOut)),
+                "without_function" => (None, Some(PgCastMethod::WithoutFunction)),
+                _ => {
+                    extern_attrs.push(arg.into_iter().collect::<TokenStream2>());
+                    continue;
+                }
+            };
+
+            if (new_context.is_some() && context.is_some())
+                || (new_method.is_some() && method.is_some())
+            {
+                return Err(syn::Error::new(
+                    arg[0].span(),
+                    "expected `#[pg_cast(implicit | assignment | explicit)]`, with at most one of `inout` or `without_function`",
+                ));
+            }
+            context = context.or(new_context);
+            method = method.or(new_method);
+        }
+
+        let cast = PgCast {
+            context: context.unwrap_or(PgCastContext::Explicit),
+            method: method.unwrap_or(PgCastMethod::Function),
+        };
+        Ok((cast, quote! { #(#extern_attrs),* }))
+    }
+}
+
+impl ToTokens for PgCast {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let context = match self.context {
+            PgCastContext::Explicit => quote! { Explicit },
+            PgCastContext::Assignment => quote! { Assignment },
+            PgCastContext::Implicit => quote! { Implicit },
+        };
+        let method = match self.method {
+            PgCastMethod::Function => quote! { Function },
+            PgCastMethod::InOut => quote! { InOut },
+            PgCastMethod::WithoutFunction => quote! { WithoutFunction },
+        };
+        let quoted = quote! {
+            ::pgx::utils::sql_entity_graph::PgCastEntity {
+                context: ::pgx::utils::sql_entity_graph::PgCastContext::#context,
+                method: ::pgx::utils::sql_entity_graph::PgCastMethod::#method,
+            }
+        };
+        tokens.append_all(quoted);
+    }
+}
@@ -0,0 +1,49 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use serde::{Deserialize, Serialize};
+
+/// The output of a [`PgCast`](crate::sql_entity_graph::PgCast) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PgCastEntity {
+    pub context: PgCastContext,
+    pub method: PgCastMethod,
+}
+
+/// When Postgres may apply a cast without it being asked for.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PgCastContext {
+    /// `#[pg_cast(explicit)]`, the default: only with `CAST(x AS y)` or `x::y`
+    Explicit,
+    /// `#[pg_cast(assignment)]`: also when assigning to a column, `AS ASSIGNMENT`
+    Assignment,
+    /// `#[pg_cast(implicit)]`: in any context, `AS IMPLICIT`
+    Implicit,
+}
+
+/// How a cast converts its source type.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PgCastMethod {
+    /// The default: `WITH FUNCTION`, calling the `#[pg_cast]` function
+    Function,
+    /// `#[pg_cast(inout)]`: `WITH INOUT`, through the source type's output function and the
+    /// target type's input function
+    InOut,
+    /// `#[pg_cast(without_function)]`: `WITHOUT FUNCTION`, for binary-coercible types
+    WithoutFunction,
+}
+
+impl PgCastContext {
+    pub(crate) fn sql(&self) -> &'static str {
+        match self {
+            PgCastContext::Explicit => "",
+            PgCastContext::Assignment => " AS ASSIGNMENT",
+            PgCastContext::Implicit => " AS IMPLICIT",
+        }
+    }
+}
@@ -7,10 +7,12 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 mod argument;
+mod cast;
 mod operator;
 mod returning;
 
 pub use argument::PgExternArgumentEntity;
+pub use cast::{PgCastContext, PgCastEntity, PgCastMethod};
 pub use operator::PgOperatorEntity;
 pub use returning::PgExternReturnEntity;
 
@@ -42,6 +44,8 @@ pub struct PgExternEntity {
     pub fn_args: Vec<PgExternArgumentEntity>,
     pub fn_return: PgExternReturnEntity,
     pub operator: Option<PgOperatorEntity>,
+    pub cast: Option<PgCastEntity>,
+    pub window: bool,
     pub to_sql_config: ToSqlConfigEntity,
 }
 
@@ -109,8 +113,10 @@ impl ToSql for PgExternEntity {
 
         let fn_sql = format!("\
                                 CREATE FUNCTION {schema}\"{name}\"({arguments}) {returns}\n\
+                                {window}\
                                 {extern_attrs}\
                                 {search_path}\
+                                {support}\
                                 LANGUAGE c /* Rust */\n\
                                 AS '{module_pathname}', '{unaliased_name}_wrapper';\
                             ",
@@ -239,6 +245,11 @@ impl ToSql for PgExternEntity {
                                  let retval = format!("SET search_path TO {}", search_path.join(", "));
                                  retval + "\n"
                              } else { Default::default() },
+                             window = if self.window { "WINDOW\n" } else { "" },
+                             support = match self.support_sql(context)? {
+                                 Some(support) => format!("SUPPORT {}\n", support),
+                                 None => Default::default(),
+                             },
                              extern_attrs = if extern_attrs.is_empty() {
                                  String::default()
                              } else {
@@ -365,6 +376,160 @@ impl ToSql for PgExternEntity {
         } else {
             ext_sql
         };
+
+        let rendered = if let Some(cast) = &self.cast {
+            let cast_sql = self.cast_sql(cast, context)?;
+            tracing::trace!(sql = %cast_sql);
+            rendered + &cast_sql
+        } else {
+            rendered
+        };
         Ok(rendered)
     }
 }
+
+impl PgExternEntity {
+    /// The name of the `internal -> internal` function generated for `#[pg_extern(support = ...)]`
+    pub(crate) fn support_wrapper(&self) -> Option<&str> {
+        self.extern_attrs.iter().find_map(|attr| match attr {
+            ExternArgs::Support(wrapper) => Some(wrapper.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The schema qualified `SUPPORT` function, if there is one.
+    fn support_sql(&self, context: &PgxSql) -> eyre::Result<Option<String>> {
+        let wrapper = match self.support_wrapper() {
+            Some(wrapper) => wrapper,
+            None => return Ok(None),
+        };
+        let self_index = context.externs[self];
+        let wrapper_path = format!("{}::{}", self.module_path, wrapper);
+        let (wrapper_index, wrapper_entity) = context
+            .graph
+            .neighbors_undirected(self_index)
+            .find_map(|neighbor| match &context.graph[neighbor] {
+                SqlGraphEntity::Function(func) if func.full_path == wrapper_path => {
+                    Some((neighbor, func))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| {
+                eyre!(
+                    "Could not find support function `{}` of `{}` in graph.",
+                    wrapper_path,
+                    self.full_path
+                )
+            })?;
+        Ok(Some(format!(
+            "{schema}\"{name}\"",
+            schema = wrapper_entity
+                .schema
+                .map(|schema| format!("{}.", schema))
+                .unwrap_or_else(|| context.schema_prefix_for(&wrapper_index)),
+            name = wrapper_entity.name,
+        )))
+    }
+
+    /// The `CREATE CAST` of a `#[pg_cast]` function, from its argument's type to its return type.
+    fn cast_sql(&self, cast: &PgCastEntity, context: &PgxSql) -> eyre::Result<String> {
+        let self_index = context.externs[self];
+        let type_graph_index = |id: &core::any::TypeId, full_path: &str| {
+            context
+                .graph
+                .neighbors_undirected(self_index)
+                .find(|neighbor| match &context.graph[*neighbor] {
+                    SqlGraphEntity::Type(ty) => ty.id_matches(id),
+                    SqlGraphEntity::Enum(en) => en.id_matches(id),
+                    SqlGraphEntity::BuiltinType(defined) => defined == full_path,
+                    _ => false,
+                })
+        };
+
+        let source = match self.fn_args.as_slice() {
+            [source] => source,
+            _ => {
+                return Err(eyre!(
+                    "`#[pg_cast]` function `{}` must take exactly one argument.",
+                    self.name
+                ))
+            }
+        };
+        let source_graph_index = type_graph_index(&source.ty_id, source.full_path)
+            .ok_or_else(|| eyre!("Could not find cast source type in graph."))?;
+        let source_sql = format!(
+            "{schema_prefix}{sql_type}",
+            schema_prefix = context.schema_prefix_for(&source_graph_index),
+            sql_type = context
+                .rust_to_sql(source.ty_id, source.ty_source, source.full_path)
+                .ok_or_else(|| eyre!(
+                    "Failed to map argument `{}` type `{}` to SQL type while building cast `{}`.",
+                    source.pattern,
+                    source.full_path,
+                    self.name
+                ))?,
+        );
+
+        let (target_id, target_source, target_full_path) = match &self.fn_return {
+            PgExternReturnEntity::Type {
+                id,
+                source,
+                full_path,
+                ..
+            } => (id, source, full_path),
+            _ => {
+                return Err(eyre!(
+                    "`#[pg_cast]` function `{}` must return a single value.",
+                    self.name
+                ))
+            }
+        };
+        let target_graph_index = type_graph_index(target_id, *target_full_path)
+            .ok_or_else(|| eyre!("Could not find cast target type in graph."))?;
+        let target_sql = format!(
+            "{schema_prefix}{sql_type}",
+            schema_prefix = context.schema_prefix_for(&target_graph_index),
+            sql_type = context
+                .source_only_to_sql_type(target_source)
+                .or_else(|| context.type_id_to_sql_type(*target_id))
+                .ok_or_else(|| eyre!(
+                    "Failed to map return type `{}` to SQL type while building cast `{}`.",
+                    target_full_path,
+                    self.name
+                ))?,
+        );
+
+        let method = match cast.method {
+            PgCastMethod::Function => format!(
+                "WITH FUNCTION {schema}\"{name}\"({source_sql})",
+                schema = self
+                    .schema
+                    .map(|schema| format!("{}.", schema))
+                    .unwrap_or_else(|| context.schema_prefix_for(&self_index)),
+                name = self.name,
+                source_sql = source_sql,
+            ),
+            PgCastMethod::InOut => String::from("WITH INOUT"),
+            PgCastMethod::WithoutFunction => String::from("WITHOUT FUNCTION"),
+        };
+
+        Ok(format!(
+            "\n\n\
+                -- {file}:{line}\n\
+                -- {module_path}::{unaliased_name}\n\
+                CREATE CAST ({source_sql} AS {target_sql}) /* {source_path} AS {target_path} */\n\
+                \t{method}{context};\
+            ",
+            file = self.file,
+            line = self.line,
+            module_path = self.module_path,
+            unaliased_name = self.unaliased_name,
+            source_sql = source_sql,
+            target_sql = target_sql,
+            source_path = source.full_path,
+            target_path = target_full_path,
+            method = method,
+            context = cast.context.sql(),
+        ))
+    }
+}
@@ -8,12 +8,14 @@ Use of this source code is governed by the MIT license that can be found in the
 */
 mod argument;
 mod attribute;
+mod cast;
 pub mod entity;
 mod operator;
 mod returning;
 mod search_path;
 
 pub use argument::PgExternArgument;
+pub use cast::PgCast;
 pub use operator::PgOperator;
 pub use returning::NameMacro;
 
@@ -29,6 +31,7 @@ use quote::{quote, ToTokens, TokenStreamExt};
 use std::convert::TryFrom;
 use syn::{
     parse::{Parse, ParseStream, Parser},
+    parse_quote,
     punctuated::Punctuated,
     Meta, Token,
 };
@@ -59,6 +62,8 @@ pub struct PgExtern {
     attrs: Vec<Attribute>,
     func: syn::ItemFn,
     to_sql_config: ToSqlConfig,
+    cast: Option<PgCast>,
+    window: bool,
 }
 
 impl PgExtern {
@@ -83,6 +88,45 @@ impl PgExtern {
         self.attrs.as_slice()
     }
 
+    /// Does the function return its set in materialize mode, rather than value-per-call?
+    pub fn materialize(&self) -> bool {
+        self.attrs.contains(&Attribute::Materialize)
+    }
+
+    /// The `internal -> internal` function generated for a `support = my_support` attribute,
+    /// which passes the planner's requests to `my_support` as a `pgx::SupportRequest`
+    pub fn support_wrapper(&self) -> Option<syn::ItemFn> {
+        let support = self.attrs.iter().find_map(|a| match a {
+            Attribute::Support(path) => Some(path),
+            _ => None,
+        })?;
+        let wrapper = attribute::support_wrapper_name(&self.func.sig.ident, support);
+        let schema = self
+            .schema()
+            .map(|schema| quote! { , schema = #schema })
+            .unwrap_or_default();
+
+        Some(parse_quote! {
+            #[allow(non_snake_case)]
+            #[pg_extern(immutable, parallel_safe #schema)]
+            fn #wrapper(request: pgx::Internal) -> pgx::Internal {
+                unsafe { pgx::support_request(request, #support) }
+            }
+        })
+    }
+
+    /// Declare the function as a `#[pg_cast]` from its argument's type to its return type
+    pub fn with_cast(mut self, cast: PgCast) -> Self {
+        self.cast = Some(cast);
+        self
+    }
+
+    /// Declare the function as a `#[pg_window]`, a `WINDOW` function
+    pub fn with_window(mut self) -> Self {
+        self.window = true;
+        self
+    }
+
     fn overridden(&self) -> Option<syn::LitStr> {
         let mut span = None;
         let mut retval = None;
@@ -232,6 +276,8 @@ impl PgExtern {
             attrs,
             func,
             to_sql_config: to_sql_config.unwrap_or_default(),
+            cast: None,
+            window: false,
         })
     }
 }
@@ -245,7 +291,7 @@ impl ToTokens for PgExtern {
         let extern_attrs = self
             .attrs
             .iter()
-            .map(|attr| attr.to_sql_entity_graph_tokens())
+            .map(|attr| attr.to_sql_entity_graph_tokens(ident))
             .collect::<Punctuated<_, Token![,]>>();
         let search_path = self.search_path().into_iter();
         let inputs = self.inputs().unwrap();
@@ -260,6 +306,8 @@ impl ToTokens for PgExtern {
             }
         };
         let operator = self.operator().into_iter();
+        let cast = self.cast.iter();
+        let window = self.window;
         let to_sql_config = match self.overridden() {
             None => self.to_sql_config.clone(),
             Some(content) => {
@@ -292,6 +340,8 @@ impl ToTokens for PgExtern {
                     fn_args: vec![#(#inputs),*],
                     fn_return: #returns,
                     operator: None #( .unwrap_or(Some(#operator)) )*,
+                    cast: None #( .unwrap_or(Some(#cast)) )*,
+                    window: #window,
                     to_sql_config: #to_sql_config,
                 };
                 ::pgx::utils::sql_entity_graph::SqlGraphEntity::Function(submission)
@@ -324,6 +374,8 @@ impl Parse for PgExtern {
             attrs,
             func,
             to_sql_config: to_sql_config.unwrap_or_default(),
+            cast: None,
+            window: false,
         })
     }
 }
@@ -6,7 +6,7 @@ All rights reserved.
 
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
-use crate::{anonymonize_lifetimes, anonymonize_lifetimes_in_type_path};
+use crate::{anonymonize_lifetimes, anonymonize_lifetimes_in_type_path, result_ok_type};
 use eyre::eyre;
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens, TokenStreamExt};
@@ -103,6 +103,11 @@ impl TryFrom<&syn::ReturnType> for Returning {
     fn try_from(value: &syn::ReturnType) -> Result<Self, Self::Error> {
         Ok(match &value {
             syn::ReturnType::Default => Returning::None,
+            syn::ReturnType::Type(arrow, ty) if result_ok_type(ty).is_some() => {
+                // the `Err` is raised rather than returned, so the SQL type is that of the `Ok`
+                let ok_type = result_ok_type(ty).unwrap().clone();
+                return Returning::try_from(&syn::ReturnType::Type(*arrow, Box::new(ok_type)));
+            }
             syn::ReturnType::Type(_, ty) => {
                 let mut ty = *ty.clone();
                 anonymonize_lifetimes(&mut ty);
@@ -21,6 +21,7 @@ use crate::sql_entity_graph::{
         SqlDeclared,
     },
     mapping::{RustSourceOnlySqlMapping, RustSqlMapping},
+    opclass::entity::PgOperatorClassEntity,
     pg_extern::entity::{PgExternEntity, PgExternReturnEntity},
     positioning_ref::PositioningRef,
     postgres_enum::entity::PostgresEnumEntity,
@@ -28,6 +29,8 @@ use crate::sql_entity_graph::{
     postgres_ord::entity::PostgresOrdEntity,
     postgres_type::entity::PostgresTypeEntity,
     schema::entity::SchemaEntity,
+    text_search_parser::entity::PgTextSearchParserEntity,
+    text_search_template::entity::PgTextSearchTemplateEntity,
     to_sql::ToSql,
     SqlGraphEntity, SqlGraphIdentifier,
 };
@@ -64,7 +67,10 @@ pub struct PgxSql {
     pub enums: HashMap<PostgresEnumEntity, NodeIndex>,
     pub ords: HashMap<PostgresOrdEntity, NodeIndex>,
     pub hashes: HashMap<PostgresHashEntity, NodeIndex>,
+    pub operator_classes: HashMap<PgOperatorClassEntity, NodeIndex>,
     pub aggregates: HashMap<PgAggregateEntity, NodeIndex>,
+    pub text_search_parsers: HashMap<PgTextSearchParserEntity, NodeIndex>,
+    pub text_search_templates: HashMap<PgTextSearchTemplateEntity, NodeIndex>,
     pub extension_name: String,
     pub versioned_so: bool,
 }
@@ -98,7 +104,10 @@ impl PgxSql {
         let mut enums: Vec<PostgresEnumEntity> = Vec::default();
         let mut ords: Vec<PostgresOrdEntity> = Vec::default();
         let mut hashes: Vec<PostgresHashEntity> = Vec::default();
+        let mut operator_classes: Vec<PgOperatorClassEntity> = Vec::default();
         let mut aggregates: Vec<PgAggregateEntity> = Vec::default();
+        let mut text_search_parsers: Vec<PgTextSearchParserEntity> = Vec::default();
+        let mut text_search_templates: Vec<PgTextSearchTemplateEntity> = Vec::default();
         for entity in entities {
             match entity {
                 SqlGraphEntity::ExtensionRoot(input_control) => {
@@ -126,9 +135,18 @@ impl PgxSql {
                 SqlGraphEntity::Hash(input_hash) => {
                     hashes.push(input_hash);
                 }
+                SqlGraphEntity::OperatorClass(input_opclass) => {
+                    operator_classes.push(input_opclass);
+                }
                 SqlGraphEntity::Aggregate(input_hash) => {
                     aggregates.push(input_hash);
                 }
+                SqlGraphEntity::TextSearchParser(input_parser) => {
+                    text_search_parsers.push(input_parser);
+                }
+                SqlGraphEntity::TextSearchTemplate(input_template) => {
+                    text_search_templates.push(input_template);
+                }
             }
         }
 
@@ -159,6 +177,8 @@ impl PgxSql {
         )?;
         let mapped_ords = initialize_ords(&mut graph, root, bootstrap, finalize, ords)?;
         let mapped_hashes = initialize_hashes(&mut graph, root, bootstrap, finalize, hashes)?;
+        let mapped_operator_classes =
+            initialize_operator_classes(&mut graph, root, bootstrap, finalize, operator_classes)?;
         let mapped_aggregates = initialize_aggregates(
             &mut graph,
             root,
@@ -169,6 +189,20 @@ impl PgxSql {
             &mapped_enums,
             &mapped_types,
         )?;
+        let mapped_text_search_parsers = initialize_text_search_parsers(
+            &mut graph,
+            root,
+            bootstrap,
+            finalize,
+            text_search_parsers,
+        )?;
+        let mapped_text_search_templates = initialize_text_search_templates(
+            &mut graph,
+            root,
+            bootstrap,
+            finalize,
+            text_search_templates,
+        )?;
 
         // Now we can circle back and build up the edge sets.
         connect_schemas(&mut graph, &mapped_schemas, root);
@@ -207,6 +241,14 @@ impl PgxSql {
             &mapped_enums,
             &mapped_externs,
         );
+        connect_operator_classes(
+            &mut graph,
+            &mapped_operator_classes,
+            &mapped_schemas,
+            &mapped_types,
+            &mapped_enums,
+            &mapped_externs,
+        );
         connect_aggregates(
             &mut graph,
             &mapped_aggregates,
@@ -216,6 +258,18 @@ impl PgxSql {
             &mapped_builtin_types,
             &mapped_externs,
         );
+        connect_text_search_parsers(
+            &mut graph,
+            &mapped_text_search_parsers,
+            &mapped_schemas,
+            &mapped_externs,
+        );
+        connect_text_search_templates(
+            &mut graph,
+            &mapped_text_search_templates,
+            &mapped_schemas,
+            &mapped_externs,
+        );
 
         let mut this = Self {
             type_mappings: type_mappings.map(|x| (x.id.clone(), x)).collect(),
@@ -229,7 +283,10 @@ impl PgxSql {
             enums: mapped_enums,
             ords: mapped_ords,
             hashes: mapped_hashes,
+            operator_classes: mapped_operator_classes,
             aggregates: mapped_aggregates,
+            text_search_parsers: mapped_text_search_parsers,
+            text_search_templates: mapped_text_search_templates,
             graph: graph,
             graph_root: root,
             graph_bootstrap: bootstrap,
@@ -356,10 +413,22 @@ impl PgxSql {
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#FFE4E0\", weight = 5, shape = \"diamond\"",
                         node.dot_identifier()
                     ),
+                    SqlGraphEntity::OperatorClass(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#FFE4E0\", weight = 5, shape = \"diamond\"",
+                        node.dot_identifier()
+                    ),
                     SqlGraphEntity::Aggregate(_item) => format!(
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#FFE4E0\", weight = 5, shape = \"diamond\"",
                         node.dot_identifier()
                     ),
+                    SqlGraphEntity::TextSearchParser(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#E0EBD8\", weight = 5, shape = \"diamond\"",
+                        node.dot_identifier()
+                    ),
+                    SqlGraphEntity::TextSearchTemplate(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#E0EBD8\", weight = 5, shape = \"diamond\"",
+                        node.dot_identifier()
+                    ),
                     SqlGraphEntity::CustomSql(_item) => format!(
                         "label = \"{}\", weight = 3, shape = \"signature\"",
                         node.dot_identifier()
@@ -488,7 +557,15 @@ impl PgxSql {
     }
 
     pub fn source_only_to_sql_type(&self, ty_source: &str) -> Option<String> {
-        self.source_mappings.get(ty_source).map(|f| f.sql.clone())
+        self.source_mappings
+            .get(ty_source)
+            .or_else(|| {
+                // Generic wrappers (like `JsonBOf<_>`) are mapped with their type parameter erased
+                self.source_mappings
+                    .values()
+                    .find(|mapping| matches_erased_generic(&mapping.rust, ty_source))
+            })
+            .map(|f| f.sql.clone())
     }
 
     pub fn map_type_to_sql_type<T: 'static>(&mut self, sql: impl AsRef<str> + Debug) {
@@ -934,10 +1011,41 @@ fn connect_externs(
                         }
                     }
                 }
+                crate::ExternArgs::Support(wrapper) => {
+                    let wrapper_path = format!("{}::{}", item.module_path, wrapper);
+                    let wrapper_index = externs
+                        .iter()
+                        .find_map(|(other, &other_index)| {
+                            (other.full_path == wrapper_path).then(|| other_index)
+                        })
+                        .ok_or_else(|| {
+                            eyre!("Could not find support function `{}`", wrapper_path)
+                        })?;
+                    tracing::debug!(from = %item.rust_identifier(), to = %wrapper_path, "Adding Extern after its support function");
+                    graph.add_edge(wrapper_index, index, SqlGraphRelationship::RequiredBy);
+                }
                 _ => (),
             }
         }
 
+        // a type's typmod_in, typmod_out and subscript handler functions don't take or return it,
+        // but their SQL is emitted along with the type's
+        for (ty_item, &ty_index) in types {
+            let typmod_fns = ty_item.typmod_fns.as_ref();
+            let is_unconnected_owned_fn = [
+                typmod_fns.map(|fns| fns.typmod_in_fn),
+                typmod_fns.map(|fns| fns.typmod_out_fn),
+                ty_item.subscript_fn,
+            ]
+            .iter()
+            .flatten()
+            .any(|func| item.full_path == format!("{}::{}", ty_item.module_path, func));
+            if is_unconnected_owned_fn {
+                tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Type (due to typmod or subscript fn) edge");
+                graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredBy);
+            }
+        }
+
         for arg in &item.fn_args {
             let mut found = false;
             for (ty_item, &ty_index) in types {
@@ -1232,6 +1340,96 @@ fn connect_hashes(
     }
 }
 
+#[tracing::instrument(level = "error", skip_all)]
+fn initialize_operator_classes(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    operator_classes: Vec<PgOperatorClassEntity>,
+) -> eyre::Result<HashMap<PgOperatorClassEntity, NodeIndex>> {
+    let mut mapped_operator_classes = HashMap::default();
+    for item in operator_classes {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_operator_classes.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_operator_classes)
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn connect_operator_classes(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    operator_classes: &HashMap<PgOperatorClassEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    types: &HashMap<PostgresTypeEntity, NodeIndex>,
+    enums: &HashMap<PostgresEnumEntity, NodeIndex>,
+    externs: &HashMap<PgExternEntity, NodeIndex>,
+) {
+    for (item, &index) in operator_classes {
+        make_schema_connection(
+            graph,
+            "OperatorClass",
+            index,
+            &item.rust_identifier(),
+            item.module_path,
+            schemas,
+        );
+
+        make_type_or_enum_connection(
+            graph,
+            "OperatorClass",
+            index,
+            &item.rust_identifier(),
+            &item.id,
+            types,
+            enums,
+        );
+        if let Some(storage_id) = item.storage() {
+            // the storage may also be a builtin type, which has no node
+            make_type_or_enum_connection(
+                graph,
+                "OperatorClass",
+                index,
+                &item.rust_identifier(),
+                &storage_id,
+                types,
+                enums,
+            );
+        }
+
+        for (_, fn_name) in &item.functions {
+            make_extern_connection(
+                graph,
+                "OperatorClass",
+                index,
+                &item.rust_identifier(),
+                &(item.module_path.to_string() + "::" + fn_name),
+                externs,
+            );
+        }
+
+        // the operators of the class are `#[pg_operator]`s taking the indexed type
+        for (extern_item, &extern_index) in externs {
+            let operator_matches = match &extern_item.operator {
+                Some(operator) => item
+                    .operator_names()
+                    .any(|name| operator.opname == Some(name)),
+                None => false,
+            };
+            let arg_matches = extern_item
+                .fn_args
+                .first()
+                .map_or(false, |arg| arg.ty_id == item.id);
+            if operator_matches && arg_matches {
+                tracing::debug!(from = ?item.full_path, to = extern_item.full_path, "Adding OperatorClass after Extern edge");
+                graph.add_edge(extern_index, index, SqlGraphRelationship::RequiredBy);
+            }
+        }
+    }
+}
+
 fn initialize_aggregates(
     graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
     root: NodeIndex,
@@ -1476,6 +1674,102 @@ fn connect_aggregates(
     }
 }
 
+#[tracing::instrument(level = "error", skip_all)]
+fn initialize_text_search_parsers(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    text_search_parsers: Vec<PgTextSearchParserEntity>,
+) -> eyre::Result<HashMap<PgTextSearchParserEntity, NodeIndex>> {
+    let mut mapped_text_search_parsers = HashMap::default();
+    for item in text_search_parsers {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_text_search_parsers.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_text_search_parsers)
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn connect_text_search_parsers(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    text_search_parsers: &HashMap<PgTextSearchParserEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    externs: &HashMap<PgExternEntity, NodeIndex>,
+) {
+    for (item, &index) in text_search_parsers {
+        make_schema_connection(
+            graph,
+            "TextSearchParser",
+            index,
+            &item.rust_identifier(),
+            item.module_path,
+            schemas,
+        );
+
+        for fn_name in item.fn_names() {
+            make_extern_connection(
+                graph,
+                "TextSearchParser",
+                index,
+                &item.rust_identifier(),
+                &(item.module_path.to_string() + "::" + fn_name),
+                externs,
+            );
+        }
+    }
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn initialize_text_search_templates(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    text_search_templates: Vec<PgTextSearchTemplateEntity>,
+) -> eyre::Result<HashMap<PgTextSearchTemplateEntity, NodeIndex>> {
+    let mut mapped_text_search_templates = HashMap::default();
+    for item in text_search_templates {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_text_search_templates.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_text_search_templates)
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn connect_text_search_templates(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    text_search_templates: &HashMap<PgTextSearchTemplateEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    externs: &HashMap<PgExternEntity, NodeIndex>,
+) {
+    for (item, &index) in text_search_templates {
+        make_schema_connection(
+            graph,
+            "TextSearchTemplate",
+            index,
+            &item.rust_identifier(),
+            item.module_path,
+            schemas,
+        );
+
+        for fn_name in [item.init, item.lexize] {
+            make_extern_connection(
+                graph,
+                "TextSearchTemplate",
+                index,
+                &item.rust_identifier(),
+                &(item.module_path.to_string() + "::" + fn_name),
+                externs,
+            );
+        }
+    }
+}
+
 fn make_schema_connection(
     graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
     kind: &str,
@@ -1545,3 +1839,75 @@ fn make_type_or_enum_connection(
 
     found
 }
+
+/// Does `ty_source` match `pattern`, where the `_` in `pattern` stands in for any type?
+///
+/// For example `Option<JsonBOf<_>>` matches `Option<JsonBOf<MyStruct>>`, and also
+/// `Option<pgx::JsonBOf<MyStruct>>`, as paths are ignored.
+fn matches_erased_generic(pattern: &str, ty_source: &str) -> bool {
+    let ty_source = &strip_paths(ty_source);
+    let (prefix, suffix) = match pattern.split_once("<_>") {
+        Some((prefix, suffix)) => (format!("{}<", prefix), format!(">{}", suffix)),
+        None => return false,
+    };
+    if ty_source.len() <= prefix.len() + suffix.len()
+        || !ty_source.starts_with(&prefix)
+        || !ty_source.ends_with(&suffix)
+    {
+        return false;
+    }
+
+    // the erased type must be a single, balanced type
+    let erased = &ty_source[prefix.len()..ty_source.len() - suffix.len()];
+    let mut depth = 0i32;
+    for c in erased.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ => (),
+        }
+        if depth < 0 {
+            return false;
+        }
+    }
+    depth == 0
+}
+
+/// Remove the paths from the types in `ty_source`, such as `Vec<pgx::JsonBOf<crate::Foo>>` to
+/// `Vec<JsonBOf<Foo>>`
+fn strip_paths(ty_source: &str) -> String {
+    let mut stripped = String::with_capacity(ty_source.len());
+    let mut segment = String::new();
+    for c in ty_source.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            stripped.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            stripped.push(c);
+        }
+    }
+    stripped.push_str(segment.rsplit("::").next().unwrap_or_default());
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches_erased_generic;
+
+    #[test]
+    fn erased_generic() {
+        assert!(matches_erased_generic("JsonBOf<_>", "JsonBOf<Foo>"));
+        assert!(matches_erased_generic("JsonBOf<_>", "pgx::JsonBOf<Foo>"));
+        assert!(matches_erased_generic(
+            "JsonBOf<_>",
+            "::pgx::JsonBOf<crate::Foo>"
+        ));
+        assert!(matches_erased_generic(
+            "Option<JsonBOf<_>>",
+            "Option<pgx::JsonBOf<Vec<Foo>>>"
+        ));
+        assert!(!matches_erased_generic("JsonBOf<_>", "JsonOf<Foo>"));
+        assert!(!matches_erased_generic("JsonBOf<_>", "Vec<JsonBOf<Foo>>"));
+    }
+}
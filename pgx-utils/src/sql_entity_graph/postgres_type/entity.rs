@@ -8,12 +8,14 @@ Use of this source code is governed by the MIT license that can be found in the
 */
 use crate::sql_entity_graph::{
     mapping::RustSqlMapping,
+    pg_extern::entity::PgExternEntity,
     pgx_sql::PgxSql,
     to_sql::{entity::ToSqlConfigEntity, ToSql},
     SqlGraphEntity, SqlGraphIdentifier,
 };
 
 use eyre::eyre;
+use petgraph::graph::NodeIndex;
 use std::{
     cmp::Ordering,
     hash::{Hash, Hasher},
@@ -32,9 +34,43 @@ pub struct PostgresTypeEntity {
     pub in_fn_module_path: String,
     pub out_fn: &'static str,
     pub out_fn_module_path: String,
+    pub send_fn: Option<&'static str>,
+    pub recv_fn: Option<&'static str>,
+    pub typmod_fns: Option<PostgresTypeTypmodFns>,
+    /// The `SubscriptRoutines` handler of a `#[pgx(subscript)]` type, on Postgres 14
+    pub subscript_fn: Option<&'static str>,
+    pub fixed_size: Option<PostgresTypeFixedSize>,
     pub to_sql_config: ToSqlConfigEntity,
 }
 
+/// The functions of a `#[pgx(typmod)]` [`PostgresTypeEntity`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostgresTypeTypmodFns {
+    pub typmod_in_fn: &'static str,
+    pub typmod_out_fn: &'static str,
+    pub typmod_coerce_fn: &'static str,
+}
+
+/// The layout of a `#[pgx(fixed_size)]` or `#[pgx(by_value)]` [`PostgresTypeEntity`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostgresTypeFixedSize {
+    pub internal_length: usize,
+    pub alignment: usize,
+    pub by_value: bool,
+}
+
+impl PostgresTypeFixedSize {
+    /// The `ALIGNMENT` of the type.  Postgres doesn't align anything to more than a `double`
+    fn alignment_sql(&self) -> &'static str {
+        match self.alignment {
+            1 => "char",
+            2 => "int2",
+            4 => "int4",
+            _ => "double",
+        }
+    }
+}
+
 impl Hash for PostgresTypeEntity {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.full_path.hash(state);
@@ -59,6 +95,39 @@ impl PostgresTypeEntity {
     pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
         self.mappings.iter().any(|tester| *candidate == tester.id)
     }
+
+    /// The full paths of the functions, other than `in_fn`/`out_fn`, whose SQL is emitted along
+    /// with this type's.
+    pub fn owned_fns(&self) -> Vec<String> {
+        let mut owned_fns = vec![self.send_fn, self.recv_fn];
+        if let Some(typmod_fns) = &self.typmod_fns {
+            owned_fns.push(Some(typmod_fns.typmod_in_fn));
+            owned_fns.push(Some(typmod_fns.typmod_out_fn));
+            owned_fns.push(Some(typmod_fns.typmod_coerce_fn));
+        }
+        owned_fns.push(self.subscript_fn);
+        owned_fns
+            .into_iter()
+            .flatten()
+            .map(|func| format!("{}::{}", self.module_path, func))
+            .collect()
+    }
+}
+
+/// Find one of the [`PostgresTypeEntity::owned_fns`] of the type at `self_index`.
+fn find_owned_fn<'a>(
+    context: &'a PgxSql,
+    self_index: NodeIndex,
+    fn_path: &str,
+) -> eyre::Result<(NodeIndex, &'a PgExternEntity)> {
+    context
+        .graph
+        .neighbors_undirected(self_index)
+        .find_map(|neighbor| match &context.graph[neighbor] {
+            SqlGraphEntity::Function(func) if func.full_path == fn_path => Some((neighbor, func)),
+            _ => None,
+        })
+        .ok_or_else(|| eyre!("Could not find {} function graph entity.", fn_path))
 }
 
 impl Into<SqlGraphEntity> for PostgresTypeEntity {
@@ -94,10 +163,12 @@ impl ToSql for PostgresTypeEntity {
             _ => return Err(eyre!("Was not called on a Type. Got: {:?}", item_node)),
         };
 
-        // The `in_fn`/`out_fn` need to be present in a certain order:
+        // The `in_fn`/`out_fn` (and `recv_fn`/`send_fn`) need to be present in a certain order:
         // - CREATE TYPE;
         // - CREATE FUNCTION _in;
         // - CREATE FUNCTION _out;
+        // - CREATE FUNCTION _recv;
+        // - CREATE FUNCTION _send;
         // - CREATE TYPE (...);
 
         let in_fn_module_path = if !item.in_fn_module_path.is_empty() {
@@ -168,6 +239,34 @@ impl ToSql for PostgresTypeEntity {
         let out_fn_sql = out_fn.to_sql(context)?;
         tracing::trace!(%out_fn_sql);
 
+        let mut sendrecv_fn_sql = String::new();
+        let mut sendrecv_options = String::new();
+        let typmod_fns = item.typmod_fns.as_ref();
+        for (option, func) in [
+            ("RECEIVE", item.recv_fn),
+            ("SEND", item.send_fn),
+            ("TYPMOD_IN", typmod_fns.map(|fns| fns.typmod_in_fn)),
+            ("TYPMOD_OUT", typmod_fns.map(|fns| fns.typmod_out_fn)),
+            ("SUBSCRIPT", item.subscript_fn),
+        ] {
+            let func = match func {
+                Some(func) => func,
+                None => continue,
+            };
+            let fn_path = format!("{}::{}", item.module_path, func);
+            let (fn_graph_index, fn_entity) = find_owned_fn(context, self_index, &fn_path)?;
+            tracing::trace!(func = ?fn_path, "Found matching `{}` function", option);
+            sendrecv_fn_sql.push('\n');
+            sendrecv_fn_sql.push_str(&fn_entity.to_sql(context)?);
+            sendrecv_options.push_str(&format!(
+                "\t{option} = {schema_prefix}{func}, /* {fn_path} */\n",
+                option = option,
+                schema_prefix = context.schema_prefix_for(&fn_graph_index),
+                func = func,
+                fn_path = fn_path,
+            ));
+        }
+
         let shell_type = format!(
             "\n\
                                 -- {file}:{line}\n\
@@ -182,14 +281,34 @@ impl ToSql for PostgresTypeEntity {
         );
         tracing::trace!(sql = %shell_type);
 
+        // fixed-length types can't be TOASTed, so they're stored plain
+        let (internal_length, layout_options, storage) = match &item.fixed_size {
+            Some(fixed_size) => (
+                fixed_size.internal_length.to_string(),
+                format!(
+                    "{by_value}\tALIGNMENT = {alignment},\n",
+                    by_value = if fixed_size.by_value {
+                        "\tPASSEDBYVALUE,\n"
+                    } else {
+                        ""
+                    },
+                    alignment = fixed_size.alignment_sql(),
+                ),
+                "plain",
+            ),
+            None => ("variable".to_string(), String::new(), "extended"),
+        };
+
         let materialized_type = format!("\n\
                                 -- {file}:{line}\n\
                                 -- {full_path}\n\
                                 CREATE TYPE {schema}{name} (\n\
-                                    \tINTERNALLENGTH = variable,\n\
+                                    \tINTERNALLENGTH = {internal_length},\n\
+                                    {layout_options}\
                                     \tINPUT = {schema_prefix_in_fn}{in_fn}, /* {in_fn_path} */\n\
                                     \tOUTPUT = {schema_prefix_out_fn}{out_fn}, /* {out_fn_path} */\n\
-                                    \tSTORAGE = extended\n\
+                                    {sendrecv_options}\
+                                    \tSTORAGE = {storage}\n\
                                 );\
                             ",
                                         full_path = item.full_path,
@@ -203,9 +322,44 @@ impl ToSql for PostgresTypeEntity {
                                         schema_prefix_out_fn = context.schema_prefix_for(&out_fn_graph_index),
                                         out_fn = item.out_fn,
                                         out_fn_path = out_fn_path,
+                                        sendrecv_options = sendrecv_options,
+                                        internal_length = internal_length,
+                                        layout_options = layout_options,
+                                        storage = storage,
         );
         tracing::trace!(sql = %materialized_type);
 
-        Ok(shell_type + "\n" + &in_fn_sql + "\n" + &out_fn_sql + "\n" + &materialized_type)
+        // the length-coercion cast Postgres applies to make a value conform to a typmod
+        let mut typmod_cast = String::new();
+        if let Some(typmod_fns) = typmod_fns {
+            let coerce_fn_path = format!("{}::{}", item.module_path, typmod_fns.typmod_coerce_fn);
+            let (coerce_fn_graph_index, coerce_fn) =
+                find_owned_fn(context, self_index, &coerce_fn_path)?;
+            typmod_cast = format!(
+                "\n\
+                    {coerce_fn_sql}\n\
+                    CREATE CAST ({schema}{name} AS {schema}{name})\n\
+                    \tWITH FUNCTION {schema_prefix_coerce_fn}{coerce_fn}({schema}{name}, integer, boolean) /* {coerce_fn_path} */\n\
+                    \tAS IMPLICIT;\
+                ",
+                coerce_fn_sql = coerce_fn.to_sql(context)?,
+                schema = context.schema_prefix_for(&self_index),
+                name = item.name,
+                schema_prefix_coerce_fn = context.schema_prefix_for(&coerce_fn_graph_index),
+                coerce_fn = typmod_fns.typmod_coerce_fn,
+                coerce_fn_path = coerce_fn_path,
+            );
+            tracing::trace!(sql = %typmod_cast);
+        }
+
+        Ok(shell_type
+            + "\n"
+            + &in_fn_sql
+            + "\n"
+            + &out_fn_sql
+            + &sendrecv_fn_sql
+            + "\n"
+            + &materialized_type
+            + &typmod_cast)
     }
 }
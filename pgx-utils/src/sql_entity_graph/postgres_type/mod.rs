@@ -16,10 +16,173 @@ use std::{
 };
 use syn::{
     parse::{Parse, ParseStream},
-    DeriveInput, Generics, ItemStruct,
+    spanned::Spanned,
+    Attribute, DeriveInput, Generics, ItemStruct,
 };
 
-use crate::sql_entity_graph::ToSqlConfig;
+use crate::sql_entity_graph::{
+    pgx_attribute::{ArgValue, PgxArg, PgxAttribute},
+    ToSqlConfig,
+};
+
+/// The `#[pgx(..)]` options of a `#[derive(PostgresType)]`, other than `sql`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostgresTypeArgs {
+    /// `#[pgx(sendrecv = custom)]`: the binary send/receive functions come from `SendRecvFuncs`
+    /// rather than CBOR
+    pub custom_sendrecv: bool,
+
+    /// `#[pgx(typmod)]`: the type accepts type modifiers through `TypmodInOutFuncs`
+    pub typmod: bool,
+
+    /// `#[pgx(storage = ..)]`: how the type is stored on disk
+    pub storage: Option<PostgresTypeStorageArg>,
+
+    /// `#[pgx(version = N)]`: the version tag stored values start with
+    pub version: Option<u8>,
+
+    /// `#[pgx(legacy_untagged)]`: values stored before the type had a `version` are read as
+    /// version 0
+    pub legacy_untagged: bool,
+
+    /// `#[pgx(fixed_size)]`: the type is stored as its fixed-length in-memory representation
+    /// rather than as a varlena
+    pub fixed_size: bool,
+
+    /// `#[pgx(by_value)]`: like `fixed_size`, but the type is passed by value in a `Datum`
+    pub by_value: bool,
+
+    /// `#[pgx(subscript)]`: the type supports `value[subscript]` through `Subscriptable`, on
+    /// Postgres 14
+    pub subscript: bool,
+}
+
+/// The value of a `#[pgx(storage = ..)]` option of a `#[derive(PostgresType)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostgresTypeStorageArg {
+    Cbor,
+    Bincode,
+    MessagePack,
+    /// `#[pgx(storage = custom)]`: the type implements `PostgresTypeStorage` itself
+    Custom,
+}
+
+impl PostgresTypeArgs {
+    pub fn from_attributes(attrs: &[Attribute]) -> Result<Self, syn::Error> {
+        let mut args = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("pgx")) {
+            for arg in attr.parse_args::<PgxAttribute>()?.args {
+                match arg {
+                    PgxArg::NameValue(nv) if nv.path.is_ident("sendrecv") => match nv.value {
+                        ArgValue::Path(ref path) if path.is_ident("custom") => {
+                            args.custom_sendrecv = true
+                        }
+                        _ => {
+                            return Err(syn::Error::new(
+                                nv.path.span(),
+                                "expected `#[pgx(sendrecv = custom)]`",
+                            ))
+                        }
+                    },
+                    PgxArg::Path(path) if path.is_ident("typmod") => args.typmod = true,
+                    PgxArg::Path(path) if path.is_ident("subscript") => args.subscript = true,
+                    PgxArg::NameValue(nv) if nv.path.is_ident("storage") => {
+                        args.storage = Some(match nv.value {
+                            ArgValue::Path(ref path) if path.is_ident("custom") => {
+                                PostgresTypeStorageArg::Custom
+                            }
+                            ArgValue::Lit(syn::Lit::Str(ref format)) => {
+                                match format.value().as_str() {
+                                    "cbor" => PostgresTypeStorageArg::Cbor,
+                                    "bincode" => PostgresTypeStorageArg::Bincode,
+                                    "messagepack" => PostgresTypeStorageArg::MessagePack,
+                                    _ => {
+                                        return Err(syn::Error::new(
+                                            format.span(),
+                                            "expected one of \"cbor\", \"bincode\" or \"messagepack\"",
+                                        ))
+                                    }
+                                }
+                            }
+                            _ => {
+                                return Err(syn::Error::new(
+                                    nv.path.span(),
+                                    "expected `#[pgx(storage = \"cbor\" | \"bincode\" | \"messagepack\" | custom)]`",
+                                ))
+                            }
+                        })
+                    }
+                    PgxArg::Path(path) if path.is_ident("legacy_untagged") => {
+                        args.legacy_untagged = true
+                    }
+                    PgxArg::Path(path) if path.is_ident("fixed_size") => args.fixed_size = true,
+                    PgxArg::Path(path) if path.is_ident("by_value") => {
+                        args.fixed_size = true;
+                        args.by_value = true;
+                    }
+                    PgxArg::NameValue(nv) if nv.path.is_ident("version") => match nv.value {
+                        ArgValue::Lit(syn::Lit::Int(ref version)) => {
+                            args.version = Some(version.base10_parse()?)
+                        }
+                        _ => {
+                            return Err(syn::Error::new(
+                                nv.path.span(),
+                                "expected `#[pgx(version = N)]`, with N from 0 to 255",
+                            ))
+                        }
+                    },
+                    _ => (),
+                }
+            }
+        }
+        if args.fixed_size && (args.storage.is_some() || args.version.is_some()) {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pgx(fixed_size)]` and `#[pgx(by_value)]` types are stored as their in-memory representation, so can't have a `storage` or `version`",
+            ));
+        }
+        if args.legacy_untagged && args.version.unwrap_or(0) == 0 {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pgx(legacy_untagged)]` needs a `#[pgx(version = N)]` of at least 1, as untagged values are read as version 0",
+            ));
+        }
+        if args.version.is_some() && args.storage == Some(PostgresTypeStorageArg::Custom) {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pgx(storage = custom)]` types declare their version in `PostgresTypeStorage::VERSION`",
+            ));
+        }
+        Ok(args)
+    }
+}
+
+/// Does a `#[derive(PostgresType)]` get binary send/receive functions?  Serde types default to
+/// CBOR, but types stored as their in-memory representation, `#[pgvarlena_inoutfuncs]` and
+/// `#[pgx(fixed_size)]` ones, only get them with `#[pgx(sendrecv = custom)]`.
+fn has_sendrecv(attrs: &[Attribute], args: &PostgresTypeArgs) -> bool {
+    args.custom_sendrecv
+        || !(args.fixed_size
+            || attrs
+                .iter()
+                .any(|attr| attr.path.is_ident("pgvarlena_inoutfuncs")))
+}
+
+fn sendrecv_fns(name: &Ident, has_sendrecv: bool) -> (Option<Ident>, Option<Ident>) {
+    if !has_sendrecv {
+        return (None, None);
+    }
+    (
+        Some(Ident::new(
+            &format!("{}_send", name).to_lowercase(),
+            name.span(),
+        )),
+        Some(Ident::new(
+            &format!("{}_recv", name).to_lowercase(),
+            name.span(),
+        )),
+    )
+}
 
 /// A parsed `#[derive(PostgresType)]` item.
 ///
@@ -49,15 +212,22 @@ pub struct PostgresType {
     generics: Generics,
     in_fn: Ident,
     out_fn: Ident,
+    send_fn: Option<Ident>,
+    recv_fn: Option<Ident>,
+    args: PostgresTypeArgs,
     to_sql_config: ToSqlConfig,
 }
 
 impl PostgresType {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: Ident,
         generics: Generics,
         in_fn: Ident,
         out_fn: Ident,
+        send_fn: Option<Ident>,
+        recv_fn: Option<Ident>,
+        args: PostgresTypeArgs,
         to_sql_config: ToSqlConfig,
     ) -> Self {
         Self {
@@ -65,6 +235,9 @@ impl PostgresType {
             name,
             in_fn,
             out_fn,
+            send_fn,
+            recv_fn,
+            args,
             to_sql_config,
         }
     }
@@ -89,11 +262,19 @@ impl PostgresType {
             &format!("{}_out", derive_input.ident).to_lowercase(),
             derive_input.ident.span(),
         );
+        let args = PostgresTypeArgs::from_attributes(derive_input.attrs.as_slice())?;
+        let (funcname_send, funcname_recv) = sendrecv_fns(
+            &derive_input.ident,
+            has_sendrecv(derive_input.attrs.as_slice(), &args),
+        );
         Ok(Self::new(
             derive_input.ident,
             derive_input.generics,
             funcname_in,
             funcname_out,
+            funcname_send,
+            funcname_recv,
+            args,
             to_sql_config,
         ))
     }
@@ -126,11 +307,17 @@ impl Parse for PostgresType {
             &format!("{}_out", parsed.ident).to_lowercase(),
             parsed.ident.span(),
         );
+        let args = PostgresTypeArgs::from_attributes(parsed.attrs.as_slice())?;
+        let (funcname_send, funcname_recv) =
+            sendrecv_fns(&parsed.ident, has_sendrecv(parsed.attrs.as_slice(), &args));
         Ok(Self::new(
             parsed.ident,
             parsed.generics,
             funcname_in,
             funcname_out,
+            funcname_send,
+            funcname_recv,
+            args,
             to_sql_config,
         ))
     }
@@ -147,6 +334,52 @@ impl ToTokens for PostgresType {
 
         let in_fn = &self.in_fn;
         let out_fn = &self.out_fn;
+        let send_fn = match &self.send_fn {
+            Some(send_fn) => quote! { Some(stringify!(#send_fn)) },
+            None => quote! { None },
+        };
+        let recv_fn = match &self.recv_fn {
+            Some(recv_fn) => quote! { Some(stringify!(#recv_fn)) },
+            None => quote! { None },
+        };
+        let typmod_fns = if self.args.typmod {
+            let lowercase_name = self.name.to_string().to_lowercase();
+            let typmod_in_fn = format!("{}_typmod_in", lowercase_name);
+            let typmod_out_fn = format!("{}_typmod_out", lowercase_name);
+            let typmod_coerce_fn = format!("{}_typmod_coerce", lowercase_name);
+            quote! {
+                Some(::pgx::utils::sql_entity_graph::PostgresTypeTypmodFns {
+                    typmod_in_fn: #typmod_in_fn,
+                    typmod_out_fn: #typmod_out_fn,
+                    typmod_coerce_fn: #typmod_coerce_fn,
+                })
+            }
+        } else {
+            quote! { None }
+        };
+
+        let subscript_fn = if self.args.subscript {
+            let subscript_fn =
+                format!("{}_subscript_handler", self.name.to_string().to_lowercase());
+            quote! {
+                if cfg!(feature = "pg14") { Some(#subscript_fn) } else { None }
+            }
+        } else {
+            quote! { None }
+        };
+
+        let fixed_size = if self.args.fixed_size {
+            let by_value = self.args.by_value;
+            quote! {
+                Some(::pgx::utils::sql_entity_graph::PostgresTypeFixedSize {
+                    internal_length: ::core::mem::size_of::<#name #ty_generics>(),
+                    alignment: ::core::mem::align_of::<#name #ty_generics>(),
+                    by_value: #by_value,
+                })
+            }
+        } else {
+            quote! { None }
+        };
 
         let sql_graph_entity_fn_name = syn::Ident::new(
             &format!("__pgx_internals_type_{}", self.name),
@@ -181,6 +414,10 @@ impl ToTokens for PostgresType {
                     &mut mappings,
                     stringify!(#name).to_string()
                 );
+                pgx::datum::WithExpandedTypeIds::<#name #ty_generics>::register_expanded(
+                    &mut mappings,
+                    stringify!(#name).to_string()
+                );
                 let submission = ::pgx::utils::sql_entity_graph::PostgresTypeEntity {
                     name: stringify!(#name),
                     file: file!(),
@@ -202,6 +439,11 @@ impl ToTokens for PostgresType {
                         let _ = path_items.pop(); // Drop the one we don't want.
                         path_items.join("::")
                     },
+                    send_fn: #send_fn,
+                    recv_fn: #recv_fn,
+                    typmod_fns: #typmod_fns,
+                    subscript_fn: #subscript_fn,
+                    fixed_size: #fixed_size,
                     to_sql_config: #to_sql_config,
                 };
                 ::pgx::utils::sql_entity_graph::SqlGraphEntity::Type(submission)
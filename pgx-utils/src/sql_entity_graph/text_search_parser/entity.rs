@@ -0,0 +1,118 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use crate::sql_entity_graph::{
+    pgx_sql::PgxSql,
+    to_sql::{entity::ToSqlConfigEntity, ToSql},
+    SqlGraphEntity, SqlGraphIdentifier,
+};
+use std::cmp::Ordering;
+
+/// The output of a [`PgTextSearchParser`](crate::sql_entity_graph::text_search_parser::PgTextSearchParser) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PgTextSearchParserEntity {
+    pub name: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub start: &'static str,
+    pub gettoken: &'static str,
+    pub end: &'static str,
+    pub lextypes: &'static str,
+    pub headline: Option<&'static str>,
+    pub to_sql_config: ToSqlConfigEntity,
+}
+
+impl PgTextSearchParserEntity {
+    pub(crate) fn fn_names(&self) -> Vec<&'static str> {
+        let mut fn_names = vec![self.start, self.gettoken, self.end, self.lextypes];
+        fn_names.extend(self.headline);
+        fn_names
+    }
+}
+
+impl Ord for PgTextSearchParserEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.line.cmp(&other.line))
+    }
+}
+
+impl PartialOrd for PgTextSearchParserEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Into<SqlGraphEntity> for PgTextSearchParserEntity {
+    fn into(self) -> SqlGraphEntity {
+        SqlGraphEntity::TextSearchParser(self)
+    }
+}
+
+impl SqlGraphIdentifier for PgTextSearchParserEntity {
+    fn dot_identifier(&self) -> String {
+        format!("ts_parser {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PgTextSearchParserEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &PgxSql) -> eyre::Result<String> {
+        let self_index = context.text_search_parsers[self];
+        let schema = context.schema_prefix_for(&self_index);
+        let maybe_headline = if let Some(headline) = self.headline {
+            format!(
+                "\n\tHEADLINE = {schema}\"{headline}\" /* {full_path}::headline */",
+                schema = schema,
+                headline = headline,
+                full_path = self.full_path,
+            )
+        } else {
+            String::default()
+        };
+        let sql = format!("\n\
+                            -- {file}:{line}\n\
+                            -- {full_path}\n\
+                            CREATE TEXT SEARCH PARSER {schema}{name} (\n\
+                                \tSTART = {schema}\"{start}\", /* {full_path}::start */\n\
+                                \tGETTOKEN = {schema}\"{gettoken}\", /* {full_path}::get_token */\n\
+                                \tEND = {schema}\"{end}\", /* {full_path}::end */\n\
+                                \tLEXTYPES = {schema}\"{lextypes}\"{maybe_comma} /* {full_path}::lextypes */\
+                                {maybe_headline}\n\
+                            );\
+                            ",
+                          schema = schema,
+                          name = self.name,
+                          full_path = self.full_path,
+                          file = self.file,
+                          line = self.line,
+                          start = self.start,
+                          gettoken = self.gettoken,
+                          end = self.end,
+                          lextypes = self.lextypes,
+                          maybe_comma = if self.headline.is_some() { "," } else { "" },
+                          maybe_headline = maybe_headline,
+        );
+        tracing::trace!(%sql);
+        Ok(sql)
+    }
+}
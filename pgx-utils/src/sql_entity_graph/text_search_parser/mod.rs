@@ -0,0 +1,274 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+pub mod entity;
+
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    spanned::Spanned,
+    Expr, ItemFn, ItemImpl, Path,
+};
+
+use crate::sql_entity_graph::ToSqlConfig;
+
+/// A parsed `#[pg_ts_parser]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the declaration for a [`PgTextSearchParserEntity`][crate::sql_entity_graph::PgTextSearchParserEntity].
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgTextSearchParser;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PgTextSearchParser = parse_quote! {
+///     impl TextSearchParser for Example {
+///         const NAME: &'static str = "example";
+///         fn start(input: &[u8]) -> Self { Example }
+///         fn get_token(&mut self, input: &[u8]) -> Option<TextSearchToken> { None }
+///         fn lextypes() -> Vec<TextSearchLexType> { vec![] }
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgTextSearchParser {
+    item_impl: ItemImpl,
+    target_path: Path,
+    target_ident: Ident,
+    name: Expr,
+    pg_externs: Vec<ItemFn>,
+    fn_start: Ident,
+    fn_gettoken: Ident,
+    fn_end: Ident,
+    fn_lextypes: Ident,
+    fn_headline: Option<Ident>,
+    to_sql_config: ToSqlConfig,
+}
+
+impl PgTextSearchParser {
+    pub fn new(mut item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let to_sql_config =
+            ToSqlConfig::from_attributes(item_impl.attrs.as_slice())?.unwrap_or_default();
+        let target_path = get_target_path(&item_impl, "#[pg_ts_parser]")?;
+        let target_ident = target_path
+            .segments
+            .last()
+            .map(|segment| segment.ident.clone())
+            .ok_or_else(|| {
+                syn::Error::new(
+                    target_path.span(),
+                    "`#[pg_ts_parser]` only works with types whose path have a final segment.",
+                )
+            })?;
+        let snake_case_target_ident = target_ident.to_string().to_case(Case::Snake);
+
+        if let Some((_, ref path, _)) = item_impl.trait_ {
+            if let Some(last) = path.segments.last() {
+                if last.ident.to_string() != "TextSearchParser" {
+                    return Err(syn::Error::new(
+                        last.ident.span(),
+                        "`#[pg_ts_parser]` only works with the `TextSearchParser` trait.",
+                    ));
+                }
+            }
+        }
+
+        let name = match get_impl_const_by_name(&item_impl, "NAME") {
+            Some(expr) => expr,
+            None => {
+                item_impl.items.push(parse_quote! {
+                    const NAME: &'static str = stringify!(#target_ident);
+                });
+                parse_quote! {
+                    stringify!(#target_ident)
+                }
+            }
+        };
+
+        for required in ["start", "get_token", "lextypes"] {
+            if !has_impl_func_by_name(&item_impl, required) {
+                return Err(syn::Error::new(
+                    item_impl.span(),
+                    format!(
+                        "`#[pg_ts_parser]` requires the `{}` function to be implemented.",
+                        required
+                    ),
+                ));
+            }
+        }
+
+        let fn_name = |suffix: &str| {
+            Ident::new(
+                &format!("{}_{}", snake_case_target_ident, suffix),
+                target_ident.span(),
+            )
+        };
+        let fn_start = fn_name("start");
+        let fn_gettoken = fn_name("gettoken");
+        let fn_end = fn_name("end");
+        let fn_lextypes = fn_name("lextypes");
+        let fn_headline = if has_impl_func_by_name(&item_impl, "headline") {
+            Some(fn_name("headline"))
+        } else {
+            None
+        };
+
+        let mut pg_externs: Vec<ItemFn> = vec![
+            parse_quote! {
+                #[allow(non_snake_case)]
+                #[pg_extern(immutable, parallel_safe)]
+                fn #fn_start(input: pgx::Internal, len: i32) -> pgx::Internal {
+                    unsafe { pgx::ts_parser_start::<#target_path>(input, len) }
+                }
+            },
+            parse_quote! {
+                #[allow(non_snake_case)]
+                #[pg_extern(immutable, parallel_safe)]
+                fn #fn_gettoken(state: pgx::Internal, token: pgx::Internal, len: pgx::Internal) -> pgx::Internal {
+                    unsafe { pgx::ts_parser_gettoken::<#target_path>(state, token, len) }
+                }
+            },
+            parse_quote! {
+                #[allow(non_snake_case)]
+                #[pg_extern(immutable, parallel_safe)]
+                fn #fn_end(state: pgx::Internal) {
+                    unsafe { pgx::ts_parser_end::<#target_path>(state) }
+                }
+            },
+            parse_quote! {
+                #[allow(non_snake_case)]
+                #[pg_extern(immutable, parallel_safe)]
+                fn #fn_lextypes(_unused: pgx::Internal) -> pgx::Internal {
+                    pgx::ts_parser_lextypes::<#target_path>()
+                }
+            },
+        ];
+        if let Some(fn_headline) = &fn_headline {
+            pg_externs.push(parse_quote! {
+                #[allow(non_snake_case)]
+                #[pg_extern(immutable, parallel_safe)]
+                fn #fn_headline(prs: pgx::Internal, options: pgx::Internal, query: pgx::PgBox<pgx::pg_sys::TSQueryData>) -> pgx::Internal {
+                    unsafe { pgx::ts_parser_headline::<#target_path>(prs, options, query) }
+                }
+            });
+        }
+
+        Ok(Self {
+            item_impl,
+            target_path,
+            target_ident,
+            name,
+            pg_externs,
+            fn_start,
+            fn_gettoken,
+            fn_end,
+            fn_lextypes,
+            fn_headline,
+            to_sql_config,
+        })
+    }
+
+    fn entity_tokens(&self) -> ItemFn {
+        let target_path = &self.target_path;
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!(
+                "__pgx_internals_ts_parser_{}",
+                self.target_ident.to_string().to_case(Case::Snake)
+            ),
+            self.target_ident.span(),
+        );
+        let name = &self.name;
+        let fn_start = &self.fn_start;
+        let fn_gettoken = &self.fn_gettoken;
+        let fn_end = &self.fn_end;
+        let fn_lextypes = &self.fn_lextypes;
+        let fn_headline_iter = self.fn_headline.iter();
+        let to_sql_config = &self.to_sql_config;
+
+        parse_quote! {
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> ::pgx::utils::sql_entity_graph::SqlGraphEntity {
+                let submission = ::pgx::utils::sql_entity_graph::PgTextSearchParserEntity {
+                    name: #name,
+                    file: file!(),
+                    line: line!(),
+                    full_path: ::core::any::type_name::<#target_path>(),
+                    module_path: module_path!(),
+                    start: stringify!(#fn_start),
+                    gettoken: stringify!(#fn_gettoken),
+                    end: stringify!(#fn_end),
+                    lextypes: stringify!(#fn_lextypes),
+                    headline: None #( .unwrap_or(Some(stringify!(#fn_headline_iter))) )*,
+                    to_sql_config: #to_sql_config,
+                };
+                ::pgx::utils::sql_entity_graph::SqlGraphEntity::TextSearchParser(submission)
+            }
+        }
+    }
+}
+
+impl Parse for PgTextSearchParser {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgTextSearchParser {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let entity_fn = self.entity_tokens();
+        let impl_item = &self.item_impl;
+        let pg_externs = self.pg_externs.iter();
+        let inv = quote! {
+            #impl_item
+
+            #(#pg_externs)*
+
+            #entity_fn
+        };
+        tokens.append_all(inv);
+    }
+}
+
+pub(crate) fn get_target_path(item_impl: &ItemImpl, macro_name: &str) -> Result<Path, syn::Error> {
+    match &*item_impl.self_ty {
+        syn::Type::Path(type_path) => Ok(type_path.path.clone()),
+        something_else => Err(syn::Error::new(
+            something_else.span(),
+            format!("`{}` only works with types.", macro_name),
+        )),
+    }
+}
+
+pub(crate) fn get_impl_const_by_name(item_impl: &ItemImpl, name: &str) -> Option<Expr> {
+    item_impl
+        .items
+        .iter()
+        .find_map(|impl_item| match impl_item {
+            syn::ImplItem::Const(impl_item_const) if impl_item_const.ident == name => {
+                Some(impl_item_const.expr.clone())
+            }
+            _ => None,
+        })
+}
+
+pub(crate) fn has_impl_func_by_name(item_impl: &ItemImpl, name: &str) -> bool {
+    item_impl.items.iter().any(|impl_item| match impl_item {
+        syn::ImplItem::Method(impl_item_method) => impl_item_method.sig.ident == name,
+        _ => false,
+    })
+}
@@ -0,0 +1,90 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+use crate::sql_entity_graph::{
+    pgx_sql::PgxSql,
+    to_sql::{entity::ToSqlConfigEntity, ToSql},
+    SqlGraphEntity, SqlGraphIdentifier,
+};
+use std::cmp::Ordering;
+
+/// The output of a [`PgTextSearchTemplate`](crate::sql_entity_graph::text_search_template::PgTextSearchTemplate) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PgTextSearchTemplateEntity {
+    pub name: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub init: &'static str,
+    pub lexize: &'static str,
+    pub to_sql_config: ToSqlConfigEntity,
+}
+
+impl Ord for PgTextSearchTemplateEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.line.cmp(&other.line))
+    }
+}
+
+impl PartialOrd for PgTextSearchTemplateEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Into<SqlGraphEntity> for PgTextSearchTemplateEntity {
+    fn into(self) -> SqlGraphEntity {
+        SqlGraphEntity::TextSearchTemplate(self)
+    }
+}
+
+impl SqlGraphIdentifier for PgTextSearchTemplateEntity {
+    fn dot_identifier(&self) -> String {
+        format!("ts_template {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PgTextSearchTemplateEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &PgxSql) -> eyre::Result<String> {
+        let self_index = context.text_search_templates[self];
+        let sql = format!(
+            "\n\
+                            -- {file}:{line}\n\
+                            -- {full_path}\n\
+                            CREATE TEXT SEARCH TEMPLATE {schema}{name} (\n\
+                                \tINIT = {schema}\"{init}\", /* {full_path}::init */\n\
+                                \tLEXIZE = {schema}\"{lexize}\" /* {full_path}::lexize */\n\
+                            );\
+                            ",
+            schema = context.schema_prefix_for(&self_index),
+            name = self.name,
+            full_path = self.full_path,
+            file = self.file,
+            line = self.line,
+            init = self.init,
+            lexize = self.lexize,
+        );
+        tracing::trace!(%sql);
+        Ok(sql)
+    }
+}
@@ -0,0 +1,205 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+pub mod entity;
+
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    spanned::Spanned,
+    Expr, ItemFn, ItemImpl, Path,
+};
+
+use crate::sql_entity_graph::{
+    text_search_parser::{get_impl_const_by_name, get_target_path, has_impl_func_by_name},
+    ToSqlConfig,
+};
+
+/// A parsed `#[pg_ts_template]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the declaration for a [`PgTextSearchTemplateEntity`][crate::sql_entity_graph::PgTextSearchTemplateEntity].
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgTextSearchTemplate;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PgTextSearchTemplate = parse_quote! {
+///     impl TextSearchDictionary for Example {
+///         const NAME: &'static str = "example";
+///         fn init(options: Vec<(String, String)>) -> Self { Example }
+///         fn lexize(&mut self, token: &str) -> Option<Vec<TextSearchLexeme>> { None }
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgTextSearchTemplate {
+    item_impl: ItemImpl,
+    target_path: Path,
+    target_ident: Ident,
+    name: Expr,
+    pg_externs: Vec<ItemFn>,
+    fn_init: Ident,
+    fn_lexize: Ident,
+    to_sql_config: ToSqlConfig,
+}
+
+impl PgTextSearchTemplate {
+    pub fn new(mut item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let to_sql_config =
+            ToSqlConfig::from_attributes(item_impl.attrs.as_slice())?.unwrap_or_default();
+        let target_path = get_target_path(&item_impl, "#[pg_ts_template]")?;
+        let target_ident = target_path
+            .segments
+            .last()
+            .map(|segment| segment.ident.clone())
+            .ok_or_else(|| {
+                syn::Error::new(
+                    target_path.span(),
+                    "`#[pg_ts_template]` only works with types whose path have a final segment.",
+                )
+            })?;
+        let snake_case_target_ident = target_ident.to_string().to_case(Case::Snake);
+
+        if let Some((_, ref path, _)) = item_impl.trait_ {
+            if let Some(last) = path.segments.last() {
+                if last.ident.to_string() != "TextSearchDictionary" {
+                    return Err(syn::Error::new(
+                        last.ident.span(),
+                        "`#[pg_ts_template]` only works with the `TextSearchDictionary` trait.",
+                    ));
+                }
+            }
+        }
+
+        let name = match get_impl_const_by_name(&item_impl, "NAME") {
+            Some(expr) => expr,
+            None => {
+                item_impl.items.push(parse_quote! {
+                    const NAME: &'static str = stringify!(#target_ident);
+                });
+                parse_quote! {
+                    stringify!(#target_ident)
+                }
+            }
+        };
+
+        for required in ["init", "lexize"] {
+            if !has_impl_func_by_name(&item_impl, required) {
+                return Err(syn::Error::new(
+                    item_impl.span(),
+                    format!(
+                        "`#[pg_ts_template]` requires the `{}` function to be implemented.",
+                        required
+                    ),
+                ));
+            }
+        }
+
+        let fn_init = Ident::new(
+            &format!("{}_init", snake_case_target_ident),
+            target_ident.span(),
+        );
+        let fn_lexize = Ident::new(
+            &format!("{}_lexize", snake_case_target_ident),
+            target_ident.span(),
+        );
+
+        let pg_externs: Vec<ItemFn> = vec![
+            parse_quote! {
+                #[allow(non_snake_case)]
+                #[pg_extern(immutable, parallel_safe)]
+                fn #fn_init(options: pgx::Internal) -> pgx::Internal {
+                    unsafe { pgx::ts_dictionary_init::<#target_path>(options) }
+                }
+            },
+            parse_quote! {
+                #[allow(non_snake_case)]
+                #[pg_extern(immutable, parallel_safe)]
+                fn #fn_lexize(dictionary: pgx::Internal, token: pgx::Internal, len: pgx::Internal, substate: pgx::Internal) -> pgx::Internal {
+                    unsafe { pgx::ts_dictionary_lexize::<#target_path>(dictionary, token, len, substate) }
+                }
+            },
+        ];
+
+        Ok(Self {
+            item_impl,
+            target_path,
+            target_ident,
+            name,
+            pg_externs,
+            fn_init,
+            fn_lexize,
+            to_sql_config,
+        })
+    }
+
+    fn entity_tokens(&self) -> ItemFn {
+        let target_path = &self.target_path;
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!(
+                "__pgx_internals_ts_template_{}",
+                self.target_ident.to_string().to_case(Case::Snake)
+            ),
+            self.target_ident.span(),
+        );
+        let name = &self.name;
+        let fn_init = &self.fn_init;
+        let fn_lexize = &self.fn_lexize;
+        let to_sql_config = &self.to_sql_config;
+
+        parse_quote! {
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> ::pgx::utils::sql_entity_graph::SqlGraphEntity {
+                let submission = ::pgx::utils::sql_entity_graph::PgTextSearchTemplateEntity {
+                    name: #name,
+                    file: file!(),
+                    line: line!(),
+                    full_path: ::core::any::type_name::<#target_path>(),
+                    module_path: module_path!(),
+                    init: stringify!(#fn_init),
+                    lexize: stringify!(#fn_lexize),
+                    to_sql_config: #to_sql_config,
+                };
+                ::pgx::utils::sql_entity_graph::SqlGraphEntity::TextSearchTemplate(submission)
+            }
+        }
+    }
+}
+
+impl Parse for PgTextSearchTemplate {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgTextSearchTemplate {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let entity_fn = self.entity_tokens();
+        let impl_item = &self.item_impl;
+        let pg_externs = self.pg_externs.iter();
+        let inv = quote! {
+            #impl_item
+
+            #(#pg_externs)*
+
+            #entity_fn
+        };
+        tokens.append_all(inv);
+    }
+}
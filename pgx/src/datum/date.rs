@@ -7,12 +7,17 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 
-use crate::{pg_sys, FromDatum, IntoDatum};
+use crate::datum::time::{output_to_string, DATEVAL_NOBEGIN, DATEVAL_NOEND};
+use crate::{pg_sys, FromDatum, Infinity, IntoDatum};
 use std::ops::{Deref, DerefMut};
 use time::format_description::FormatItem;
 
-#[derive(Debug)]
-pub struct Date(time::Date);
+/// A Postgres `date`, which may also be `-infinity` or `infinity`.
+///
+/// Use [`Date::as_time()`] to get at the underlying [`time::Date`], which doesn't exist for
+/// infinite dates.  Infinite dates dereference to [`time::Date::MIN`] and [`time::Date::MAX`].
+#[derive(Debug, Copy, Clone)]
+pub struct Date(time::Date, Option<Infinity>);
 impl FromDatum for Date {
     const NEEDS_TYPID: bool = false;
     #[inline]
@@ -20,17 +25,27 @@ impl FromDatum for Date {
         if is_null {
             None
         } else {
-            Some(Date(
-                time::Date::from_julian_day(datum as i32 + pg_sys::POSTGRES_EPOCH_JDATE as i32)
-                    .expect("Unexpected error getting the Julian day in Date::from_datum"),
-            ))
+            match datum as i32 {
+                DATEVAL_NOBEGIN => Some(Date::NEG_INFINITY),
+                DATEVAL_NOEND => Some(Date::INFINITY),
+                days => Some(Date(
+                    time::Date::from_julian_day(days + pg_sys::POSTGRES_EPOCH_JDATE as i32)
+                        .expect("Unexpected error getting the Julian day in Date::from_datum"),
+                    None,
+                )),
+            }
         }
     }
 }
 impl IntoDatum for Date {
     #[inline]
     fn into_datum(self) -> Option<pg_sys::Datum> {
-        Some((self.to_julian_day() as i32 - pg_sys::POSTGRES_EPOCH_JDATE as i32) as pg_sys::Datum)
+        let days = match self.1 {
+            Some(Infinity::Negative) => DATEVAL_NOBEGIN,
+            Some(Infinity::Positive) => DATEVAL_NOEND,
+            None => self.0.to_julian_day() as i32 - pg_sys::POSTGRES_EPOCH_JDATE as i32,
+        };
+        Some(days as pg_sys::Datum)
     }
 
     fn type_oid() -> u32 {
@@ -39,11 +54,46 @@ impl IntoDatum for Date {
 }
 
 impl Date {
+    /// `-infinity`
+    pub const NEG_INFINITY: Date = Date(time::Date::MIN, Some(Infinity::Negative));
+
+    /// `infinity`
+    pub const INFINITY: Date = Date(time::Date::MAX, Some(Infinity::Positive));
+
     pub fn new(date: time::Date) -> Self {
-        Date(date)
+        Date(date, None)
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        self.1.is_some()
+    }
+
+    /// Which infinity this date is, if any
+    pub fn infinity(&self) -> Option<Infinity> {
+        self.1
+    }
+
+    /// The [`time::Date`] this date represents, or `None` if it's infinite
+    pub fn as_time(&self) -> Option<&time::Date> {
+        match self.1 {
+            Some(_) => None,
+            None => Some(&self.0),
+        }
     }
 }
 
+/// Formats the date like Postgres' `date_out`, honoring the session's `DateStyle`
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&output_to_string(pg_sys::date_out, self.into_datum()))
+    }
+}
+
+/// Dereferences to the underlying [`time::Date`].
+///
+/// Deprecated in favor of [`Date::as_time()`]: infinite values dereference to
+/// [`time::Date::MIN`] and [`time::Date::MAX`], which is easy to mistake for a real value.
+/// (`#[deprecated]` has no effect on trait impls, so this can't be flagged by the compiler.)
 impl Deref for Date {
     type Target = time::Date;
 
@@ -66,8 +116,12 @@ impl serde::Serialize for Date {
     where
         S: serde::Serializer,
     {
+        if let Some(infinity) = self.1 {
+            return serializer.serialize_str(infinity.as_str());
+        }
+
         serializer.serialize_str(
-            &self.format(&DATE_FORMAT).map_err(|e| {
+            &self.0.format(&DATE_FORMAT).map_err(|e| {
                 serde::ser::Error::custom(format!("Date formatting problem: {:?}", e))
             })?,
         )
@@ -0,0 +1,219 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+use crate::{
+    pg_sys, set_varsize, storage_decode, storage_encode_into, varatt_is_1b_e, vartag_external,
+    vartag_is_expanded, FromDatum, IntoDatum, PgMemoryContexts, PostgresType, PostgresTypeStorage,
+    StringInfo,
+};
+use std::any::TypeId;
+use std::ops::{Deref, DerefMut};
+use std::panic::AssertUnwindSafe;
+
+/// The in-memory layout of a [`PgExpanded`] value.  Postgres only knows about the header, which
+/// must come first.
+#[repr(C)]
+struct ExpandedValue<T> {
+    header: pg_sys::ExpandedObjectHeader,
+    methods: pg_sys::ExpandedObjectMethods,
+    type_id: TypeId,
+
+    /// the stored representation of `value`, cached between `get_flat_size()` and
+    /// `flatten_into()`
+    flat: Option<Vec<u8>>,
+    value: T,
+}
+
+/// A `#[derive(PostgresType)]` value kept in Postgres' "expanded object" form.
+///
+/// Taking and returning a plain `T` decodes its stored representation on every call and encodes it
+/// again on return.  Taking and returning `PgExpanded<T>` instead hands Postgres a read-write
+/// pointer to the Rust value itself, so chained function calls, and PL/pgSQL variables assigned
+/// from them, keep working on the same value.  It's only encoded, to the same representation a
+/// plain `T` is stored with, when Postgres needs to store it in a tuple.
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Clone, Default, Serialize, Deserialize, PostgresType)]
+/// pub struct Counts {
+///     counts: Vec<i64>,
+/// }
+///
+/// #[pg_extern]
+/// fn counts_add(mut counts: PgExpanded<Counts>, value: i64) -> PgExpanded<Counts> {
+///     counts.counts.push(value);
+///     counts
+/// }
+/// ```
+///
+/// When Postgres passes a read-write pointer the value is modified in place.  Otherwise it's
+/// cloned (if it's already expanded) or decoded into a new expanded object.
+pub struct PgExpanded<T: 'static> {
+    expanded: *mut ExpandedValue<T>,
+}
+
+impl<T> PgExpanded<T>
+where
+    T: PostgresType + for<'de> PostgresTypeStorage<'de> + 'static,
+{
+    /// Expand `value` into a new expanded object, owned by the `CurrentMemoryContext`
+    pub fn new(value: T) -> Self {
+        unsafe {
+            // the expanded object gets its own context, which Postgres deletes (and we drop
+            // `value` with) when it's done with the object
+            let context = pg_sys::AllocSetContextCreateExtended(
+                pg_sys::CurrentMemoryContext,
+                b"pgx expanded object\0".as_ptr() as *const std::os::raw::c_char,
+                pg_sys::ALLOCSET_DEFAULT_MINSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_INITSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_MAXSIZE as usize,
+            );
+
+            let expanded = PgMemoryContexts::For(context).leak_and_drop_on_delete(ExpandedValue {
+                header: std::mem::zeroed(),
+                methods: pg_sys::ExpandedObjectMethods {
+                    get_flat_size: Some(get_flat_size::<T>),
+                    flatten_into: Some(flatten_into::<T>),
+                },
+                type_id: TypeId::of::<T>(),
+                flat: None,
+                value,
+            });
+            pg_sys::EOH_init_header(&mut (*expanded).header, &(*expanded).methods, context);
+
+            PgExpanded { expanded }
+        }
+    }
+
+    fn rw_datum(&self) -> pg_sys::Datum {
+        unsafe { (*self.expanded).header.eoh_rw_ptr.as_ptr() as pg_sys::Datum }
+    }
+}
+
+impl<T: 'static> Deref for PgExpanded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &(*self.expanded).value }
+    }
+}
+
+impl<T: 'static> DerefMut for PgExpanded<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe {
+            (*self.expanded).flat = None;
+            &mut (*self.expanded).value
+        }
+    }
+}
+
+unsafe extern "C" fn get_flat_size<T: for<'de> PostgresTypeStorage<'de>>(
+    header: *mut pg_sys::ExpandedObjectHeader,
+) -> pg_sys::Size {
+    crate::guard::guard(AssertUnwindSafe(|| {
+        let expanded = &mut *(header as *mut ExpandedValue<T>);
+        pg_sys::VARHDRSZ + flat(expanded).len()
+    }))
+}
+
+unsafe extern "C" fn flatten_into<T: for<'de> PostgresTypeStorage<'de>>(
+    header: *mut pg_sys::ExpandedObjectHeader,
+    result: *mut std::os::raw::c_void,
+    allocated_size: pg_sys::Size,
+) {
+    crate::guard::guard(AssertUnwindSafe(|| {
+        let expanded = &mut *(header as *mut ExpandedValue<T>);
+        let flat = flat(expanded);
+        assert_eq!(
+            pg_sys::VARHDRSZ + flat.len(),
+            allocated_size,
+            "expanded object changed size while being flattened"
+        );
+
+        let data = (result as *mut u8).add(pg_sys::VARHDRSZ);
+        std::ptr::copy_nonoverlapping(flat.as_ptr(), data, flat.len());
+        set_varsize(result as *mut pg_sys::varlena, allocated_size as i32);
+    }))
+}
+
+fn flat<T: for<'de> PostgresTypeStorage<'de>>(expanded: &mut ExpandedValue<T>) -> &[u8] {
+    let value = &expanded.value;
+    expanded.flat.get_or_insert_with(|| {
+        let mut buffer = StringInfo::new();
+        storage_encode_into(value, &mut buffer);
+        buffer.as_bytes().to_vec()
+    })
+}
+
+impl<T> IntoDatum for PgExpanded<T>
+where
+    T: PostgresType + for<'de> PostgresTypeStorage<'de> + 'static,
+{
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.rw_datum())
+    }
+
+    fn type_oid() -> u32 {
+        crate::rust_regtypein::<T>()
+    }
+}
+
+impl<T> FromDatum for PgExpanded<T>
+where
+    T: PostgresType + for<'de> PostgresTypeStorage<'de> + Clone + 'static,
+{
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Self> {
+        if is_null {
+            return None;
+        } else if datum == 0 {
+            panic!("an expanded Datum was flagged as non-null but the datum is zero");
+        }
+
+        let varlena = datum as *const pg_sys::varlena;
+        if varatt_is_1b_e(varlena)
+            && vartag_is_expanded(vartag_external(varlena) as pg_sys::vartag_external)
+        {
+            let header = pg_sys::DatumGetEOHP(datum);
+            let expanded = header as *mut ExpandedValue<T>;
+
+            // only the objects we expanded point at their own methods
+            let ours = (*header).eoh_methods == &(*expanded).methods as *const _
+                && (*expanded).type_id == TypeId::of::<T>();
+            if ours {
+                return if datum == (*header).eoh_rw_ptr.as_ptr() as pg_sys::Datum {
+                    // we've been given the object to modify as we please
+                    Some(PgExpanded { expanded })
+                } else {
+                    Some(PgExpanded::new((*expanded).value.clone()))
+                };
+            }
+        }
+
+        // a flat (or otherwise expanded) value.  Detoasting it flattens it to its stored
+        // representation
+        let value: T = storage_decode(datum as *mut pg_sys::varlena);
+        Some(PgExpanded::new(value))
+    }
+
+    unsafe fn from_datum_in_memory_context(
+        mut memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: u32,
+    ) -> Option<Self> {
+        let context = memory_context.value();
+        let expanded = memory_context.switch_to(|_| Self::from_datum(datum, is_null, typoid))?;
+
+        // a read-write object we were handed still belongs to its original context
+        pg_sys::TransferExpandedObject(expanded.rw_datum(), context);
+        Some(expanded)
+    }
+}
@@ -0,0 +1,58 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Conversions for `#[pgx(fixed_size)]` and `#[pgx(by_value)]` `#[derive(PostgresType)]` types,
+//! which are stored as their in-memory representation rather than as a varlena
+use crate::pg_sys;
+use std::mem::{size_of, transmute_copy};
+
+/// Copy a `T` out of a `Datum`.
+///
+/// A `by_value` `T` is held in the `Datum` itself, the same way Postgres holds an integer of its
+/// size.  Otherwise the `Datum` points to the `T`, which may not be aligned for Rust.
+///
+/// ## Safety
+///
+/// `datum` must hold, or point to, a `T` converted by [`fixed_size_into_datum`]
+pub unsafe fn fixed_size_from_datum<T: Copy>(datum: pg_sys::Datum, by_value: bool) -> T {
+    if !by_value {
+        return std::ptr::read_unaligned(datum as *const T);
+    }
+
+    match size_of::<T>() {
+        1 => transmute_copy(&(datum as u8)),
+        2 => transmute_copy(&(datum as u16)),
+        4 => transmute_copy(&(datum as u32)),
+        8 => transmute_copy(&(datum as u64)),
+        size => panic!("cannot pass a type of {} bytes by value", size),
+    }
+}
+
+/// Copy a `T` into a `Datum`.
+///
+/// A `by_value` `T` must be 1, 2, 4 or 8 bytes long.  Otherwise the `T` is copied into memory
+/// allocated in the `CurrentMemoryContext`, without regard for its alignment.  Either way `T`
+/// must not have padding, whose uninitialized bytes would end up on disk.
+pub fn fixed_size_into_datum<T: Copy>(value: T, by_value: bool) -> pg_sys::Datum {
+    unsafe {
+        if !by_value {
+            let ptr = pg_sys::palloc(size_of::<T>()) as *mut T;
+            ptr.write_unaligned(value);
+            return ptr as pg_sys::Datum;
+        }
+
+        match size_of::<T>() {
+            1 => transmute_copy::<T, u8>(&value) as pg_sys::Datum,
+            2 => transmute_copy::<T, u16>(&value) as pg_sys::Datum,
+            4 => transmute_copy::<T, u32>(&value) as pg_sys::Datum,
+            8 => transmute_copy::<T, u64>(&value) as pg_sys::Datum,
+            size => panic!("cannot pass a type of {} bytes by value", size),
+        }
+    }
+}
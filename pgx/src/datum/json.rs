@@ -8,10 +8,10 @@ Use of this source code is governed by the MIT license that can be found in the
 */
 
 use crate::{
-    direct_function_call, direct_function_call_as_datum, pg_sys, vardata_any, varsize_any_exhdr,
-    void_mut_ptr, FromDatum, IntoDatum,
+    direct_function_call, direct_function_call_as_datum, ereport, pg_sys, vardata_any,
+    varsize_any_exhdr, void_mut_ptr, FromDatum, IntoDatum, JsonBRef, PgLogLevel, PgSqlErrorCode,
 };
-use serde::{Serialize, Serializer};
+use serde::{de::DeserializeOwned, Serialize, Serializer};
 use serde_json::Value;
 
 #[derive(Debug)]
@@ -23,6 +23,73 @@ pub struct JsonB(pub Value);
 #[derive(Debug)]
 pub struct JsonString(pub String);
 
+/// A `json` value deserialized directly into (or serialized directly from) a Rust type.
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct User {
+///     username: String,
+/// }
+///
+/// #[pg_extern]
+/// fn username(user: JsonOf<User>) -> String {
+///     user.0.username
+/// }
+/// ```
+///
+/// If the value doesn't deserialize into `T` an `ERRCODE_INVALID_TEXT_REPRESENTATION` ERROR is raised.
+#[derive(Debug)]
+pub struct JsonOf<T>(pub T);
+
+/// A `jsonb` value deserialized directly into (or serialized directly from) a Rust type.
+///
+/// Like [`JsonOf`], but for `jsonb`.
+#[derive(Debug)]
+pub struct JsonBOf<T>(pub T);
+
+/// Convert a (possibly toasted) `jsonb` Datum into its text representation
+unsafe fn jsonb_datum_to_string(datum: pg_sys::Datum) -> String {
+    let varlena = datum as *mut pg_sys::varlena;
+    let detoasted = pg_sys::pg_detoast_datum_packed(varlena);
+
+    let cstr = direct_function_call::<&std::ffi::CStr>(
+        pg_sys::jsonb_out,
+        vec![Some(detoasted as pg_sys::Datum)],
+    )
+    .expect("failed to convert jsonb to a cstring");
+
+    let string = cstr
+        .to_str()
+        .expect("text version of jsonb is not valid UTF8")
+        .to_owned();
+
+    // free the cstring returned from direct_function_call -- we don't need it anymore
+    pg_sys::pfree(cstr.as_ptr() as void_mut_ptr);
+
+    // free the detoasted datum if it turned out to be a copy
+    if detoasted != varlena {
+        pg_sys::pfree(detoasted as void_mut_ptr);
+    }
+
+    string
+}
+
+/// Convert a `serde_json` deserialization error into a Postgres ERROR
+fn invalid_json_representation<R>(sql_type: &str, e: serde_json::Error) -> R {
+    ereport(
+        PgLogLevel::ERROR,
+        PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION,
+        &format!("invalid input syntax for type {}: {}", sql_type, e),
+        file!(),
+        line!(),
+        column!(),
+    );
+    unreachable!("ereport(ERROR) returned")
+}
+
 /// for json
 impl FromDatum for Json {
     #[inline]
@@ -50,31 +117,54 @@ impl FromDatum for JsonB {
         } else if datum == 0 {
             panic!("a jsonb Datum was flagged as non-null but the datum is zero")
         } else {
-            let varlena = datum as *mut pg_sys::varlena;
-            let detoasted = pg_sys::pg_detoast_datum_packed(varlena);
-
-            let cstr = direct_function_call::<&std::ffi::CStr>(
-                pg_sys::jsonb_out,
-                vec![Some(detoasted as pg_sys::Datum)],
-            )
-            .expect("failed to convert jsonb to a cstring");
-
-            let value = serde_json::from_str(
-                cstr.to_str()
-                    .expect("text version of jsonb is not valid UTF8"),
-            )
-            .expect("failed to parse JsonB value");
+            let value = serde_json::from_str(&jsonb_datum_to_string(datum))
+                .expect("failed to parse JsonB value");
 
-            // free the cstring returned from direct_function_call -- we don't need it anymore
-            pg_sys::pfree(cstr.as_ptr() as void_mut_ptr);
+            // return the parsed serde_json::Value
+            Some(JsonB(value))
+        }
+    }
+}
 
-            // free the detoasted datum if it turned out to be a copy
-            if detoasted != varlena {
-                pg_sys::pfree(detoasted as void_mut_ptr);
+/// for typed json
+impl<T: DeserializeOwned> FromDatum for JsonOf<T> {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<JsonOf<T>> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a json Datum was flagged as non-null but the datum is zero");
+        } else {
+            let varlena = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+            let len = varsize_any_exhdr(varlena);
+            let data = vardata_any(varlena);
+            let slice = std::slice::from_raw_parts(data as *const u8, len);
+            match serde_json::from_slice(slice) {
+                Ok(value) => Some(JsonOf(value)),
+                Err(e) => invalid_json_representation("json", e),
             }
+        }
+    }
+}
 
-            // return the parsed serde_json::Value
-            Some(JsonB(value))
+/// for typed jsonb
+impl<T: DeserializeOwned> FromDatum for JsonBOf<T> {
+    unsafe fn from_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _: pg_sys::Oid,
+    ) -> Option<JsonBOf<T>> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a jsonb Datum was flagged as non-null but the datum is zero")
+        } else {
+            // deserialize straight from the binary container, without a round-trip through text
+            // or a `serde_json::Value`
+            let jsonb = JsonBRef::from_datum(datum, false, pg_sys::JSONBOID)?;
+            match T::deserialize(jsonb) {
+                Ok(value) => Some(JsonBOf(value)),
+                Err(e) => invalid_json_representation("jsonb", e),
+            }
         }
     }
 }
@@ -144,6 +234,38 @@ impl IntoDatum for JsonB {
     }
 }
 
+/// for typed json
+impl<T: Serialize> IntoDatum for JsonOf<T> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let string = serde_json::to_string(&self.0).expect("failed to serialize JsonOf value");
+        string.into_datum()
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::JSONOID
+    }
+}
+
+/// for typed jsonb
+impl<T: Serialize> IntoDatum for JsonBOf<T> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let string = serde_json::to_string(&self.0).expect("failed to serialize JsonBOf value");
+        let cstring =
+            std::ffi::CString::new(string).expect("string version of jsonb is not valid UTF8");
+
+        unsafe {
+            direct_function_call_as_datum(
+                pg_sys::jsonb_in,
+                vec![Some(cstring.as_ptr() as pg_sys::Datum)],
+            )
+        }
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::JSONBOID
+    }
+}
+
 /// for jsonstring
 impl IntoDatum for JsonString {
     fn into_datum(self) -> Option<pg_sys::Datum> {
@@ -183,3 +305,21 @@ impl Serialize for JsonString {
             .serialize(serializer)
     }
 }
+
+impl<T: Serialize> Serialize for JsonOf<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<T: Serialize> Serialize for JsonBOf<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
@@ -0,0 +1,736 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+use crate::{
+    direct_function_call, direct_function_call_as_datum, pg_sys, varsize_any_exhdr, FromDatum,
+    IntoDatum, Numeric, PgMemoryContexts,
+};
+use serde::de::{
+    self, value::BorrowedStrDeserializer, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+    SeqAccess, VariantAccess, Visitor,
+};
+use std::marker::PhantomData;
+
+/// A borrowed, zero-copy view into a `jsonb` Datum.
+///
+/// Unlike [`JsonB`](crate::JsonB), which converts the whole document into a `serde_json::Value`,
+/// a [`JsonBRef`] walks Postgres' binary `JsonbContainer` in place.  Looking up a single key of a
+/// large document only touches the parts of the document needed to find it.
+///
+/// Strings returned by [`JsonBRef::as_str`] and nested values returned by [`JsonBRef::get`] and
+/// [`JsonBRef::index`] borrow from the original Datum.
+#[derive(Clone, Copy)]
+pub struct JsonBRef<'a> {
+    value: pg_sys::JsonbValue,
+    _marker: PhantomData<&'a pg_sys::Jsonb>,
+}
+
+impl<'a> JsonBRef<'a> {
+    /// Wrap a detoasted `Jsonb` pointer.
+    ///
+    /// ## Safety
+    ///
+    /// `jsonb` must point to a valid, detoasted `Jsonb` that outlives `'a`.
+    pub unsafe fn from_jsonb(jsonb: *mut pg_sys::Jsonb) -> Self {
+        let container = &mut (*jsonb).root as *mut pg_sys::JsonbContainer;
+        if (*container).header & pg_sys::JB_FSCALAR != 0 {
+            // scalars are stored as a one-element "raw scalar" array
+            Self::from_value_ptr(pg_sys::getIthJsonbValueFromContainer(container, 0))
+                .expect("jsonb scalar container is empty")
+        } else {
+            let mut value = pg_sys::JsonbValue::default();
+            value.type_ = pg_sys::jbvType_jbvBinary;
+            value.val.binary.len = varsize_any_exhdr(jsonb as *const pg_sys::varlena) as i32;
+            value.val.binary.data = container;
+            Self::from_value(value)
+        }
+    }
+
+    fn from_value(value: pg_sys::JsonbValue) -> Self {
+        JsonBRef {
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    unsafe fn from_value_ptr(ptr: *mut pg_sys::JsonbValue) -> Option<Self> {
+        if ptr.is_null() {
+            None
+        } else {
+            let value = *ptr;
+            pg_sys::pfree(ptr as crate::void_mut_ptr);
+            Some(Self::from_value(value))
+        }
+    }
+
+    fn container(&self) -> Option<*mut pg_sys::JsonbContainer> {
+        if self.value.type_ == pg_sys::jbvType_jbvBinary {
+            Some(unsafe { self.value.val.binary.data })
+        } else {
+            None
+        }
+    }
+
+    fn container_flags(&self) -> u32 {
+        match self.container() {
+            Some(container) => unsafe { (*container).header },
+            None => 0,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.value.type_ == pg_sys::jbvType_jbvNull
+    }
+
+    pub fn is_string(&self) -> bool {
+        self.value.type_ == pg_sys::jbvType_jbvString
+    }
+
+    pub fn is_number(&self) -> bool {
+        self.value.type_ == pg_sys::jbvType_jbvNumeric
+    }
+
+    pub fn is_bool(&self) -> bool {
+        self.value.type_ == pg_sys::jbvType_jbvBool
+    }
+
+    pub fn is_object(&self) -> bool {
+        self.container_flags() & pg_sys::JB_FOBJECT != 0
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.container_flags() & pg_sys::JB_FARRAY != 0
+    }
+
+    /// The number of elements of an array or pairs of an object, `0` for scalars.
+    pub fn len(&self) -> usize {
+        (self.container_flags() & pg_sys::JB_CMASK) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Look up `key` in an object. Returns `None` if this isn't an object or the key is missing.
+    pub fn get(&self, key: &str) -> Option<JsonBRef<'a>> {
+        if !self.is_object() {
+            return None;
+        }
+
+        let mut needle = pg_sys::JsonbValue::default();
+        needle.type_ = pg_sys::jbvType_jbvString;
+        needle.val.string.len = key.len() as i32;
+        needle.val.string.val = key.as_ptr() as *mut std::os::raw::c_char;
+
+        unsafe {
+            Self::from_value_ptr(pg_sys::findJsonbValueFromContainer(
+                self.container()?,
+                pg_sys::JB_FOBJECT,
+                &mut needle,
+            ))
+        }
+    }
+
+    /// Fetch element `i` of an array. Returns `None` if this isn't an array or `i` is out of bounds.
+    pub fn index(&self, i: usize) -> Option<JsonBRef<'a>> {
+        if !self.is_array() || i >= self.len() {
+            return None;
+        }
+
+        unsafe {
+            Self::from_value_ptr(pg_sys::getIthJsonbValueFromContainer(
+                self.container()?,
+                i as u32,
+            ))
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        if !self.is_string() {
+            return None;
+        }
+
+        unsafe {
+            let string = self.value.val.string;
+            let bytes = std::slice::from_raw_parts(string.val as *const u8, string.len as usize);
+            // Postgres validates jsonb input as UTF8
+            Some(std::str::from_utf8_unchecked(bytes))
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        if self.is_bool() {
+            Some(unsafe { self.value.val.boolean })
+        } else {
+            None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        let numeric = self.numeric_datum()?;
+        unsafe { direct_function_call::<f64>(pg_sys::numeric_float8, vec![Some(numeric)]) }
+    }
+
+    /// Returns the number as an `i64`, rounding any fractional part.
+    ///
+    /// Raises an ERROR if the number is out of range for an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        let numeric = self.numeric_datum()?;
+        unsafe { direct_function_call::<i64>(pg_sys::numeric_int8, vec![Some(numeric)]) }
+    }
+
+    pub fn as_numeric(&self) -> Option<Numeric> {
+        let numeric = self.numeric_datum()?;
+        unsafe { Numeric::from_datum(numeric, false, pg_sys::NUMERICOID) }
+    }
+
+    fn numeric_datum(&self) -> Option<pg_sys::Datum> {
+        if self.is_number() {
+            Some(unsafe { self.value.val.numeric } as pg_sys::Datum)
+        } else {
+            None
+        }
+    }
+
+    /// Iterate the elements of an array. Yields nothing if this isn't an array.
+    pub fn iter(&self) -> JsonBArrayIter<'a> {
+        JsonBArrayIter {
+            array: *self,
+            index: 0,
+            len: if self.is_array() { self.len() } else { 0 },
+        }
+    }
+
+    /// Iterate the `(key, value)` pairs of an object. Yields nothing if this isn't an object.
+    pub fn entries(&self) -> JsonBObjectIter<'a> {
+        let iterator = match self.container() {
+            Some(container) if self.is_object() => unsafe { pg_sys::JsonbIteratorInit(container) },
+            _ => std::ptr::null_mut(),
+        };
+        JsonBObjectIter {
+            iterator,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Iterate the keys of an object. Yields nothing if this isn't an object.
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> {
+        self.entries().map(|(key, _)| key)
+    }
+
+    /// Convert this value, and everything below it, into a `serde_json::Value`.
+    pub fn to_value(&self) -> serde_json::Value {
+        use serde_json::Value;
+
+        if self.is_null() {
+            Value::Null
+        } else if let Some(b) = self.as_bool() {
+            Value::Bool(b)
+        } else if let Some(s) = self.as_str() {
+            Value::String(s.to_string())
+        } else if let Some(numeric) = self.as_numeric() {
+            serde_json::from_str(&numeric.0).expect("jsonb numeric is not a valid JSON number")
+        } else if self.is_array() {
+            Value::Array(self.iter().map(|v| v.to_value()).collect())
+        } else if self.is_object() {
+            Value::Object(
+                self.entries()
+                    .map(|(k, v)| (k.to_string(), v.to_value()))
+                    .collect(),
+            )
+        } else {
+            panic!("unsupported jsonb value type: {}", self.value.type_)
+        }
+    }
+}
+
+impl<'a> std::fmt::Debug for JsonBRef<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("JsonBRef").field(&self.to_value()).finish()
+    }
+}
+
+/// Iterator over the elements of a `jsonb` array, see [`JsonBRef::iter`].
+pub struct JsonBArrayIter<'a> {
+    array: JsonBRef<'a>,
+    index: usize,
+    len: usize,
+}
+
+impl<'a> Iterator for JsonBArrayIter<'a> {
+    type Item = JsonBRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.len {
+            return None;
+        }
+        let value = self.array.index(self.index);
+        self.index += 1;
+        value
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Iterator over the `(key, value)` pairs of a `jsonb` object, see [`JsonBRef::entries`].
+pub struct JsonBObjectIter<'a> {
+    iterator: *mut pg_sys::JsonbIterator,
+    _marker: PhantomData<&'a pg_sys::Jsonb>,
+}
+
+impl<'a> Iterator for JsonBObjectIter<'a> {
+    type Item = (&'a str, JsonBRef<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut key = None;
+        while !self.iterator.is_null() {
+            let mut value = pg_sys::JsonbValue::default();
+            // `skipNested` hands back nested containers as `jbvBinary` values instead of descending
+            let token = unsafe { pg_sys::JsonbIteratorNext(&mut self.iterator, &mut value, true) };
+            match token {
+                pg_sys::JsonbIteratorToken_WJB_KEY => {
+                    key = JsonBRef::from_value(value).as_str();
+                }
+                pg_sys::JsonbIteratorToken_WJB_VALUE => {
+                    return Some((
+                        key.expect("jsonb object value without a key"),
+                        JsonBRef::from_value(value),
+                    ));
+                }
+                pg_sys::JsonbIteratorToken_WJB_DONE => {
+                    // JsonbIteratorNext frees the iterator and sets it to NULL when done
+                    self.iterator = std::ptr::null_mut();
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+impl<'a> FromDatum for JsonBRef<'a> {
+    unsafe fn from_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _: pg_sys::Oid,
+    ) -> Option<JsonBRef<'a>> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a jsonb Datum was flagged as non-null but the datum is zero")
+        } else {
+            let jsonb =
+                pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena) as *mut pg_sys::Jsonb;
+            Some(JsonBRef::from_jsonb(jsonb))
+        }
+    }
+}
+
+impl<'a> IntoDatum for JsonBRef<'a> {
+    fn into_datum(mut self) -> Option<pg_sys::Datum> {
+        Some(unsafe { pg_sys::JsonbValueToJsonb(&mut self.value) } as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::JSONBOID
+    }
+}
+
+/// Deserialize a `T` straight from the binary `jsonb` container, without building a
+/// `serde_json::Value` first.  Strings are borrowed from the Datum.
+impl<'de> serde::Deserializer<'de> for JsonBRef<'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.is_null() {
+            visitor.visit_unit()
+        } else if let Some(b) = self.as_bool() {
+            visitor.visit_bool(b)
+        } else if let Some(s) = self.as_str() {
+            visitor.visit_borrowed_str(s)
+        } else if let Some(numeric) = self.as_numeric() {
+            let number = numeric.0;
+            if let Ok(i) = number.parse::<i64>() {
+                visitor.visit_i64(i)
+            } else if let Ok(u) = number.parse::<u64>() {
+                visitor.visit_u64(u)
+            } else {
+                match number.parse::<f64>() {
+                    Ok(f) => visitor.visit_f64(f),
+                    Err(_) => Err(de::Error::custom(format!(
+                        "jsonb numeric {} is not a valid number",
+                        number
+                    ))),
+                }
+            }
+        } else if self.is_array() {
+            visitor.visit_seq(JsonBSeqAccess(self.iter()))
+        } else if self.is_object() {
+            visitor.visit_map(JsonBMapAccess {
+                entries: self.entries(),
+                value: None,
+            })
+        } else {
+            Err(de::Error::custom(format!(
+                "unsupported jsonb value type: {}",
+                self.value.type_
+            )))
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        // unit variants are strings, the others are objects with the variant as their only key
+        if let Some(variant) = self.as_str() {
+            return visitor.visit_enum(variant.into_deserializer());
+        }
+        let mut entries = self.entries();
+        match (entries.next(), entries.next()) {
+            (Some((variant, value)), None) => {
+                visitor.visit_enum(JsonBEnumAccess { variant, value })
+            }
+            _ => Err(de::Error::invalid_type(
+                de::Unexpected::Other("jsonb value"),
+                &"a string or an object with a single key",
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct JsonBSeqAccess<'de>(JsonBArrayIter<'de>);
+
+impl<'de> SeqAccess<'de> for JsonBSeqAccess<'de> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(value))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len - self.0.index)
+    }
+}
+
+struct JsonBMapAccess<'de> {
+    entries: JsonBObjectIter<'de>,
+    value: Option<JsonBRef<'de>>,
+}
+
+impl<'de> MapAccess<'de> for JsonBMapAccess<'de> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(BorrowedStrDeserializer::new(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("jsonb object value requested before its key"))?;
+        seed.deserialize(value)
+    }
+}
+
+struct JsonBEnumAccess<'de> {
+    variant: &'de str,
+    value: JsonBRef<'de>,
+}
+
+impl<'de> EnumAccess<'de> for JsonBEnumAccess<'de> {
+    type Error = serde_json::Error;
+    type Variant = JsonBRef<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::new(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for JsonBRef<'de> {
+    type Error = serde_json::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        serde::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        serde::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        serde::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+/// Build a `jsonb` value directly, without going through its text representation.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let mut builder = JsonbBuilder::new();
+/// builder
+///     .begin_object()
+///     .key("name")
+///     .string("pgx")
+///     .key("tags")
+///     .begin_array()
+///     .i64(1)
+///     .bool(true)
+///     .end_array()
+///     .end_object();
+/// let jsonb = builder.build();
+/// assert_eq!(jsonb.get("name").and_then(|v| v.as_str()), Some("pgx"));
+/// ```
+///
+/// Values are allocated in the `CurrentMemoryContext`.
+pub struct JsonbBuilder {
+    state: *mut pg_sys::JsonbParseState,
+    result: *mut pg_sys::JsonbValue,
+}
+
+impl Default for JsonbBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonbBuilder {
+    pub fn new() -> Self {
+        JsonbBuilder {
+            state: std::ptr::null_mut(),
+            result: std::ptr::null_mut(),
+        }
+    }
+
+    fn push(&mut self, token: pg_sys::JsonbIteratorToken, value: Option<pg_sys::JsonbValue>) {
+        let mut value = value;
+        let value_ptr = value
+            .as_mut()
+            .map_or(std::ptr::null_mut(), |v| v as *mut pg_sys::JsonbValue);
+        self.result = unsafe { pg_sys::pushJsonbValue(&mut self.state, token, value_ptr) };
+    }
+
+    fn push_scalar(&mut self, value: pg_sys::JsonbValue) -> &mut Self {
+        if self.state.is_null() {
+            // a top-level scalar is stored as a one-element "raw scalar" array
+            let mut array = pg_sys::JsonbValue::default();
+            array.type_ = pg_sys::jbvType_jbvArray;
+            array.val.array.rawScalar = true;
+            self.push(pg_sys::JsonbIteratorToken_WJB_BEGIN_ARRAY, Some(array));
+            self.push(pg_sys::JsonbIteratorToken_WJB_ELEM, Some(value));
+            self.push(pg_sys::JsonbIteratorToken_WJB_END_ARRAY, None);
+        } else {
+            let token = if unsafe { (*self.state).contVal.type_ } == pg_sys::jbvType_jbvObject {
+                pg_sys::JsonbIteratorToken_WJB_VALUE
+            } else {
+                pg_sys::JsonbIteratorToken_WJB_ELEM
+            };
+            self.push(token, Some(value));
+        }
+        self
+    }
+
+    fn string_value(s: &str) -> pg_sys::JsonbValue {
+        let mut value = pg_sys::JsonbValue::default();
+        value.type_ = pg_sys::jbvType_jbvString;
+        value.val.string.len = s.len() as i32;
+        // the value must outlive the builder, so copy it into Postgres' memory
+        value.val.string.val = PgMemoryContexts::CurrentMemoryContext.pstrdup(s);
+        value
+    }
+
+    fn numeric_value(numeric: pg_sys::Datum) -> pg_sys::JsonbValue {
+        let mut value = pg_sys::JsonbValue::default();
+        value.type_ = pg_sys::jbvType_jbvNumeric;
+        value.val.numeric = numeric as pg_sys::Numeric;
+        value
+    }
+
+    pub fn begin_object(&mut self) -> &mut Self {
+        self.push(pg_sys::JsonbIteratorToken_WJB_BEGIN_OBJECT, None);
+        self
+    }
+
+    pub fn end_object(&mut self) -> &mut Self {
+        self.push(pg_sys::JsonbIteratorToken_WJB_END_OBJECT, None);
+        self
+    }
+
+    pub fn begin_array(&mut self) -> &mut Self {
+        self.push(pg_sys::JsonbIteratorToken_WJB_BEGIN_ARRAY, None);
+        self
+    }
+
+    pub fn end_array(&mut self) -> &mut Self {
+        self.push(pg_sys::JsonbIteratorToken_WJB_END_ARRAY, None);
+        self
+    }
+
+    /// Push the key of the next object member. Must be followed by a value.
+    pub fn key(&mut self, key: &str) -> &mut Self {
+        self.push(
+            pg_sys::JsonbIteratorToken_WJB_KEY,
+            Some(Self::string_value(key)),
+        );
+        self
+    }
+
+    pub fn null(&mut self) -> &mut Self {
+        let mut value = pg_sys::JsonbValue::default();
+        value.type_ = pg_sys::jbvType_jbvNull;
+        self.push_scalar(value)
+    }
+
+    pub fn bool(&mut self, b: bool) -> &mut Self {
+        let mut value = pg_sys::JsonbValue::default();
+        value.type_ = pg_sys::jbvType_jbvBool;
+        value.val.boolean = b;
+        self.push_scalar(value)
+    }
+
+    pub fn string(&mut self, s: &str) -> &mut Self {
+        self.push_scalar(Self::string_value(s))
+    }
+
+    pub fn i64(&mut self, i: i64) -> &mut Self {
+        let numeric = unsafe {
+            direct_function_call_as_datum(pg_sys::int8_numeric, vec![i.into_datum()])
+                .expect("int8_numeric returned null")
+        };
+        self.push_scalar(Self::numeric_value(numeric))
+    }
+
+    pub fn f64(&mut self, f: f64) -> &mut Self {
+        let numeric = unsafe {
+            direct_function_call_as_datum(pg_sys::float8_numeric, vec![f.into_datum()])
+                .expect("float8_numeric returned null")
+        };
+        self.push_scalar(Self::numeric_value(numeric))
+    }
+
+    pub fn numeric(&mut self, n: Numeric) -> &mut Self {
+        let numeric = n.into_datum().expect("numeric was null");
+        self.push_scalar(Self::numeric_value(numeric))
+    }
+
+    /// Push an existing `jsonb` value (and everything below it) into the document.
+    pub fn value(&mut self, value: JsonBRef<'_>) -> &mut Self {
+        if value.container().is_none() {
+            self.push_scalar(value.value)
+        } else if self.state.is_null() {
+            // a top-level container is used as is
+            let ptr = PgMemoryContexts::CurrentMemoryContext.palloc_struct::<pg_sys::JsonbValue>();
+            unsafe { *ptr = value.value };
+            self.result = ptr;
+            self
+        } else {
+            let token = if unsafe { (*self.state).contVal.type_ } == pg_sys::jbvType_jbvObject {
+                pg_sys::JsonbIteratorToken_WJB_VALUE
+            } else {
+                pg_sys::JsonbIteratorToken_WJB_ELEM
+            };
+            // `pushJsonbValue` unpacks binary containers for us
+            self.push(token, Some(value.value));
+            self
+        }
+    }
+
+    /// Finish the document.
+    ///
+    /// The returned value borrows from the builder, as both live in the `CurrentMemoryContext`.
+    /// To return the document from a `#[pg_extern]` function, return the builder itself.
+    ///
+    /// Panics if any object or array is still open.
+    pub fn build(&self) -> JsonBRef<'_> {
+        unsafe { JsonBRef::from_jsonb(self.to_jsonb()) }
+    }
+
+    fn to_jsonb(&self) -> *mut pg_sys::Jsonb {
+        if !self.state.is_null() || self.result.is_null() {
+            panic!("JsonbBuilder::build() called on an incomplete document");
+        }
+        unsafe { pg_sys::JsonbValueToJsonb(self.result) }
+    }
+}
+
+impl IntoDatum for JsonbBuilder {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.to_jsonb() as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::JSONBOID
+    }
+}
@@ -13,6 +13,8 @@ mod anyarray;
 mod anyelement;
 mod array;
 mod date;
+mod expanded;
+mod fixed_size;
 mod from;
 mod geo;
 mod inet;
@@ -20,7 +22,10 @@ mod internal;
 mod into;
 mod item_pointer_data;
 mod json;
+mod jsonb_ref;
 mod numeric;
+mod record;
+mod storage;
 mod time;
 mod time_stamp;
 mod time_stamp_with_timezone;
@@ -28,6 +33,7 @@ mod time_with_timezone;
 mod tuples;
 mod uuid;
 mod varlena;
+mod varlena_reader;
 
 pub use self::time::*;
 pub use self::uuid::*;
@@ -35,6 +41,8 @@ pub use anyarray::*;
 pub use anyelement::*;
 pub use array::*;
 pub use date::*;
+pub use expanded::*;
+pub use fixed_size::*;
 pub use from::*;
 pub use geo::*;
 pub use inet::*;
@@ -42,14 +50,18 @@ pub use internal::*;
 pub use into::*;
 pub use item_pointer_data::*;
 pub use json::*;
+pub use jsonb_ref::*;
 pub use numeric::*;
 use once_cell::sync::Lazy;
+pub use record::*;
 use std::any::TypeId;
+pub use storage::*;
 pub use time_stamp::*;
 pub use time_stamp_with_timezone::*;
 pub use time_with_timezone::*;
 pub use tuples::*;
 pub use varlena::*;
+pub use varlena_reader::*;
 
 use crate::PgBox;
 use pgx_utils::sql_entity_graph::RustSqlMapping;
@@ -504,3 +516,52 @@ impl<T: Copy + 'static> WithVarlenaTypeIds<T> {
         }
     }
 }
+
+/// A [`PgExpanded`] compatible type which can have it's [`core::any::TypeId`]s registered for Rust to SQL mapping.
+///
+/// An example use of this trait:
+///
+/// ```rust
+/// use pgx::{
+///     datum::{WithExpandedTypeIds, PgExpanded},
+///     PostgresType, StringInfo, JsonInOutFuncs, pg_extern, pg_sys, IntoDatum, pg_guard,
+/// };
+/// use serde::{Serialize, Deserialize};
+///
+/// #[derive(Debug, Clone, Serialize, Deserialize, PostgresType)]
+/// pub struct Treat { best_part: String, };
+///
+/// let mut mappings = Default::default();
+/// let treat_string = stringify!(Treat).to_string();
+///
+/// pgx::datum::WithExpandedTypeIds::<Treat>::register_expanded(
+///     &mut mappings,
+///     treat_string.clone()
+/// );
+///
+/// assert!(mappings.iter().any(|x| x.id == core::any::TypeId::of::<PgExpanded<Treat>>()));
+/// ```
+pub struct WithExpandedTypeIds<T>(pub core::marker::PhantomData<T>);
+
+impl<T: 'static> WithExpandedTypeIds<T> {
+    pub const EXPANDED_ID: Lazy<Option<TypeId>> = Lazy::new(|| Some(TypeId::of::<PgExpanded<T>>()));
+
+    pub fn register_expanded(
+        map: &mut std::collections::HashSet<RustSqlMapping>,
+        single_sql: String,
+    ) {
+        if let Some(id) = *WithExpandedTypeIds::<T>::EXPANDED_ID {
+            let rust = core::any::type_name::<PgExpanded<T>>();
+            assert_eq!(
+                map.insert(RustSqlMapping {
+                    sql: single_sql.clone(),
+                    rust: rust.to_string(),
+                    id: id,
+                }),
+                true,
+                "Cannot map `{}` twice.",
+                rust,
+            );
+        }
+    }
+}
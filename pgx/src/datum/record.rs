@@ -0,0 +1,143 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+use crate::{heap_tuple_get_datum, pg_sys, IntoDatum, PgTupleDesc};
+
+/// An anonymous `RECORD` to return from a `#[pg_extern]` function.
+///
+/// The shape of the record isn't known until the function is called: it comes from the column
+/// definition list the caller supplies, which [`PgRecord::from_fcinfo`] resolves through
+/// `get_call_result_type()`.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// // SELECT * FROM make_pair() AS (a int, b text);
+/// #[pg_extern]
+/// fn make_pair(fcinfo: pg_sys::FunctionCallInfo) -> PgRecord {
+///     let mut record = PgRecord::from_fcinfo(fcinfo);
+///     record.set(0, 42i32).set(1, "hello");
+///     record
+/// }
+/// ```
+///
+/// Columns that are never set are `NULL`.
+pub struct PgRecord {
+    tupdesc: PgTupleDesc<'static>,
+    values: Vec<pg_sys::Datum>,
+    nulls: Vec<bool>,
+}
+
+impl PgRecord {
+    /// Resolve the record's descriptor from the calling context.
+    ///
+    /// Raises an ERROR if the caller didn't say what columns it expects, for example when the
+    /// function is called as `SELECT f()` rather than `SELECT * FROM f() AS (a int)`.
+    pub fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        let mut tupdesc: pg_sys::TupleDesc = std::ptr::null_mut();
+        let class =
+            unsafe { pg_sys::get_call_result_type(fcinfo, std::ptr::null_mut(), &mut tupdesc) };
+        if class != pg_sys::TypeFuncClass_TYPEFUNC_COMPOSITE || tupdesc.is_null() {
+            crate::error!(
+                "function returning record called in context that cannot accept type record"
+            );
+        }
+
+        // register the descriptor so the returned tuple can be interpreted.  It may belong to the
+        // executor (the caller's column definition list), so we must not free it
+        let tupdesc = unsafe { PgTupleDesc::from_pg(pg_sys::BlessTupleDesc(tupdesc)) };
+        let natts = tupdesc.len();
+        PgRecord {
+            tupdesc,
+            values: vec![0; natts],
+            nulls: vec![true; natts],
+        }
+    }
+
+    /// The descriptor of the record the caller expects
+    pub fn tupdesc(&self) -> &PgTupleDesc<'static> {
+        &self.tupdesc
+    }
+
+    /// How many columns does the record have?
+    pub fn len(&self) -> usize {
+        self.tupdesc.len()
+    }
+
+    /// Does the record have no columns?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Set the value of column `attno`.  Column numbers are zero-based.
+    ///
+    /// Raises an ERROR if there's no such column or if `T` isn't binary-coercible to the column's
+    /// type.
+    pub fn set<T: IntoDatum>(&mut self, attno: usize, value: T) -> &mut Self {
+        let att = match self.tupdesc.get(attno) {
+            Some(att) => att,
+            None => crate::error!(
+                "record has {} columns, cannot set column {}",
+                self.len(),
+                attno + 1
+            ),
+        };
+
+        // binary-coercible types, such as `text` for a `varchar` column, share a representation
+        let typoid = T::type_oid();
+        if typoid != att.atttypid && !unsafe { pg_sys::IsBinaryCoercible(typoid, att.atttypid) } {
+            crate::error!(
+                "column {} of the returned record is of type {}, but the function returned {}",
+                attno + 1,
+                type_name(att.atttypid),
+                type_name(typoid)
+            );
+        }
+
+        match value.into_datum() {
+            Some(datum) => {
+                self.values[attno] = datum;
+                self.nulls[attno] = false;
+            }
+            None => {
+                self.values[attno] = 0;
+                self.nulls[attno] = true;
+            }
+        }
+        self
+    }
+}
+
+fn type_name(typoid: pg_sys::Oid) -> String {
+    unsafe {
+        let name = pg_sys::format_type_be(typoid);
+        let result = std::ffi::CStr::from_ptr(name)
+            .to_string_lossy()
+            .into_owned();
+        pg_sys::pfree(name as crate::void_mut_ptr);
+        result
+    }
+}
+
+impl IntoDatum for PgRecord {
+    fn into_datum(mut self) -> Option<pg_sys::Datum> {
+        let tuple = unsafe {
+            pg_sys::heap_form_tuple(
+                self.tupdesc.as_ptr(),
+                self.values.as_mut_ptr(),
+                self.nulls.as_mut_ptr(),
+            )
+        };
+        Some(heap_tuple_get_datum(tuple))
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::RECORDOID
+    }
+}
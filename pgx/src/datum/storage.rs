@@ -0,0 +1,218 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+use crate::{pg_sys, set_varsize, vardata_any, varsize_any_exhdr, PgMemoryContexts, StringInfo};
+use serde::{Deserialize, Serialize};
+
+/// How a `#[derive(PostgresType)]` type is stored on disk.
+///
+/// Implemented automatically by `#[derive(PostgresType)]` for the serde formats of
+/// `#[pgx(storage = "cbor" | "bincode" | "messagepack")]`, and by hand for types with
+/// `#[pgx(storage = custom)]`.
+///
+/// When [`PostgresTypeStorage::VERSION`] is `Some(version)`, every stored value starts with a tag
+/// byte holding the version it was encoded with.  Values tagged with an older version are read
+/// with [`PostgresTypeStorage::migrate_from`], so changing how a type is encoded only requires
+/// bumping its version and teaching `migrate_from` to read the previous encodings.  Untagged
+/// values (a `VERSION` of `None`) can't be told apart, so changing their encoding corrupts
+/// existing rows.
+///
+/// Types that start versioning after values were already stored untagged set
+/// [`PostgresTypeStorage::LEGACY_UNTAGGED`].  Their version tag is then preceded by
+/// [`LEGACY_UNTAGGED_MARKER`], and stored values without the marker are read as version 0 by
+/// `migrate_from`.
+pub trait PostgresTypeStorage<'de>: Sized {
+    /// The version written in front of the encoding of every value, or `None` for no tag byte
+    const VERSION: Option<u8> = None;
+
+    /// Are there stored values from before this type had a version tag?  Those values must not
+    /// start with [`LEGACY_UNTAGGED_MARKER`], which holds for the untagged CBOR of
+    /// `#[derive(PostgresType)]`
+    const LEGACY_UNTAGGED: bool = false;
+
+    /// Encode `self` by writing to the supplied `StringInfo` buffer
+    fn encode(&self, buffer: &mut StringInfo);
+
+    /// Decode a value encoded by [`PostgresTypeStorage::encode`], without its version tag
+    fn decode(bytes: &'de [u8]) -> Self;
+
+    /// Decode a value encoded with the older `version`, without its version tag.
+    ///
+    /// The default raises an `error!()`, as there are no older versions to read
+    fn migrate_from(version: u8, _bytes: &'de [u8]) -> Self {
+        crate::error!(
+            "{} has no migration from stored version {}",
+            std::any::type_name::<Self>(),
+            version
+        )
+    }
+}
+
+/// Precedes the version tag of [`PostgresTypeStorage::LEGACY_UNTAGGED`] types.  It's a reserved
+/// initial byte in CBOR, so no untagged CBOR value starts with it
+pub const LEGACY_UNTAGGED_MARKER: u8 = 0x1c;
+
+/// `#[derive(PostgresType)]` types with a serde `#[pgx(storage = ..)]` format and a
+/// `#[pgx(version = N)]` greater than zero need to implement this trait to read the values stored
+/// by their earlier versions
+pub trait MigrateStorage<'de>: Sized {
+    /// Decode a value encoded with the older `version`, without its version tag.
+    ///
+    /// It is expected that a version which can't be migrated will raise an `error!()` or `panic!()`
+    fn migrate_from(version: u8, bytes: &'de [u8]) -> Self;
+}
+
+/// The serde formats `#[pgx(storage = ..)]` can store a `#[derive(PostgresType)]` type with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageFormat {
+    /// `#[pgx(storage = "cbor")]`, the default, through `serde_cbor`
+    Cbor,
+
+    /// `#[pgx(storage = "bincode")]`, through `bincode`.  It's more compact than CBOR, but not
+    /// self-describing, so any change to the type's fields needs a new version
+    Bincode,
+
+    /// `#[pgx(storage = "messagepack")]`, through `rmp_serde`.  Structs are stored as arrays of
+    /// their fields, without the field names
+    MessagePack,
+}
+
+impl StorageFormat {
+    /// Encode `value` in this format by writing to the supplied `StringInfo` buffer
+    pub fn encode<T: Serialize + ?Sized>(&self, value: &T, buffer: &mut StringInfo) {
+        match self {
+            StorageFormat::Cbor => {
+                serde_cbor::to_writer(buffer, value).expect("failed to encode as CBOR")
+            }
+            StorageFormat::Bincode => {
+                bincode::serialize_into(buffer, value).expect("failed to encode as bincode")
+            }
+            StorageFormat::MessagePack => {
+                rmp_serde::encode::write(buffer, value).expect("failed to encode as MessagePack")
+            }
+        }
+    }
+
+    /// Decode a `T` encoded in this format
+    pub fn decode<'de, T: Deserialize<'de>>(&self, bytes: &'de [u8]) -> T {
+        match self {
+            StorageFormat::Cbor => serde_cbor::from_slice(bytes).expect("failed to decode CBOR"),
+            StorageFormat::Bincode => {
+                bincode::deserialize(bytes).expect("failed to decode bincode")
+            }
+            StorageFormat::MessagePack => {
+                rmp_serde::from_slice(bytes).expect("failed to decode MessagePack")
+            }
+        }
+    }
+}
+
+/// Encode `value`, and its version tag, by writing to the supplied `StringInfo` buffer
+pub fn storage_encode_into<'de, T>(value: &T, buffer: &mut StringInfo)
+where
+    T: PostgresTypeStorage<'de>,
+{
+    if let Some(version) = T::VERSION {
+        if T::LEGACY_UNTAGGED {
+            buffer.push_bytes(&[LEGACY_UNTAGGED_MARKER]);
+        }
+        buffer.push_bytes(&[version]);
+    }
+    value.encode(buffer);
+}
+
+/// Encode `value` into a new `varlena`, allocated in the `CurrentMemoryContext`
+pub fn storage_encode<'de, T>(value: &T) -> *const pg_sys::varlena
+where
+    T: PostgresTypeStorage<'de>,
+{
+    let mut serialized = StringInfo::new();
+
+    serialized.push_bytes(&[0u8; pg_sys::VARHDRSZ]); // reserve space for the header
+    storage_encode_into(value, &mut serialized);
+
+    let size = serialized.len() as usize;
+    let varlena = serialized.into_char_ptr();
+    unsafe {
+        set_varsize(varlena as *mut pg_sys::varlena, size as i32);
+    }
+
+    varlena as *const pg_sys::varlena
+}
+
+/// Decode the stored `bytes` of a `T`, migrating them if they're tagged with an older version
+pub fn storage_decode_bytes<'de, T>(bytes: &'de [u8]) -> T
+where
+    T: PostgresTypeStorage<'de>,
+{
+    let version = match T::VERSION {
+        Some(version) => version,
+        None => return T::decode(bytes),
+    };
+
+    let bytes = if T::LEGACY_UNTAGGED {
+        match bytes.split_first() {
+            Some((&LEGACY_UNTAGGED_MARKER, bytes)) => bytes,
+            // stored before the type was versioned
+            _ => return T::migrate_from(0, bytes),
+        }
+    } else {
+        bytes
+    };
+
+    match bytes.split_first() {
+        Some((&stored, bytes)) if stored == version => T::decode(bytes),
+        Some((&stored, bytes)) if stored < version => T::migrate_from(stored, bytes),
+        Some((&stored, _)) => crate::error!(
+            "stored {} is version {}, but only versions up to {} can be read",
+            std::any::type_name::<T>(),
+            stored,
+            version
+        ),
+        None => crate::error!(
+            "stored {} is missing its version tag",
+            std::any::type_name::<T>()
+        ),
+    }
+}
+
+/// Decode the stored `varlena` of a `T`, which may be TOASTed
+///
+/// ## Safety
+///
+/// `input` must be a valid, non-null `varlena` holding a value stored by [`storage_encode`]
+pub unsafe fn storage_decode<'de, T>(input: *mut pg_sys::varlena) -> T
+where
+    T: PostgresTypeStorage<'de>,
+{
+    let varlena = pg_sys::pg_detoast_datum_packed(input as *mut pg_sys::varlena);
+    let len = varsize_any_exhdr(varlena);
+    let data = vardata_any(varlena);
+    let slice = std::slice::from_raw_parts(data as *const u8, len);
+    storage_decode_bytes(slice)
+}
+
+/// Decode the stored `varlena` of a `T` after copying it into `memory_context`
+///
+/// ## Safety
+///
+/// `input` must be a valid, non-null `varlena` holding a value stored by [`storage_encode`]
+pub unsafe fn storage_decode_into_context<'de, T>(
+    mut memory_context: PgMemoryContexts,
+    input: *mut pg_sys::varlena,
+) -> T
+where
+    T: PostgresTypeStorage<'de>,
+{
+    memory_context.switch_to(|_| {
+        // this gets the varlena Datum copied into this memory context
+        let varlena = pg_sys::pg_detoast_datum_copy(input as *mut pg_sys::varlena);
+        storage_decode(varlena)
+    })
+}
@@ -7,7 +7,7 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 
-use crate::{pg_sys, FromDatum, IntoDatum};
+use crate::{direct_function_call, pg_sys, void_mut_ptr, FromDatum, IntoDatum};
 use std::ops::{Deref, DerefMut};
 use time::format_description::FormatItem;
 
@@ -17,6 +17,52 @@ pub(crate) const USECS_PER_SEC: i64 = 1_000_000;
 pub(crate) const MINS_PER_HOUR: i64 = 60;
 pub(crate) const SEC_PER_MIN: i64 = 60;
 
+/// `DATEVAL_NOBEGIN` and `DATEVAL_NOEND` from `utils/date.h`
+pub(crate) const DATEVAL_NOBEGIN: i32 = i32::MIN;
+pub(crate) const DATEVAL_NOEND: i32 = i32::MAX;
+
+/// `DT_NOBEGIN` and `DT_NOEND` from `datatype/timestamp.h`
+pub(crate) const DT_NOBEGIN: i64 = i64::MIN;
+pub(crate) const DT_NOEND: i64 = i64::MAX;
+
+/// The special `-infinity` and `infinity` values a [`Date`](crate::Date),
+/// [`Timestamp`](crate::Timestamp) or [`TimestampWithTimeZone`](crate::TimestampWithTimeZone)
+/// can hold
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Infinity {
+    /// `-infinity`, earlier than all other values
+    Negative,
+    /// `infinity`, later than all other values
+    Positive,
+}
+
+impl Infinity {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Infinity::Negative => "-infinity",
+            Infinity::Positive => "infinity",
+        }
+    }
+}
+
+/// Render `datum` through a type's output function, which honors the session's `DateStyle`
+/// and `TimeZone` settings
+pub(crate) fn output_to_string(
+    func: unsafe fn(pg_sys::FunctionCallInfo) -> pg_sys::Datum,
+    datum: Option<pg_sys::Datum>,
+) -> String {
+    unsafe {
+        let cstr = direct_function_call::<&std::ffi::CStr>(func, vec![datum])
+            .expect("output function returned NULL");
+        let string = cstr
+            .to_str()
+            .expect("output function returned invalid UTF8")
+            .to_owned();
+        pg_sys::pfree(cstr.as_ptr() as void_mut_ptr);
+        string
+    }
+}
+
 #[derive(Debug)]
 pub struct Time(pub(crate) time::Time);
 impl FromDatum for Time {
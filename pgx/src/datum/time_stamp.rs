@@ -7,13 +7,21 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 
-use crate::datum::time::USECS_PER_SEC;
-use crate::{direct_function_call_as_datum, pg_sys, FromDatum, IntoDatum, TimestampWithTimeZone};
+use crate::datum::time::{output_to_string, DT_NOBEGIN, DT_NOEND, USECS_PER_SEC};
+use crate::datum::time_stamp_with_timezone::decode_timestamp;
+use crate::{
+    direct_function_call, direct_function_call_as_datum, pg_sys, FromDatum, Infinity, IntoDatum,
+};
 use std::ops::{Deref, DerefMut};
 use time::{format_description::FormatItem, PrimitiveDateTime};
 
+/// A Postgres `timestamp` (without time zone), which may also be `-infinity` or `infinity`.
+///
+/// Use [`Timestamp::as_time()`] to get at the underlying [`time::PrimitiveDateTime`], which
+/// doesn't exist for infinite timestamps.  Infinite timestamps dereference to midnight of
+/// [`time::Date::MIN`] and [`time::Date::MAX`].
 #[derive(Debug, Copy, Clone)]
-pub struct Timestamp(time::PrimitiveDateTime);
+pub struct Timestamp(time::PrimitiveDateTime, Option<Infinity>);
 
 impl From<pg_sys::Timestamp> for Timestamp {
     fn from(item: pg_sys::Timestamp) -> Self {
@@ -23,16 +31,20 @@ impl From<pg_sys::Timestamp> for Timestamp {
 
 impl FromDatum for Timestamp {
     #[inline]
-    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: u32) -> Option<Timestamp> {
-        let ts: Option<TimestampWithTimeZone> =
-            TimestampWithTimeZone::from_datum(datum, is_null, typoid);
-        match ts {
-            None => None,
-            Some(ts) => {
-                let date = ts.date();
-                let time = ts.time();
-
-                Some(Timestamp(PrimitiveDateTime::new(date, time)))
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Timestamp> {
+        if is_null {
+            None
+        } else {
+            match datum as i64 {
+                DT_NOBEGIN => Some(Timestamp::NEG_INFINITY),
+                DT_NOEND => Some(Timestamp::INFINITY),
+                ts => {
+                    let ts = decode_timestamp(ts, None);
+                    Some(Timestamp(
+                        PrimitiveDateTime::new(ts.date(), ts.time()),
+                        None,
+                    ))
+                }
             }
         }
     }
@@ -40,12 +52,19 @@ impl FromDatum for Timestamp {
 impl IntoDatum for Timestamp {
     #[inline]
     fn into_datum(self) -> Option<pg_sys::Datum> {
-        let year = self.year();
-        let month = self.month() as i32;
-        let mday = self.day() as i32;
-        let hour = self.hour() as i32;
-        let minute = self.minute() as i32;
-        let second = self.second() as f64 + (self.microsecond() as f64 / USECS_PER_SEC as f64);
+        match self.1 {
+            Some(Infinity::Negative) => return Some(DT_NOBEGIN as pg_sys::Datum),
+            Some(Infinity::Positive) => return Some(DT_NOEND as pg_sys::Datum),
+            None => {}
+        }
+
+        let ts = self.0;
+        let year = ts.year();
+        let month = ts.month() as i32;
+        let mday = ts.day() as i32;
+        let hour = ts.hour() as i32;
+        let minute = ts.minute() as i32;
+        let second = ts.second() as f64 + (ts.microsecond() as f64 / USECS_PER_SEC as f64);
 
         unsafe {
             direct_function_call_as_datum(
@@ -67,11 +86,59 @@ impl IntoDatum for Timestamp {
     }
 }
 impl Timestamp {
+    /// `-infinity`
+    pub const NEG_INFINITY: Timestamp =
+        Timestamp(time::Date::MIN.midnight(), Some(Infinity::Negative));
+
+    /// `infinity`
+    pub const INFINITY: Timestamp = Timestamp(time::Date::MAX.midnight(), Some(Infinity::Positive));
+
     pub fn new(timestamp: time::PrimitiveDateTime) -> Self {
-        Timestamp(timestamp)
+        Timestamp(timestamp, None)
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        self.1.is_some()
+    }
+
+    /// Which infinity this timestamp is, if any
+    pub fn infinity(&self) -> Option<Infinity> {
+        self.1
+    }
+
+    /// The [`time::PrimitiveDateTime`] this timestamp represents, or `None` if it's infinite
+    pub fn as_time(&self) -> Option<&time::PrimitiveDateTime> {
+        match self.1 {
+            Some(_) => None,
+            None => Some(&self.0),
+        }
+    }
+
+    /// Format with a Postgres `to_char()` template, such as `YYYY-MM-DD HH24:MI:SS`.  Returns
+    /// `None` if this timestamp is infinite.
+    pub fn to_char(&self, format: &str) -> Option<String> {
+        unsafe {
+            direct_function_call::<String>(
+                pg_sys::timestamp_to_char,
+                vec![self.into_datum(), format.into_datum()],
+            )
+        }
+    }
+}
+
+/// Formats the timestamp like Postgres' `timestamp_out`, honoring the session's `DateStyle`
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&output_to_string(pg_sys::timestamp_out, self.into_datum()))
     }
 }
 
+/// Dereferences to the underlying [`time::PrimitiveDateTime`].
+///
+/// Deprecated in favor of [`Timestamp::as_time()`]: infinite values dereference to
+/// midnight of [`time::Date::MIN`] and [`time::Date::MAX`], which is easy to mistake for a
+/// real value.  (`#[deprecated]` has no effect on trait impls, so this can't be flagged by the
+/// compiler.)
 impl Deref for Timestamp {
     type Target = time::PrimitiveDateTime;
 
@@ -93,13 +160,16 @@ impl serde::Serialize for Timestamp {
     where
         S: serde::Serializer,
     {
-        if self.millisecond() > 0 {
+        if let Some(infinity) = self.1 {
+            serializer.serialize_str(infinity.as_str())
+        } else if self.0.millisecond() > 0 {
             serializer.serialize_str(
                 &self
+                    .0
                     .format(
                         &time::format_description::parse(&format!(
                             "[year]-[month]-[day]T[hour]:[minute]:[second].{}-00",
-                            self.millisecond()
+                            self.0.millisecond()
                         ))
                         .map_err(|e| {
                             serde::ser::Error::custom(format!(
@@ -113,7 +183,7 @@ impl serde::Serialize for Timestamp {
                     })?,
             )
         } else {
-            serializer.serialize_str(&self.format(&DEFAULT_TIMESTAMP_FORMAT).map_err(|e| {
+            serializer.serialize_str(&self.0.format(&DEFAULT_TIMESTAMP_FORMAT).map_err(|e| {
                 serde::ser::Error::custom(format!("Timestamp formatting problem: {:?}", e))
             })?)
         }
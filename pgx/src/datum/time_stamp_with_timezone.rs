@@ -7,16 +7,28 @@ All rights reserved.
 Use of this source code is governed by the MIT license that can be found in the LICENSE file.
 */
 
-use crate::datum::time::USECS_PER_SEC;
-use crate::{direct_function_call_as_datum, pg_sys, FromDatum, IntoDatum};
+use crate::datum::time::{output_to_string, DT_NOBEGIN, DT_NOEND, USECS_PER_SEC};
+use crate::{
+    direct_function_call, direct_function_call_as_datum, ereport, pg_sys, FromDatum, Infinity,
+    IntoDatum, PgLogLevel, PgSqlErrorCode,
+};
 use std::{
     convert::TryFrom,
     ops::{Deref, DerefMut},
 };
 use time::{format_description::FormatItem, UtcOffset};
 
+/// A Postgres `timestamp with time zone`, which may also be `-infinity` or `infinity`.
+///
+/// Values read from Postgres are always at UTC.  Use
+/// [`TimestampWithTimeZone::to_session_time_zone()`] or [`TimestampWithTimeZone::at_time_zone()`]
+/// to get the local time somewhere else.
+///
+/// Use [`TimestampWithTimeZone::as_time()`] to get at the underlying [`time::OffsetDateTime`],
+/// which doesn't exist for infinite timestamps.  Infinite timestamps dereference to midnight
+/// (UTC) of [`time::Date::MIN`] and [`time::Date::MAX`].
 #[derive(Debug, Copy, Clone)]
-pub struct TimestampWithTimeZone(time::OffsetDateTime);
+pub struct TimestampWithTimeZone(time::OffsetDateTime, Option<Infinity>);
 
 impl From<pg_sys::TimestampTz> for TimestampWithTimeZone {
     fn from(item: pg_sys::TimestampTz) -> Self {
@@ -28,8 +40,66 @@ impl From<pg_sys::TimestampTz> for TimestampWithTimeZone {
 
 impl From<time::OffsetDateTime> for TimestampWithTimeZone {
     fn from(time: time::OffsetDateTime) -> Self {
-        TimestampWithTimeZone(time)
+        TimestampWithTimeZone(time, None)
+    }
+}
+
+/// Break a finite Postgres timestamp down into its fields, either at UTC or, when `attimezone`
+/// is given, at that time zone
+pub(crate) unsafe fn decode_timestamp(
+    ts: i64,
+    attimezone: Option<*mut pg_sys::pg_tz>,
+) -> time::OffsetDateTime {
+    let mut tm = pg_sys::pg_tm::default();
+    let mut tz = 0i32;
+    let mut fsec = 0 as pg_sys::fsec_t;
+    let mut tzn = std::ptr::null::<std::os::raw::c_char>();
+    let tzp = match attimezone {
+        // Postgres only applies a time zone when asked for the offset
+        Some(_) => &mut tz as *mut i32,
+        None => std::ptr::null_mut(),
+    };
+    if pg_sys::timestamp2tm(
+        ts,
+        tzp,
+        &mut tm,
+        &mut fsec,
+        &mut tzn,
+        attimezone.unwrap_or(std::ptr::null_mut()),
+    ) != 0
+    {
+        ereport(
+            PgLogLevel::ERROR,
+            PgSqlErrorCode::ERRCODE_DATETIME_FIELD_OVERFLOW,
+            "timestamp out of range",
+            file!(),
+            line!(),
+            column!(),
+        );
     }
+
+    let date = time::Date::from_calendar_date(
+        tm.tm_year,
+        time::Month::try_from(tm.tm_mon as u8)
+            .expect("Got month outside of range in TimestampWithTimeZone::from_datum"),
+        tm.tm_mday as u8,
+    )
+    .expect("failed to create date from TimestampWithTimeZone");
+
+    let time = time::Time::from_hms_micro(
+        tm.tm_hour as u8,
+        tm.tm_min as u8,
+        tm.tm_sec as u8,
+        fsec as u32,
+    )
+    .expect("failed to create time from TimestampWithTimeZonez");
+
+    // Postgres offsets are in seconds west of UTC
+    time::PrimitiveDateTime::new(date, time).assume_offset(
+        UtcOffset::from_whole_seconds(-tz).expect(
+            "Unexpected error in `UtcOffset::from_whole_seconds` during `decode_timestamp`",
+        ),
+    )
 }
 
 impl FromDatum for TimestampWithTimeZone {
@@ -42,54 +112,11 @@ impl FromDatum for TimestampWithTimeZone {
         if is_null {
             None
         } else {
-            let mut tm = pg_sys::pg_tm {
-                tm_sec: 0,
-                tm_min: 0,
-                tm_hour: 0,
-                tm_mday: 0,
-                tm_mon: 0,
-                tm_year: 0,
-                tm_wday: 0,
-                tm_yday: 0,
-                tm_isdst: 0,
-                tm_gmtoff: 0,
-                tm_zone: std::ptr::null_mut(),
-            };
-            let mut tz = 0i32;
-            let mut fsec = 0 as pg_sys::fsec_t;
-            let mut tzn = std::ptr::null::<std::os::raw::c_char>();
-            pg_sys::timestamp2tm(
-                datum as i64,
-                &mut tz,
-                &mut tm,
-                &mut fsec,
-                &mut tzn,
-                std::ptr::null_mut(),
-            );
-            let date = time::Date::from_calendar_date(
-                tm.tm_year,
-                time::Month::try_from(tm.tm_mon as u8)
-                    .expect("Got month outside of range in TimestampWithTimeZone::from_datum"),
-                tm.tm_mday as u8,
-            )
-            .expect("failed to create date from TimestampWithTimeZone");
-
-            let time = time::Time::from_hms_micro(
-                tm.tm_hour as u8,
-                tm.tm_min as u8,
-                tm.tm_sec as u8,
-                fsec as u32,
-            )
-            .expect("failed to create time from TimestampWithTimeZonez");
-
-            Some(TimestampWithTimeZone(
-                time::PrimitiveDateTime::new(date, time)
-                    .assume_utc()
-                    .to_offset(
-                        UtcOffset::from_whole_seconds(tz)
-                            .expect("Unexpected error in `UtcOffset::from_whole_seconds` during `TimestampWithTimeZone::from_datum`")
-                    ),
-            ))
+            match datum as i64 {
+                DT_NOBEGIN => Some(TimestampWithTimeZone::NEG_INFINITY),
+                DT_NOEND => Some(TimestampWithTimeZone::INFINITY),
+                ts => Some(TimestampWithTimeZone(decode_timestamp(ts, None), None)),
+            }
         }
     }
 }
@@ -97,12 +124,19 @@ impl FromDatum for TimestampWithTimeZone {
 impl IntoDatum for TimestampWithTimeZone {
     #[inline]
     fn into_datum(self) -> Option<pg_sys::Datum> {
-        let year = self.year();
-        let month = self.month() as i32;
-        let mday = self.day() as i32;
-        let hour = self.hour() as i32;
-        let minute = self.minute() as i32;
-        let second = self.second() as f64 + (self.microsecond() as f64 / USECS_PER_SEC as f64);
+        match self.1 {
+            Some(Infinity::Negative) => return Some(DT_NOBEGIN as pg_sys::Datum),
+            Some(Infinity::Positive) => return Some(DT_NOEND as pg_sys::Datum),
+            None => {}
+        }
+
+        let utc = self.0.to_offset(UtcOffset::UTC);
+        let year = utc.year();
+        let month = utc.month() as i32;
+        let mday = utc.day() as i32;
+        let hour = utc.hour() as i32;
+        let minute = utc.minute() as i32;
+        let second = utc.second() as f64 + (utc.microsecond() as f64 / USECS_PER_SEC as f64);
 
         unsafe {
             direct_function_call_as_datum(
@@ -126,6 +160,18 @@ impl IntoDatum for TimestampWithTimeZone {
 }
 
 impl TimestampWithTimeZone {
+    /// `-infinity`
+    pub const NEG_INFINITY: TimestampWithTimeZone = TimestampWithTimeZone(
+        time::Date::MIN.midnight().assume_utc(),
+        Some(Infinity::Negative),
+    );
+
+    /// `infinity`
+    pub const INFINITY: TimestampWithTimeZone = TimestampWithTimeZone(
+        time::Date::MAX.midnight().assume_utc(),
+        Some(Infinity::Positive),
+    );
+
     /// This shifts the provided `time` back to UTC
     pub fn new(time: time::PrimitiveDateTime, at_tz_offset: time::UtcOffset) -> Self {
         TimestampWithTimeZone(
@@ -134,10 +180,89 @@ impl TimestampWithTimeZone {
                     UtcOffset::from_whole_seconds(-at_tz_offset.whole_seconds())
                         .expect("Unexpected error in `UtcOffset::from_whole_seconds` during `TimestampWithTimeZone::new`")
                 ),
+            None,
         )
     }
+
+    pub fn is_infinite(&self) -> bool {
+        self.1.is_some()
+    }
+
+    /// Which infinity this timestamp is, if any
+    pub fn infinity(&self) -> Option<Infinity> {
+        self.1
+    }
+
+    /// The [`time::OffsetDateTime`] this timestamp represents, or `None` if it's infinite
+    pub fn as_time(&self) -> Option<&time::OffsetDateTime> {
+        match self.1 {
+            Some(_) => None,
+            None => Some(&self.0),
+        }
+    }
+
+    /// The local time in the session's `TimeZone`, or `None` if this timestamp is infinite
+    pub fn to_session_time_zone(&self) -> Option<time::OffsetDateTime> {
+        self.at_pg_tz(unsafe { pg_sys::session_timezone })
+    }
+
+    /// The local time in the named time zone, or `None` if this timestamp is infinite.
+    ///
+    /// `time_zone` is anything Postgres accepts for `AT TIME ZONE`, such as `America/Denver`.
+    /// Raises an ERROR if the time zone isn't recognized.
+    pub fn at_time_zone(&self, time_zone: &str) -> Option<time::OffsetDateTime> {
+        let name = std::ffi::CString::new(time_zone).expect("time zone name contains a NUL byte");
+        let tz = unsafe { pg_sys::pg_tzset(name.as_ptr()) };
+        if tz.is_null() {
+            ereport(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+                &format!("time zone \"{}\" not recognized", time_zone),
+                file!(),
+                line!(),
+                column!(),
+            );
+        }
+        self.at_pg_tz(tz)
+    }
+
+    fn at_pg_tz(&self, tz: *mut pg_sys::pg_tz) -> Option<time::OffsetDateTime> {
+        if self.is_infinite() {
+            return None;
+        }
+        let datum = self.into_datum().expect("timestamptz datum was NULL");
+        Some(unsafe { decode_timestamp(datum as i64, Some(tz)) })
+    }
+
+    /// Format with a Postgres `to_char()` template, such as `YYYY-MM-DD HH24:MI:SS TZ`, in the
+    /// session's `TimeZone`.  Returns `None` if this timestamp is infinite.
+    pub fn to_char(&self, format: &str) -> Option<String> {
+        unsafe {
+            direct_function_call::<String>(
+                pg_sys::timestamptz_to_char,
+                vec![self.into_datum(), format.into_datum()],
+            )
+        }
+    }
+}
+
+/// Formats the timestamp like Postgres' `timestamptz_out`, honoring the session's `DateStyle`
+/// and `TimeZone`
+impl std::fmt::Display for TimestampWithTimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&output_to_string(
+            pg_sys::timestamptz_out,
+            self.into_datum(),
+        ))
+    }
 }
 
+/// Dereferences to the underlying [`time::OffsetDateTime`].
+///
+/// Deprecated in favor of [`TimestampWithTimeZone::as_time()`]: infinite values dereference
+/// to midnight (UTC) of [`time::Date::MIN`] and [`time::Date::MAX`], which is easy to mistake
+/// for a real value.  (`#[deprecated]` has no effect on trait impls, so this can't be flagged
+/// by the compiler.)
 impl Deref for TimestampWithTimeZone {
     type Target = time::OffsetDateTime;
 
@@ -159,13 +284,16 @@ impl serde::Serialize for TimestampWithTimeZone {
     where
         S: serde::Serializer,
     {
-        if self.millisecond() > 0 {
+        if let Some(infinity) = self.1 {
+            serializer.serialize_str(infinity.as_str())
+        } else if self.0.millisecond() > 0 {
             serializer.serialize_str(
                 &self
+                    .0
                     .format(
                         &time::format_description::parse(&format!(
                             "[year]-[month]-[day]T[hour]:[minute]:[second].{}-00",
-                            self.millisecond()
+                            self.0.millisecond()
                         ))
                         .map_err(|e| {
                             serde::ser::Error::custom(format!(
@@ -184,6 +312,7 @@ impl serde::Serialize for TimestampWithTimeZone {
         } else {
             serializer.serialize_str(
                 &self
+                    .0
                     .format(&DEFAULT_TIMESTAMP_WITH_TIMEZONE_FORMAT)
                     .map_err(|e| {
                         serde::ser::Error::custom(format!(
@@ -9,105 +9,80 @@ Use of this source code is governed by the MIT license that can be found in the
 
 use crate::{pg_sys, FromDatum, IntoDatum};
 
-impl<A, B> IntoDatum for (Option<A>, Option<B>)
-where
-    A: IntoDatum,
-    B: IntoDatum,
-{
-    fn into_datum(self) -> Option<pg_sys::Datum> {
-        let vec = vec![self.0.into_datum(), self.1.into_datum()];
-        vec.into_datum()
-    }
-
-    fn type_oid() -> pg_sys::Oid {
-        0
-    }
-}
-
-impl<A, B, C> IntoDatum for (Option<A>, Option<B>, Option<C>)
-where
-    A: IntoDatum,
-    B: IntoDatum,
-    C: IntoDatum,
-{
-    fn into_datum(self) -> Option<pg_sys::Datum> {
-        let vec = vec![
-            self.0.into_datum(),
-            self.1.into_datum(),
-            self.2.into_datum(),
-        ];
-        vec.into_datum()
-    }
-
-    fn type_oid() -> pg_sys::Oid {
-        0
-    }
-}
-
-impl<A, B> FromDatum for (Option<A>, Option<B>)
-where
-    A: FromDatum + IntoDatum,
-    B: FromDatum + IntoDatum,
-{
-    const NEEDS_TYPID: bool = A::NEEDS_TYPID || B::NEEDS_TYPID;
-    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: pg_sys::Oid) -> Option<Self>
-    where
-        Self: Sized,
-    {
-        let mut vec = Vec::<Option<pg_sys::Datum>>::from_datum(datum, is_null, typoid).unwrap();
-        let b = vec.pop().unwrap();
-        let a = vec.pop().unwrap();
-
-        let a_datum = if a.is_some() {
-            A::from_datum(a.unwrap(), false, A::type_oid())
-        } else {
-            None
-        };
-
-        let b_datum = if b.is_some() {
-            B::from_datum(b.unwrap(), false, B::type_oid())
-        } else {
-            None
-        };
-
-        Some((a_datum, b_datum))
-    }
+/// Implements [`IntoDatum`] and [`FromDatum`] for a tuple of `Option`s, which are represented as an
+/// array of the tuple's elements
+macro_rules! impl_tuple_datum {
+    ($($T:ident => $idx:tt),+) => {
+        impl<$($T),+> IntoDatum for ($(Option<$T>,)+)
+        where
+            $($T: IntoDatum,)+
+        {
+            fn into_datum(self) -> Option<pg_sys::Datum> {
+                let vec = vec![$(self.$idx.into_datum()),+];
+                vec.into_datum()
+            }
+
+            fn type_oid() -> pg_sys::Oid {
+                0
+            }
+        }
+
+        impl<$($T),+> FromDatum for ($(Option<$T>,)+)
+        where
+            $($T: FromDatum + IntoDatum,)+
+        {
+            const NEEDS_TYPID: bool = $($T::NEEDS_TYPID)||+;
+            unsafe fn from_datum(
+                datum: pg_sys::Datum,
+                is_null: bool,
+                typoid: pg_sys::Oid,
+            ) -> Option<Self>
+            where
+                Self: Sized,
+            {
+                let vec = Vec::<Option<pg_sys::Datum>>::from_datum(datum, is_null, typoid).unwrap();
+                let mut elements = vec.into_iter();
+
+                Some(($(
+                    match elements.next().expect("tuple array has too few elements") {
+                        Some(datum) => $T::from_datum(datum, false, $T::type_oid()),
+                        None => None,
+                    },
+                )+))
+            }
+        }
+    };
 }
 
-impl<A, B, C> FromDatum for (Option<A>, Option<B>, Option<C>)
-where
-    A: FromDatum + IntoDatum,
-    B: FromDatum + IntoDatum,
-    C: FromDatum + IntoDatum,
-{
-    const NEEDS_TYPID: bool = A::NEEDS_TYPID || B::NEEDS_TYPID || C::NEEDS_TYPID;
-    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: pg_sys::Oid) -> Option<Self>
-    where
-        Self: Sized,
-    {
-        let mut vec = Vec::<Option<pg_sys::Datum>>::from_datum(datum, is_null, typoid).unwrap();
-        let c = vec.pop().unwrap();
-        let b = vec.pop().unwrap();
-        let a = vec.pop().unwrap();
-
-        let a_datum = if a.is_some() {
-            A::from_datum(a.unwrap(), false, A::type_oid())
-        } else {
-            None
-        };
-
-        let b_datum = if b.is_some() {
-            B::from_datum(b.unwrap(), false, B::type_oid())
-        } else {
-            None
-        };
-
-        let c_datum = if c.is_some() {
-            C::from_datum(c.unwrap(), false, C::type_oid())
-        } else {
-            None
-        };
-
-        Some((a_datum, b_datum, c_datum))
-    }
-}
+impl_tuple_datum!(A => 0, B => 1);
+impl_tuple_datum!(A => 0, B => 1, C => 2);
+impl_tuple_datum!(A => 0, B => 1, C => 2, D => 3);
+impl_tuple_datum!(A => 0, B => 1, C => 2, D => 3, E => 4);
+impl_tuple_datum!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5);
+impl_tuple_datum!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6);
+impl_tuple_datum!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7);
+impl_tuple_datum!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8);
+impl_tuple_datum!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9);
+impl_tuple_datum!(
+    A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10
+);
+impl_tuple_datum!(
+    A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10,
+    L => 11
+);
+impl_tuple_datum!(
+    A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10,
+    L => 11, M => 12
+);
+impl_tuple_datum!(
+    A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10,
+    L => 11, M => 12, N => 13
+);
+impl_tuple_datum!(
+    A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10,
+    L => 11, M => 12, N => 13, O => 14
+);
+impl_tuple_datum!(
+    A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7, I => 8, J => 9, K => 10,
+    L => 11, M => 12, N => 13, O => 14, P => 15
+);
@@ -9,8 +9,9 @@ Use of this source code is governed by the MIT license that can be found in the
 //! Wrapper for Postgres 'varlena' type, over Rust types of a fixed size (ie, `impl Copy`)
 use crate::pg_sys::{VARATT_SHORT_MAX, VARHDRSZ_SHORT};
 use crate::{
-    pg_sys, rust_regtypein, set_varsize, set_varsize_short, vardata_any, varsize_any,
-    varsize_any_exhdr, void_mut_ptr, FromDatum, IntoDatum, PgMemoryContexts, PostgresType,
+    pg_sys, rust_regtypein, set_varsize, set_varsize_short, storage_decode,
+    storage_decode_into_context, storage_encode, vardata_any, varsize_any, varsize_any_exhdr,
+    void_mut_ptr, FromDatum, IntoDatum, PgMemoryContexts, PostgresType, PostgresTypeStorage,
     StringInfo,
 };
 use pgx_pg_sys::varlena;
@@ -332,12 +333,12 @@ where
     }
 }
 
-impl<T> IntoDatum for T
+impl<'de, T> IntoDatum for T
 where
-    T: PostgresType + Serialize,
+    T: PostgresType + PostgresTypeStorage<'de>,
 {
     fn into_datum(self) -> Option<pg_sys::Datum> {
-        Some(cbor_encode(&self) as pg_sys::Datum)
+        Some(storage_encode(&self) as pg_sys::Datum)
     }
 
     fn type_oid() -> u32 {
@@ -347,13 +348,13 @@ where
 
 impl<'de, T> FromDatum for T
 where
-    T: PostgresType + Deserialize<'de>,
+    T: PostgresType + PostgresTypeStorage<'de>,
 {
     unsafe fn from_datum(datum: usize, is_null: bool, _typoid: u32) -> Option<Self> {
         if is_null {
             None
         } else {
-            cbor_decode(datum as *mut pg_sys::varlena)
+            Some(storage_decode(datum as *mut pg_sys::varlena))
         }
     }
 
@@ -366,29 +367,14 @@ where
         if is_null {
             None
         } else {
-            cbor_decode_into_context(memory_context, datum as *mut pg_sys::varlena)
+            Some(storage_decode_into_context(
+                memory_context,
+                datum as *mut pg_sys::varlena,
+            ))
         }
     }
 }
 
-fn cbor_encode<T>(input: T) -> *const pg_sys::varlena
-where
-    T: Serialize,
-{
-    let mut serialized = StringInfo::new();
-
-    serialized.push_bytes(&[0u8; pg_sys::VARHDRSZ]); // reserve space fo the header
-    serde_cbor::to_writer(&mut serialized, &input).expect("failed to encode as CBOR");
-
-    let size = serialized.len() as usize;
-    let varlena = serialized.into_char_ptr();
-    unsafe {
-        set_varsize(varlena as *mut pg_sys::varlena, size as i32);
-    }
-
-    varlena as *const pg_sys::varlena
-}
-
 pub unsafe fn cbor_decode<'de, T>(input: *mut pg_sys::varlena) -> T
 where
     T: Deserialize<'de>,
@@ -0,0 +1,178 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+use crate::{
+    pg_sys, toast_raw_datum_size, vardata_any, varsize_any_exhdr, void_mut_ptr, FromDatum,
+};
+use std::io::{Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+
+/// The Postgres type a [`PgVarlenaReader`] reads
+pub trait VarlenaReaderKind {
+    fn type_oid() -> pg_sys::Oid;
+}
+
+/// Marks a [`PgVarlenaReader`] over a `bytea`
+pub enum ByteaVarlena {}
+
+/// Marks a [`PgVarlenaReader`] over a `text`, whose bytes are UTF8
+pub enum TextVarlena {}
+
+impl VarlenaReaderKind for ByteaVarlena {
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::BYTEAOID
+    }
+}
+
+impl VarlenaReaderKind for TextVarlena {
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::TEXTOID
+    }
+}
+
+/// A streaming [`PgVarlenaReader`] over a `bytea` argument
+pub type PgByteaReader = PgVarlenaReader<ByteaVarlena>;
+
+/// A streaming [`PgVarlenaReader`] over a `text` argument
+pub type PgTextReader = PgVarlenaReader<TextVarlena>;
+
+/// Reads a (possibly TOASTed) varlena Datum without detoasting all of it up front.
+///
+/// Unlike `&[u8]` and `&str` arguments, which are fully detoasted into memory before the function
+/// runs, each [`Read::read`] only fetches the slice of the value it was asked for, through
+/// `pg_detoast_datum_slice()`.  For values stored uncompressed (`STORAGE EXTERNAL`) only the TOAST
+/// chunks covering that slice are read; compressed values still have to be decompressed from their
+/// beginning, so prefer large reads.
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use std::io::Read;
+///
+/// #[pg_extern]
+/// fn count_zero_bytes(mut input: PgByteaReader) -> i64 {
+///     let mut buf = vec![0u8; 1024 * 1024];
+///     let mut zeros = 0;
+///     loop {
+///         let n = input.read(&mut buf).unwrap();
+///         if n == 0 {
+///             break;
+///         }
+///         zeros += buf[..n].iter().filter(|b| **b == 0).count() as i64;
+///     }
+///     zeros
+/// }
+/// ```
+pub struct PgVarlenaReader<K: VarlenaReaderKind = ByteaVarlena> {
+    varlena: *mut pg_sys::varlena,
+    len: u64,
+    pos: u64,
+    _kind: PhantomData<K>,
+}
+
+impl<K: VarlenaReaderKind> PgVarlenaReader<K> {
+    /// Wrap a varlena Datum, which may be TOASTed.
+    ///
+    /// ## Safety
+    ///
+    /// `datum` must be a valid, non-null varlena Datum that outlives this reader.
+    pub unsafe fn from_datum_unchecked(datum: pg_sys::Datum) -> Self {
+        let len = toast_raw_datum_size(datum) - pg_sys::VARHDRSZ;
+        PgVarlenaReader {
+            varlena: datum as *mut pg_sys::varlena,
+            len: len as u64,
+            pos: 0,
+            _kind: PhantomData,
+        }
+    }
+
+    /// The number of bytes in the fully detoasted value
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The position the next read starts at
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+}
+
+impl<K: VarlenaReaderKind> Read for PgVarlenaReader<K> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.len {
+            return Ok(0);
+        }
+
+        let want = (buf.len() as u64).min(self.len - self.pos) as usize;
+        let got = unsafe {
+            let slice = pg_sys::pg_detoast_datum_slice(self.varlena, self.pos as i32, want as i32);
+            let got = varsize_any_exhdr(slice).min(want);
+            std::ptr::copy_nonoverlapping(vardata_any(slice) as *const u8, buf.as_mut_ptr(), got);
+
+            // `pg_detoast_datum_slice` always returns a copy of the slice
+            if slice != self.varlena {
+                pg_sys::pfree(slice as void_mut_ptr);
+            }
+            got
+        };
+
+        self.pos += got as u64;
+        Ok(got)
+    }
+}
+
+impl<K: VarlenaReaderKind> Seek for PgVarlenaReader<K> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => add_offset(self.len, offset),
+            SeekFrom::Current(offset) => add_offset(self.pos, offset),
+        };
+
+        match new_pos {
+            Some(new_pos) => {
+                self.pos = new_pos;
+                Ok(new_pos)
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+fn add_offset(base: u64, offset: i64) -> Option<u64> {
+    if offset >= 0 {
+        base.checked_add(offset as u64)
+    } else {
+        base.checked_sub(offset.unsigned_abs())
+    }
+}
+
+impl<K: VarlenaReaderKind> FromDatum for PgVarlenaReader<K> {
+    const NEEDS_TYPID: bool = false;
+
+    unsafe fn from_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a varlena Datum was flagged as non-null but the datum is zero");
+        } else {
+            Some(PgVarlenaReader::from_datum_unchecked(datum))
+        }
+    }
+}
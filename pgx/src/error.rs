@@ -0,0 +1,165 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Errors returned by a `#[pg_extern]` function as the `Err` of a `Result`, which are raised as
+//! an `ERROR` with their SQLSTATE
+use crate::{PgErrorReport, PgLogLevel, PgSqlErrorCode};
+use std::fmt::{self, Display, Formatter};
+
+/// An `ERROR` to raise, with its SQLSTATE, message, and optional detail and hint.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn checked_divide(dividend: i32, divisor: i32) -> Result<i32, PgError> {
+///     if divisor == 0 {
+///         return Err(PgError::new(PgSqlErrorCode::ERRCODE_DIVISION_BY_ZERO, "division by zero")
+///             .with_hint("divide by something else"));
+///     }
+///     Ok(dividend / divisor)
+/// }
+/// ```
+///
+/// A `#[pg_extern]` function can return `Result<T, E>` for any `E: Into<PgError>`, and its
+/// `Err` is raised with [`PgError::report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgError {
+    code: PgSqlErrorCode,
+    message: String,
+    detail: Option<String>,
+    hint: Option<String>,
+}
+
+impl PgError {
+    /// An error with the SQLSTATE `code` and primary `message`
+    pub fn new<S: Into<String>>(code: PgSqlErrorCode, message: S) -> Self {
+        PgError {
+            code,
+            message: message.into(),
+            detail: None,
+            hint: None,
+        }
+    }
+
+    /// Add the `DETAIL` line of the error
+    pub fn with_detail<S: Into<String>>(mut self, detail: S) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Add the `HINT` line of the error
+    pub fn with_hint<S: Into<String>>(mut self, hint: S) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    pub fn code(&self) -> PgSqlErrorCode {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    /// Raise the error with `ereport(ERROR)`, aborting the current transaction
+    #[track_caller]
+    pub fn report(self) -> ! {
+        PgErrorReport::from(self).report(PgLogLevel::ERROR);
+        unreachable!("ereport(ERROR) returned")
+    }
+}
+
+impl From<PgError> for PgErrorReport {
+    fn from(error: PgError) -> Self {
+        let mut report = PgErrorReport::new(error.code, error.message);
+        if let Some(detail) = error.detail {
+            report = report.with_detail(detail);
+        }
+        if let Some(hint) = error.hint {
+            report = report.with_hint(hint);
+        }
+        report
+    }
+}
+
+impl Display for PgError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for PgError {}
+
+impl From<std::io::Error> for PgError {
+    fn from(error: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        let code = match error.kind() {
+            ErrorKind::NotFound => PgSqlErrorCode::ERRCODE_UNDEFINED_FILE,
+            ErrorKind::AlreadyExists => PgSqlErrorCode::ERRCODE_DUPLICATE_FILE,
+            ErrorKind::PermissionDenied => PgSqlErrorCode::ERRCODE_INSUFFICIENT_PRIVILEGE,
+            _ => PgSqlErrorCode::ERRCODE_IO_ERROR,
+        };
+        PgError::new(code, error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for PgError {
+    fn from(error: serde_json::Error) -> Self {
+        use serde_json::error::Category;
+
+        let code = match error.classify() {
+            Category::Io => PgSqlErrorCode::ERRCODE_IO_ERROR,
+            Category::Syntax | Category::Eof => PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION,
+            Category::Data => PgSqlErrorCode::ERRCODE_DATA_EXCEPTION,
+        };
+        PgError::new(code, error.to_string())
+    }
+}
+
+/// A `PgError` within the report is raised as it is, otherwise the report is an internal error
+/// whose causes are its detail
+impl From<eyre::Report> for PgError {
+    fn from(report: eyre::Report) -> Self {
+        if let Some(error) = report.downcast_ref::<PgError>() {
+            return error.clone();
+        }
+
+        let causes = report
+            .chain()
+            .skip(1)
+            .map(|cause| cause.to_string())
+            .collect::<Vec<_>>();
+        let error = PgError::new(PgSqlErrorCode::ERRCODE_INTERNAL_ERROR, report.to_string());
+        if causes.is_empty() {
+            error
+        } else {
+            error.with_detail(causes.join(": "))
+        }
+    }
+}
+
+/// The `Ok` value of a `#[pg_extern]` function's `Result`, or its `Err` raised as an `ERROR`
+#[doc(hidden)]
+#[track_caller]
+pub fn unwrap_or_report<T, E: Into<PgError>>(result: Result<T, E>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => error.into().report(),
+    }
+}
@@ -8,7 +8,12 @@ Use of this source code is governed by the MIT license that can be found in the
 */
 
 //! A trait and registration system for hooking Postgres internal operations such as its planner and executor
-use crate::{pg_guard, pg_sys, void_mut_ptr, PgBox, PgList};
+use crate::{
+    pg_guard, pg_sys, void_mut_ptr, FromDatum, IntoDatum, JumbledQuery, PgBox, PgError, PgList,
+    PgSqlErrorCode, TimestampWithTimeZone,
+};
+use std::borrow::Cow;
+use std::ffi::{CStr, CString};
 use std::ops::Deref;
 
 pub struct HookResult<T> {
@@ -29,6 +34,377 @@ impl<T> Deref for HookResult<T> {
     }
 }
 
+/// The `ErrorData` of a message passed to [`PgHooks::emit_log`], which can be inspected and kept
+/// from the server log or the client
+pub struct ErrorReportMut<'a> {
+    edata: &'a mut pg_sys::ErrorData,
+}
+
+/// A string Postgres owns, or `None` if it's `NULL`
+fn string<'a>(ptr: *const std::os::raw::c_char) -> Option<Cow<'a, str>> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(ptr) }.to_string_lossy())
+    }
+}
+
+impl<'a> ErrorReportMut<'a> {
+    /// The level of the message, such as `pg_sys::WARNING`
+    pub fn elevel(&self) -> i32 {
+        self.edata.elevel
+    }
+
+    /// The encoded SQLSTATE of the message
+    pub fn sqlerrcode(&self) -> i32 {
+        self.edata.sqlerrcode
+    }
+
+    /// The five character SQLSTATE of the message, such as `01000`
+    pub fn sqlstate(&self) -> String {
+        unsafe { CStr::from_ptr(pg_sys::unpack_sql_state(self.edata.sqlerrcode)) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn message(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.message)
+    }
+
+    pub fn detail(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.detail)
+    }
+
+    /// The detail which is only sent to the server log
+    pub fn detail_log(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.detail_log)
+    }
+
+    pub fn hint(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.hint)
+    }
+
+    pub fn context(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.context)
+    }
+
+    #[cfg(any(feature = "pg13", feature = "pg14"))]
+    pub fn backtrace(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.backtrace)
+    }
+
+    pub fn schema_name(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.schema_name)
+    }
+
+    pub fn table_name(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.table_name)
+    }
+
+    pub fn column_name(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.column_name)
+    }
+
+    pub fn datatype_name(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.datatype_name)
+    }
+
+    pub fn constraint_name(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.constraint_name)
+    }
+
+    /// The position of the error in the query string, counting characters from 1, or 0
+    pub fn cursor_position(&self) -> i32 {
+        self.edata.cursorpos
+    }
+
+    /// The position of the error in the internally-generated query, counting characters from 1,
+    /// or 0
+    pub fn internal_position(&self) -> i32 {
+        self.edata.internalpos
+    }
+
+    pub fn internal_query(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.internalquery)
+    }
+
+    /// The source file of the `ereport()` call
+    pub fn filename(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.filename)
+    }
+
+    /// The source line of the `ereport()` call
+    pub fn lineno(&self) -> i32 {
+        self.edata.lineno
+    }
+
+    /// The function of the `ereport()` call
+    pub fn funcname(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.funcname)
+    }
+
+    /// The message domain, used to translate the message
+    pub fn domain(&self) -> Option<Cow<'a, str>> {
+        string(self.edata.domain)
+    }
+
+    /// The `errno` at the time of the `ereport()` call
+    pub fn saved_errno(&self) -> i32 {
+        self.edata.saved_errno
+    }
+
+    /// Is the statement left out of the server log?
+    pub fn hide_stmt(&self) -> bool {
+        self.edata.hide_stmt
+    }
+
+    /// Is the context left out of the server log?
+    pub fn hide_ctx(&self) -> bool {
+        self.edata.hide_ctx
+    }
+
+    /// Is the message going to the server log?
+    pub fn output_to_server(&self) -> bool {
+        self.edata.output_to_server
+    }
+
+    /// Is the message going to the client?
+    pub fn output_to_client(&self) -> bool {
+        self.edata.output_to_client
+    }
+
+    /// Send the message to the server log, or not
+    pub fn set_output_to_server(&mut self, output_to_server: bool) {
+        self.edata.output_to_server = output_to_server;
+    }
+
+    /// Send the message to the client, or not
+    pub fn set_output_to_client(&mut self, output_to_client: bool) {
+        self.edata.output_to_client = output_to_client;
+    }
+}
+
+/// An access to an object passed to [`PgHooks::object_access`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectAccessEvent {
+    /// The object was just created, and isn't visible in the catalogs until the next command
+    PostCreate { is_internal: bool },
+    /// The object is about to be dropped, with the `PERFORM_DELETION_*` flags of the drop
+    Drop { flags: i32 },
+    /// The object was just altered, and its change isn't visible until the next command
+    PostAlter {
+        auxiliary_id: pg_sys::Oid,
+        is_internal: bool,
+    },
+    /// The schema is about to be searched for an object
+    NamespaceSearch { ereport_on_violation: bool },
+    /// The function is about to be executed
+    FunctionExecute,
+    /// The table is about to be truncated
+    #[cfg(feature = "pg14")]
+    Truncate,
+}
+
+impl ObjectAccessEvent {
+    /// The event of an `ObjectAccessType` and its argument, or `None` for one pgx doesn't know
+    unsafe fn from_hook(access: pg_sys::ObjectAccessType, arg: void_mut_ptr) -> Option<Self> {
+        match access {
+            pg_sys::ObjectAccessType_OAT_POST_CREATE => {
+                let arg = (arg as *mut pg_sys::ObjectAccessPostCreate).as_ref();
+                Some(ObjectAccessEvent::PostCreate {
+                    is_internal: arg.map_or(false, |arg| arg.is_internal),
+                })
+            }
+            pg_sys::ObjectAccessType_OAT_DROP => {
+                let arg = (arg as *mut pg_sys::ObjectAccessDrop).as_ref();
+                Some(ObjectAccessEvent::Drop {
+                    flags: arg.map_or(0, |arg| arg.dropflags),
+                })
+            }
+            pg_sys::ObjectAccessType_OAT_POST_ALTER => {
+                let arg = (arg as *mut pg_sys::ObjectAccessPostAlter).as_ref();
+                Some(ObjectAccessEvent::PostAlter {
+                    auxiliary_id: arg.map_or(pg_sys::InvalidOid, |arg| arg.auxiliary_id),
+                    is_internal: arg.map_or(false, |arg| arg.is_internal),
+                })
+            }
+            pg_sys::ObjectAccessType_OAT_NAMESPACE_SEARCH => {
+                let arg = (arg as *mut pg_sys::ObjectAccessNamespaceSearch).as_ref();
+                Some(ObjectAccessEvent::NamespaceSearch {
+                    ereport_on_violation: arg.map_or(true, |arg| arg.ereport_on_violation),
+                })
+            }
+            pg_sys::ObjectAccessType_OAT_FUNCTION_EXECUTE => {
+                Some(ObjectAccessEvent::FunctionExecute)
+            }
+            #[cfg(feature = "pg14")]
+            pg_sys::ObjectAccessType_OAT_TRUNCATE => Some(ObjectAccessEvent::Truncate),
+            _ => None,
+        }
+    }
+}
+
+/// The address of an object passed to [`PgHooks::object_access`]
+#[derive(Debug, Clone, Copy)]
+pub struct PgObjectAddress(pg_sys::ObjectAddress);
+
+impl PgObjectAddress {
+    pub fn new(class_id: pg_sys::Oid, object_id: pg_sys::Oid, sub_id: i32) -> Self {
+        PgObjectAddress(pg_sys::ObjectAddress {
+            classId: class_id,
+            objectId: object_id,
+            objectSubId: sub_id,
+        })
+    }
+
+    /// The catalog the object is in, such as `pg_sys::RelationRelationId`
+    pub fn class_id(&self) -> pg_sys::Oid {
+        self.0.classId
+    }
+
+    pub fn object_id(&self) -> pg_sys::Oid {
+        self.0.objectId
+    }
+
+    /// The column number of a table's column, otherwise zero
+    pub fn sub_id(&self) -> i32 {
+        self.0.objectSubId
+    }
+
+    /// The class of the object, such as `pg_sys::ObjectClass_OCLASS_CLASS`
+    pub fn class(&self) -> pg_sys::ObjectClass {
+        unsafe { pg_sys::getObjectClass(&self.0) }
+    }
+
+    /// The qualified name of the object, such as `public.foo`, or `None` on Postgres 14 if it
+    /// isn't visible in the catalogs.  Earlier versions raise an `ERROR` instead.
+    pub fn identity(&self) -> Option<String> {
+        #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+        let identity = unsafe { pg_sys::getObjectIdentity(&self.0) };
+        #[cfg(feature = "pg14")]
+        let identity = unsafe { pg_sys::getObjectIdentity(&self.0, true) };
+        string(identity).map(Cow::into_owned)
+    }
+
+    /// The kind of the object, such as `table` or `function`, or `None` on Postgres 14 if it
+    /// isn't visible in the catalogs.  Earlier versions raise an `ERROR` instead.
+    pub fn object_type(&self) -> Option<String> {
+        #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+        let object_type = unsafe { pg_sys::getObjectTypeDescription(&self.0) };
+        #[cfg(feature = "pg14")]
+        let object_type = unsafe { pg_sys::getObjectTypeDescription(&self.0, true) };
+        string(object_type).map(Cow::into_owned)
+    }
+}
+
+/// The outcome of authenticating a client, before [`PgAuthHooks::client_authentication`] sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthStatus {
+    /// The client authenticated
+    Ok,
+    /// The client failed to authenticate
+    Error,
+    /// The client disconnected while authenticating
+    Eof,
+}
+
+impl From<i32> for AuthStatus {
+    fn from(status: i32) -> Self {
+        match status {
+            status if status == pg_sys::STATUS_OK as i32 => AuthStatus::Ok,
+            pg_sys::STATUS_EOF => AuthStatus::Eof,
+            _ => AuthStatus::Error,
+        }
+    }
+}
+
+impl From<AuthStatus> for i32 {
+    fn from(status: AuthStatus) -> Self {
+        match status {
+            AuthStatus::Ok => pg_sys::STATUS_OK as i32,
+            AuthStatus::Error => pg_sys::STATUS_ERROR,
+            AuthStatus::Eof => pg_sys::STATUS_EOF,
+        }
+    }
+}
+
+/// The `Port` of a client connection passed to [`PgAuthHooks::client_authentication`], along
+/// with the status of its authentication
+pub struct PgPort<'a> {
+    port: &'a mut pg_sys::Port,
+    status: AuthStatus,
+}
+
+impl<'a> PgPort<'a> {
+    /// Whether the client authenticated
+    pub fn status(&self) -> AuthStatus {
+        self.status
+    }
+
+    /// The role the client connects as
+    pub fn user_name(&self) -> Option<Cow<'a, str>> {
+        string(self.port.user_name)
+    }
+
+    /// The database the client connects to
+    pub fn database_name(&self) -> Option<Cow<'a, str>> {
+        string(self.port.database_name)
+    }
+
+    /// The client's address, or `[local]` for a Unix socket
+    pub fn remote_host(&self) -> Option<Cow<'a, str>> {
+        string(self.port.remote_host)
+    }
+
+    /// The client's host name, if `log_hostname` or a host name in `pg_hba.conf` looked it up
+    pub fn remote_hostname(&self) -> Option<Cow<'a, str>> {
+        string(self.port.remote_hostname)
+    }
+
+    /// The client's port, which is empty for a Unix socket
+    pub fn remote_port(&self) -> Option<Cow<'a, str>> {
+        string(self.port.remote_port)
+    }
+
+    /// The `application_name` from the client's startup packet
+    pub fn application_name(&self) -> Option<Cow<'a, str>> {
+        string(self.port.application_name)
+    }
+
+    pub fn as_ptr(&self) -> *const pg_sys::Port {
+        &*self.port
+    }
+}
+
+/// How a password passed to [`PgAuthHooks::check_password`] is encrypted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordType {
+    Plaintext,
+    Md5,
+    ScramSha256,
+}
+
+impl From<pg_sys::PasswordType> for PasswordType {
+    fn from(password_type: pg_sys::PasswordType) -> Self {
+        match password_type {
+            pg_sys::PasswordType_PASSWORD_TYPE_PLAINTEXT => PasswordType::Plaintext,
+            pg_sys::PasswordType_PASSWORD_TYPE_MD5 => PasswordType::Md5,
+            pg_sys::PasswordType_PASSWORD_TYPE_SCRAM_SHA_256 => PasswordType::ScramSha256,
+            _ => panic!("unrecognized PasswordType: {}", password_type),
+        }
+    }
+}
+
+impl From<PasswordType> for pg_sys::PasswordType {
+    fn from(password_type: PasswordType) -> Self {
+        match password_type {
+            PasswordType::Plaintext => pg_sys::PasswordType_PASSWORD_TYPE_PLAINTEXT,
+            PasswordType::Md5 => pg_sys::PasswordType_PASSWORD_TYPE_MD5,
+            PasswordType::ScramSha256 => pg_sys::PasswordType_PASSWORD_TYPE_SCRAM_SHA_256,
+        }
+    }
+}
+
 pub trait PgHooks {
     /// Hook for plugins to get control in ExecutorStart()
     fn executor_start(
@@ -139,6 +515,50 @@ pub trait PgHooks {
         prev_hook(parse, query_string, cursor_options, bound_params)
     }
 
+    /// Hook for plugins to get control at the end of parse analysis, such as to track statements
+    /// by their [`JumbledQuery::query_id`].
+    ///
+    /// On Postgres 14, `jumbled_query` is what Postgres computed when `compute_query_id` is
+    /// enabled.  Otherwise it's `None`, and [`jumble_query`](crate::jumble::jumble_query)
+    /// computes it.
+    fn post_parse_analyze(
+        &mut self,
+        parse_state: PgBox<pg_sys::ParseState>,
+        query: PgBox<pg_sys::Query>,
+        jumbled_query: Option<JumbledQuery>,
+        prev_hook: fn(
+            parse_state: PgBox<pg_sys::ParseState>,
+            query: PgBox<pg_sys::Query>,
+            jumbled_query: Option<JumbledQuery>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(parse_state, query, jumbled_query)
+    }
+
+    /// Hook for plugins to get control when an object is created, dropped, altered, or accessed.
+    ///
+    /// Return an `Err` to deny the access, which is raised as an `ERROR`.  For a
+    /// `NamespaceSearch` that doesn't report violations, the schema is quietly skipped instead.
+    fn object_access(
+        &mut self,
+        event: ObjectAccessEvent,
+        object: PgObjectAddress,
+        prev_hook: fn(
+            event: ObjectAccessEvent,
+            object: PgObjectAddress,
+        ) -> HookResult<Result<(), PgError>>,
+    ) -> HookResult<Result<(), PgError>> {
+        prev_hook(event, object)
+    }
+
+    /// Hook for plugins to get control in `EmitErrorReport()`, before a message is sent to the
+    /// server log and the client.  The previous `emit_log_hook`, if any, is called after it.
+    ///
+    /// Messages logged while it runs are emitted without calling it again.  It must not raise an
+    /// `ERROR`.  A panic, which would otherwise become an `ERROR` while the message is being
+    /// emitted, is written to stderr and dropped, and the previous hook is still called.
+    fn emit_log(&mut self, _error_data: &mut ErrorReportMut) {}
+
     /// Called when the transaction aborts
     fn abort(&mut self) {}
 
@@ -146,6 +566,42 @@ pub trait PgHooks {
     fn commit(&mut self) {}
 }
 
+/// A trait for hooking client authentication and password checks, which is registered apart from
+/// [`PgHooks`] with [`register_auth_hook`].
+///
+/// `ClientAuthentication_hook` is only called when the extension is loaded before clients
+/// connect, so its hooks are registered from `_PG_init()` of an extension listed in
+/// `shared_preload_libraries`.
+pub trait PgAuthHooks {
+    /// Hook for plugins to get control in `ClientAuthentication()`, after the client has or has
+    /// not authenticated.  Raise an `ERROR` to refuse the connection.
+    fn client_authentication(
+        &mut self,
+        port: PgPort,
+        prev_hook: fn(port: PgPort) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(port)
+    }
+
+    /// Hook for plugins to check a password given to `CREATE ROLE` or `ALTER ROLE`.  Raise an
+    /// `ERROR` to reject it.
+    fn check_password(
+        &mut self,
+        username: &str,
+        shadow_pass: &str,
+        password_type: PasswordType,
+        valid_until: Option<TimestampWithTimeZone>,
+        prev_hook: fn(
+            username: &str,
+            shadow_pass: &str,
+            password_type: PasswordType,
+            valid_until: Option<TimestampWithTimeZone>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(username, shadow_pass, password_type, valid_until)
+    }
+}
+
 struct Hooks {
     current_hook: Box<&'static mut (dyn PgHooks)>,
     prev_executor_start_hook: pg_sys::ExecutorStart_hook_type,
@@ -155,6 +611,9 @@ struct Hooks {
     prev_executor_check_perms_hook: pg_sys::ExecutorCheckPerms_hook_type,
     prev_process_utility_hook: pg_sys::ProcessUtility_hook_type,
     prev_planner_hook: pg_sys::planner_hook_type,
+    prev_post_parse_analyze_hook: pg_sys::post_parse_analyze_hook_type,
+    prev_object_access_hook: pg_sys::object_access_hook_type,
+    prev_emit_log_hook: pg_sys::emit_log_hook_type,
 }
 
 static mut HOOKS: Option<Hooks> = None;
@@ -187,6 +646,13 @@ pub unsafe fn register_hook(hook: &'static mut (dyn PgHooks)) {
         prev_planner_hook: pg_sys::planner_hook
             .replace(pgx_planner)
             .or(Some(pgx_standard_planner_wrapper)),
+        prev_post_parse_analyze_hook: pg_sys::post_parse_analyze_hook
+            .replace(pgx_post_parse_analyze)
+            .or(Some(pgx_standard_post_parse_analyze_wrapper)),
+        prev_object_access_hook: pg_sys::object_access_hook
+            .replace(pgx_object_access)
+            .or(Some(pgx_standard_object_access_wrapper)),
+        prev_emit_log_hook: pg_sys::emit_log_hook.replace(pgx_emit_log),
     });
 
     unsafe extern "C" fn xact_callback(event: pg_sys::XactEvent, _: void_mut_ptr) {
@@ -204,6 +670,30 @@ pub unsafe fn register_hook(hook: &'static mut (dyn PgHooks)) {
     pg_sys::RegisterXactCallback(Some(xact_callback), std::ptr::null_mut());
 }
 
+struct AuthHooks {
+    current_hook: Box<&'static mut (dyn PgAuthHooks)>,
+    prev_client_authentication_hook: pg_sys::ClientAuthentication_hook_type,
+    prev_check_password_hook: pg_sys::check_password_hook_type,
+}
+
+static mut AUTH_HOOKS: Option<AuthHooks> = None;
+
+/// Register a `PgAuthHooks` instance to respond to client authentication and password checks
+pub unsafe fn register_auth_hook(hook: &'static mut (dyn PgAuthHooks)) {
+    if AUTH_HOOKS.is_some() {
+        panic!("PgAuthHooks instance already registered");
+    }
+    AUTH_HOOKS = Some(AuthHooks {
+        current_hook: Box::new(hook),
+        prev_client_authentication_hook: pg_sys::ClientAuthentication_hook
+            .replace(pgx_client_authentication)
+            .or(Some(pgx_standard_client_authentication_wrapper)),
+        prev_check_password_hook: pg_sys::check_password_hook
+            .replace(pgx_check_password)
+            .or(Some(pgx_standard_check_password_wrapper)),
+    });
+}
+
 #[pg_guard]
 unsafe extern "C" fn pgx_executor_start(query_desc: *mut pg_sys::QueryDesc, eflags: i32) {
     fn prev(query_desc: PgBox<pg_sys::QueryDesc>, eflags: i32) -> HookResult<()> {
@@ -486,6 +976,288 @@ unsafe extern "C" fn pgx_planner_impl(
     .inner
 }
 
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+#[pg_guard]
+unsafe extern "C" fn pgx_post_parse_analyze(
+    parse_state: *mut pg_sys::ParseState,
+    query: *mut pg_sys::Query,
+) {
+    fn prev(
+        parse_state: PgBox<pg_sys::ParseState>,
+        query: PgBox<pg_sys::Query>,
+        _jumbled_query: Option<JumbledQuery>,
+    ) -> HookResult<()> {
+        unsafe {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_post_parse_analyze_hook
+                .as_ref()
+                .unwrap())(parse_state.into_pg(), query.into_pg())
+        }
+        HookResult::new(())
+    }
+    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
+    hook.post_parse_analyze(
+        PgBox::from_pg(parse_state),
+        PgBox::from_pg(query),
+        None,
+        prev,
+    );
+}
+
+#[cfg(feature = "pg14")]
+#[pg_guard]
+unsafe extern "C" fn pgx_post_parse_analyze(
+    parse_state: *mut pg_sys::ParseState,
+    query: *mut pg_sys::Query,
+    jumble_state: *mut pg_sys::JumbleState,
+) {
+    fn prev(
+        parse_state: PgBox<pg_sys::ParseState>,
+        query: PgBox<pg_sys::Query>,
+        jumbled_query: Option<JumbledQuery>,
+    ) -> HookResult<()> {
+        unsafe {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_post_parse_analyze_hook
+                .as_ref()
+                .unwrap())(
+                parse_state.into_pg(),
+                query.into_pg(),
+                jumbled_query.map_or(std::ptr::null_mut(), |jumbled| jumbled.as_ptr()),
+            )
+        }
+        HookResult::new(())
+    }
+    // Postgres computed a query ID when it's set
+    let jumbled_query = match query.as_ref() {
+        Some(parsed) if parsed.queryId != 0 => {
+            Some(JumbledQuery::from_jumble_state(parsed, jumble_state))
+        }
+        _ => None,
+    };
+    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
+    hook.post_parse_analyze(
+        PgBox::from_pg(parse_state),
+        PgBox::from_pg(query),
+        jumbled_query,
+        prev,
+    );
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_object_access(
+    access: pg_sys::ObjectAccessType,
+    class_id: pg_sys::Oid,
+    object_id: pg_sys::Oid,
+    sub_id: i32,
+    arg: void_mut_ptr,
+) {
+    fn prev(event: ObjectAccessEvent, object: PgObjectAddress) -> HookResult<Result<(), PgError>> {
+        let call = |access: pg_sys::ObjectAccessType, arg: void_mut_ptr| unsafe {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_object_access_hook
+                .as_ref()
+                .unwrap())(
+                access,
+                object.class_id(),
+                object.object_id(),
+                object.sub_id(),
+                arg,
+            )
+        };
+        match event {
+            ObjectAccessEvent::PostCreate { is_internal } => {
+                let mut arg = pg_sys::ObjectAccessPostCreate { is_internal };
+                call(
+                    pg_sys::ObjectAccessType_OAT_POST_CREATE,
+                    &mut arg as *mut _ as void_mut_ptr,
+                )
+            }
+            ObjectAccessEvent::Drop { flags } => {
+                let mut arg = pg_sys::ObjectAccessDrop { dropflags: flags };
+                call(
+                    pg_sys::ObjectAccessType_OAT_DROP,
+                    &mut arg as *mut _ as void_mut_ptr,
+                )
+            }
+            ObjectAccessEvent::PostAlter {
+                auxiliary_id,
+                is_internal,
+            } => {
+                let mut arg = pg_sys::ObjectAccessPostAlter {
+                    auxiliary_id,
+                    is_internal,
+                };
+                call(
+                    pg_sys::ObjectAccessType_OAT_POST_ALTER,
+                    &mut arg as *mut _ as void_mut_ptr,
+                )
+            }
+            ObjectAccessEvent::NamespaceSearch {
+                ereport_on_violation,
+            } => {
+                let mut arg = pg_sys::ObjectAccessNamespaceSearch {
+                    ereport_on_violation,
+                    result: true,
+                };
+                call(
+                    pg_sys::ObjectAccessType_OAT_NAMESPACE_SEARCH,
+                    &mut arg as *mut _ as void_mut_ptr,
+                );
+                if !arg.result {
+                    return HookResult::new(Err(PgError::new(
+                        PgSqlErrorCode::ERRCODE_INSUFFICIENT_PRIVILEGE,
+                        "permission denied for schema",
+                    )));
+                }
+            }
+            ObjectAccessEvent::FunctionExecute => call(
+                pg_sys::ObjectAccessType_OAT_FUNCTION_EXECUTE,
+                std::ptr::null_mut(),
+            ),
+            #[cfg(feature = "pg14")]
+            ObjectAccessEvent::Truncate => {
+                call(pg_sys::ObjectAccessType_OAT_TRUNCATE, std::ptr::null_mut())
+            }
+        }
+        HookResult::new(Ok(()))
+    }
+    let event = match ObjectAccessEvent::from_hook(access, arg) {
+        Some(event) => event,
+        None => {
+            let prev_hook = HOOKS.as_ref().unwrap().prev_object_access_hook.unwrap();
+            return prev_hook(access, class_id, object_id, sub_id, arg);
+        }
+    };
+    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
+    let object = PgObjectAddress::new(class_id, object_id, sub_id);
+    if let Err(error) = hook.object_access(event, object, prev).inner {
+        match event {
+            ObjectAccessEvent::NamespaceSearch {
+                ereport_on_violation: false,
+            } => {
+                (arg as *mut pg_sys::ObjectAccessNamespaceSearch)
+                    .as_mut()
+                    .unwrap()
+                    .result = false
+            }
+            _ => error.report(),
+        }
+    }
+}
+
+static mut IN_EMIT_LOG: bool = false;
+
+#[pg_guard]
+unsafe extern "C" fn pgx_emit_log(error_data: *mut pg_sys::ErrorData) {
+    // a message the hook logs comes back through here, and is emitted without it
+    if !IN_EMIT_LOG {
+        struct Reentry;
+        impl Drop for Reentry {
+            fn drop(&mut self) {
+                unsafe { IN_EMIT_LOG = false }
+            }
+        }
+
+        IN_EMIT_LOG = true;
+        let _reentry = Reentry;
+        let hook = &mut HOOKS.as_mut().unwrap().current_hook;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            hook.emit_log(&mut ErrorReportMut {
+                edata: error_data.as_mut().unwrap(),
+            })
+        }));
+
+        // raising an ERROR from within EmitErrorReport() recurses, so the panic goes no further
+        if let Err(e) = result {
+            let message = if let Some(&s) = e.downcast_ref::<&str>() {
+                s.to_string()
+            } else if let Some(s) = e.downcast_ref::<String>() {
+                s.clone()
+            } else {
+                "Box<Any>".to_string()
+            };
+            eprintln!("emit_log hook panicked: {}", message);
+        }
+    }
+
+    if let Some(prev_hook) = HOOKS.as_ref().unwrap().prev_emit_log_hook {
+        prev_hook(error_data);
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_client_authentication(port: *mut pg_sys::Port, status: i32) {
+    fn prev(port: PgPort) -> HookResult<()> {
+        unsafe {
+            (AUTH_HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_client_authentication_hook
+                .as_ref()
+                .unwrap())(port.port, port.status.into())
+        }
+        HookResult::new(())
+    }
+    let hook = &mut AUTH_HOOKS.as_mut().unwrap().current_hook;
+    hook.client_authentication(
+        PgPort {
+            port: port.as_mut().unwrap(),
+            status: status.into(),
+        },
+        prev,
+    );
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_check_password(
+    username: *const ::std::os::raw::c_char,
+    shadow_pass: *const ::std::os::raw::c_char,
+    password_type: pg_sys::PasswordType,
+    validuntil_time: pg_sys::Datum,
+    validuntil_null: bool,
+) {
+    fn prev(
+        username: &str,
+        shadow_pass: &str,
+        password_type: PasswordType,
+        valid_until: Option<TimestampWithTimeZone>,
+    ) -> HookResult<()> {
+        let username = CString::new(username).expect("username contains a NUL");
+        let shadow_pass = CString::new(shadow_pass).expect("password contains a NUL");
+        let validuntil_time = valid_until.into_datum();
+        unsafe {
+            (AUTH_HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_check_password_hook
+                .as_ref()
+                .unwrap())(
+                username.as_ptr(),
+                shadow_pass.as_ptr(),
+                password_type.into(),
+                validuntil_time.unwrap_or(0),
+                validuntil_time.is_none(),
+            )
+        }
+        HookResult::new(())
+    }
+    let hook = &mut AUTH_HOOKS.as_mut().unwrap().current_hook;
+    hook.check_password(
+        &CStr::from_ptr(username).to_string_lossy(),
+        &CStr::from_ptr(shadow_pass).to_string_lossy(),
+        password_type.into(),
+        TimestampWithTimeZone::from_datum(validuntil_time, validuntil_null, pg_sys::TIMESTAMPTZOID),
+        prev,
+    );
+}
+
 #[pg_guard]
 unsafe extern "C" fn pgx_standard_executor_start_wrapper(
     query_desc: *mut pg_sys::QueryDesc,
@@ -522,6 +1294,50 @@ unsafe extern "C" fn pgx_standard_executor_check_perms_wrapper(
     true
 }
 
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_post_parse_analyze_wrapper(
+    _parse_state: *mut pg_sys::ParseState,
+    _query: *mut pg_sys::Query,
+) {
+}
+
+#[cfg(feature = "pg14")]
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_post_parse_analyze_wrapper(
+    _parse_state: *mut pg_sys::ParseState,
+    _query: *mut pg_sys::Query,
+    _jumble_state: *mut pg_sys::JumbleState,
+) {
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_object_access_wrapper(
+    _access: pg_sys::ObjectAccessType,
+    _class_id: pg_sys::Oid,
+    _object_id: pg_sys::Oid,
+    _sub_id: i32,
+    _arg: void_mut_ptr,
+) {
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_client_authentication_wrapper(
+    _port: *mut pg_sys::Port,
+    _status: i32,
+) {
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_check_password_wrapper(
+    _username: *const ::std::os::raw::c_char,
+    _shadow_pass: *const ::std::os::raw::c_char,
+    _password_type: pg_sys::PasswordType,
+    _validuntil_time: pg_sys::Datum,
+    _validuntil_null: bool,
+) {
+}
+
 #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
 #[pg_guard]
 unsafe extern "C" fn pgx_standard_process_utility_wrapper(
@@ -8,10 +8,11 @@ Use of this source code is governed by the MIT license that can be found in the
 */
 
 //! Helper trait for the `#[derive(PostgresType)]` proc macro for overriding custom Postgres type
-//! input/output functions.
+//! input/output and binary send/receive functions.
 //!
 //! The default implementations use `serde_json` to serialize a custom type to human-readable strings,
-//! and `serde_cbor` to serialize internally as a `varlena *` for storage on disk.
+//! and `serde_cbor` for binary I/O.  How the type is stored on disk is up to its
+//! [`PostgresTypeStorage`](crate::PostgresTypeStorage).
 
 use crate::*;
 
@@ -60,3 +61,68 @@ pub trait JsonInOutFuncs<'de>: serde::de::Deserialize<'de> + serde::ser::Seriali
         serde_json::to_writer(buffer, self).expect("failed to serialize to json")
     }
 }
+
+/// `#[derive(PostgresType)]` types with the `#[pgx(sendrecv = custom)]` attribute need to implement
+/// this trait to provide the binary send/receive functions for that type
+pub trait SendRecvFuncs {
+    /// Convert `Self` into its binary representation by writing to the supplied `StringInfo` buffer
+    fn send(&self, buffer: &mut StringInfo);
+
+    /// Given the binary representation of `Self`, parse it into `Self`.
+    ///
+    /// It is expected that malformed input will raise an `error!()` or `panic!()`
+    fn recv(input: &[u8]) -> Self
+    where
+        Self: Sized;
+}
+
+/// Automatically implemented for `#[derive(Serialize, Deserialize, PostgresType)]` types that do
+/// **not** also have the `#[pgx(sendrecv = custom)]` attribute
+pub trait CborSendRecvFuncs<'de>: serde::de::Deserialize<'de> + serde::ser::Serialize {
+    /// Uses `serde_cbor` to serialize `Self`
+    fn send(&self, buffer: &mut StringInfo) {
+        serde_cbor::to_writer(buffer, self).expect("failed to serialize to cbor")
+    }
+
+    /// Uses `serde_cbor` to deserialize the input, which is assumed to be CBOR
+    fn recv(input: &'de [u8]) -> Self {
+        serde_cbor::from_slice(input).expect("failed to deserialize cbor")
+    }
+}
+
+/// Consume the unread bytes of the message buffer a type's `_recv` function is given
+///
+/// ## Safety
+///
+/// `buffer` must be the `StringInfo` Postgres passed to the `_recv` function, and the returned
+/// slice must not outlive it
+#[doc(hidden)]
+pub unsafe fn recv_buffer_bytes<'a>(buffer: pg_sys::StringInfo) -> &'a [u8] {
+    let buffer = &mut *buffer;
+    let start = buffer.cursor as usize;
+    let len = buffer.len as usize - start;
+    buffer.cursor = buffer.len;
+    std::slice::from_raw_parts((buffer.data as *const u8).add(start), len)
+}
+
+/// `#[derive(PostgresType)]` types with the `#[pgx(typmod)]` attribute need to implement this
+/// trait to accept a type modifier, as in `my_type(384)`
+pub trait TypmodInOutFuncs {
+    /// Given the modifiers of a type declaration, such as `[384]` for `my_type(384)`, encode them
+    /// into a non-negative `i32` typmod.
+    ///
+    /// It is expected that invalid modifiers will raise an `error!()` or `panic!()`
+    fn typmod_in(modifiers: &[i32]) -> i32;
+
+    /// Convert `typmod` back into its declaration form, such as `(384)`, by writing to the supplied
+    /// `StringInfo` buffer
+    fn typmod_out(typmod: i32, buffer: &mut StringInfo);
+
+    /// Make `self` conform to `typmod`, such as when it's stored in a `my_type(384)` column or
+    /// cast to `my_type(384)`.  `explicit` is true for explicit casts.
+    ///
+    /// It is expected that a value that can't conform will raise an `error!()` or `panic!()`
+    fn apply_typmod(self, typmod: i32, explicit: bool) -> Self
+    where
+        Self: Sized;
+}
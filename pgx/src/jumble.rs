@@ -0,0 +1,719 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Query jumbling, which fingerprints a `Query` so that statements differing only by their
+//! constants share a query ID, and normalizes their text with those constants replaced by `$n`.
+//!
+//! Postgres 14 jumbles queries itself when `compute_query_id` is enabled, and this reuses its
+//! `JumbleState`.  Earlier versions are jumbled the way their `pg_stat_statements` does it.
+//!
+//! [`jumble_query`] leaves the `Query`'s `queryId` alone, as `pg_stat_statements` expects to set
+//! it itself before Postgres 14.  An extension that wants to set it should do so after calling
+//! the previous hook, and only when it's still zero.
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//! use std::ffi::CStr;
+//!
+//! struct Statements;
+//! impl PgHooks for Statements {
+//!     fn post_parse_analyze(
+//!         &mut self,
+//!         parse_state: PgBox<pg_sys::ParseState>,
+//!         mut query: PgBox<pg_sys::Query>,
+//!         jumbled_query: Option<JumbledQuery>,
+//!         prev_hook: fn(
+//!             PgBox<pg_sys::ParseState>,
+//!             PgBox<pg_sys::Query>,
+//!             Option<JumbledQuery>,
+//!         ) -> HookResult<()>,
+//!     ) -> HookResult<()> {
+//!         let source_text = unsafe { CStr::from_ptr(parse_state.p_sourcetext) };
+//!         let jumbled = jumbled_query
+//!             .clone()
+//!             .or_else(|| jumble_query(&mut query, source_text));
+//!         if let Some(jumbled) = jumbled {
+//!             info!("{}: {}", jumbled.query_id(), jumbled.normalize(source_text));
+//!         }
+//!         prev_hook(parse_state, query, jumbled_query)
+//!     }
+//! }
+//! ```
+use crate::pg_sys;
+use std::ffi::CStr;
+
+/// The query ID of a `Query`, and where the constants it was jumbled without are in its text
+#[derive(Debug, Clone)]
+pub struct JumbledQuery {
+    query_id: u64,
+    stmt_location: i32,
+    stmt_len: i32,
+    constant_locations: Vec<i32>,
+    highest_extern_param_id: i32,
+    #[cfg(feature = "pg14")]
+    jumble_state: *mut pg_sys::JumbleState,
+}
+
+impl JumbledQuery {
+    /// The fingerprint of the query, which is never zero
+    pub fn query_id(&self) -> u64 {
+        self.query_id
+    }
+
+    /// The byte offsets of the query's constants in the source text, in order
+    pub fn constant_locations(&self) -> &[i32] {
+        &self.constant_locations
+    }
+
+    /// The highest `$n` parameter the query refers to, or zero
+    pub fn highest_extern_param_id(&self) -> i32 {
+        self.highest_extern_param_id
+    }
+
+    /// The `JumbleState` Postgres jumbled the query into, or null for a utility statement
+    #[cfg(feature = "pg14")]
+    pub fn as_ptr(&self) -> *mut pg_sys::JumbleState {
+        self.jumble_state
+    }
+
+    /// The text of the query's statement within `source_text`, with its constants replaced by
+    /// `$n` parameters numbered after the query's own
+    pub fn normalize(&self, source_text: &CStr) -> String {
+        let source = source_text.to_bytes();
+        let (start, end) = statement_bounds(source, self.stmt_location, self.stmt_len);
+        let statement = &source[start..end];
+        let constants = unsafe { constant_lengths(source_text, start, &self.constant_locations) };
+
+        let mut normalized = Vec::with_capacity(statement.len() + constants.len() * 4);
+        let mut copied = 0;
+        let mut param_id = self.highest_extern_param_id;
+        for (location, length) in constants {
+            if location < copied || location + length > statement.len() {
+                continue;
+            }
+            param_id += 1;
+            normalized.extend_from_slice(&statement[copied..location]);
+            normalized.extend_from_slice(format!("${}", param_id).as_bytes());
+            copied = location + length;
+        }
+        normalized.extend_from_slice(&statement[copied..]);
+        String::from_utf8_lossy(&normalized).into_owned()
+    }
+
+    fn new(query: &pg_sys::Query, query_id: u64, mut constant_locations: Vec<i32>) -> Self {
+        constant_locations.sort_unstable();
+        constant_locations.dedup();
+        JumbledQuery {
+            query_id,
+            stmt_location: query.stmt_location,
+            stmt_len: query.stmt_len,
+            constant_locations,
+            highest_extern_param_id: 0,
+            #[cfg(feature = "pg14")]
+            jumble_state: std::ptr::null_mut(),
+        }
+    }
+
+    /// The jumble Postgres made of `query`, which has its `queryId` set
+    #[cfg(feature = "pg14")]
+    pub(crate) unsafe fn from_jumble_state(
+        query: &pg_sys::Query,
+        jumble_state: *mut pg_sys::JumbleState,
+    ) -> Self {
+        match jumble_state.as_ref() {
+            Some(state) => {
+                let locations: &[pg_sys::LocationLen] = if state.clocations.is_null() {
+                    &[]
+                } else {
+                    std::slice::from_raw_parts(state.clocations, state.clocations_count as usize)
+                };
+                let mut jumbled = JumbledQuery::new(
+                    query,
+                    query.queryId,
+                    locations.iter().map(|location| location.location).collect(),
+                );
+                jumbled.highest_extern_param_id = state.highest_extern_param_id;
+                jumbled.jumble_state = jumble_state;
+                jumbled
+            }
+            None => JumbledQuery::new(query, query.queryId, Vec::new()),
+        }
+    }
+}
+
+/// Compute the query ID of `query`, which was parsed from `source_text`.  Its `queryId` is left
+/// as it was.
+///
+/// Utility statements are identified by their text, as they have no constants to jumble.  On
+/// Postgres 14, this is `None` unless `compute_query_id` is enabled.
+#[cfg(feature = "pg14")]
+pub fn jumble_query(query: &mut pg_sys::Query, source_text: &CStr) -> Option<JumbledQuery> {
+    // IsQueryIdEnabled() is a static inline function
+    let enabled = unsafe {
+        match pg_sys::compute_query_id as pg_sys::ComputeQueryIdType {
+            pg_sys::ComputeQueryIdType_COMPUTE_QUERY_ID_OFF => false,
+            pg_sys::ComputeQueryIdType_COMPUTE_QUERY_ID_AUTO => pg_sys::query_id_enabled,
+            _ => true,
+        }
+    };
+    if !enabled {
+        return None;
+    }
+
+    unsafe {
+        // `JumbleQuery()` sets the query's `queryId`, which is up to the caller
+        let query_id = query.queryId;
+        let jumble_state = pg_sys::JumbleQuery(query, source_text.as_ptr());
+        let jumbled = JumbledQuery::from_jumble_state(query, jumble_state);
+        query.queryId = query_id;
+        Some(jumbled)
+    }
+}
+
+/// Compute the query ID of `query`, which was parsed from `source_text`.  Its `queryId` is left
+/// as it was.
+///
+/// Utility statements are identified by their text, as they have no constants to jumble.  On
+/// Postgres 14, this is `None` unless `compute_query_id` is enabled.
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+pub fn jumble_query(query: &mut pg_sys::Query, source_text: &CStr) -> Option<JumbledQuery> {
+    Some(if query.utilityStmt.is_null() {
+        let mut jumbler = Jumbler::default();
+        unsafe { jumbler.query(query) };
+        let mut jumbled =
+            JumbledQuery::new(query, query_id(&jumbler.jumble), jumbler.constant_locations);
+        jumbled.highest_extern_param_id = jumbler.highest_extern_param_id;
+        jumbled
+    } else {
+        let source = source_text.to_bytes();
+        let (start, end) = statement_bounds(source, query.stmt_location, query.stmt_len);
+        JumbledQuery::new(query, query_id(&source[start..end]), Vec::new())
+    })
+}
+
+/// Have Postgres 14 compute query IDs when `compute_query_id` is `auto`, as `pg_stat_statements`
+/// does.  Call it from `_PG_init()`.  Earlier versions don't compute query IDs themselves.
+pub fn enable_query_id() {
+    #[cfg(feature = "pg14")]
+    unsafe {
+        pg_sys::EnableQueryId()
+    }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+fn query_id(jumble: &[u8]) -> u64 {
+    // zero means "no query ID"
+    match crate::misc::pgx_seahash(&jumble) {
+        0 => 1,
+        query_id => query_id,
+    }
+}
+
+/// The start and end of a statement in its source text, without surrounding whitespace, as in
+/// `CleanQuerytext()`
+fn statement_bounds(source: &[u8], stmt_location: i32, stmt_len: i32) -> (usize, usize) {
+    let is_space = |byte: &u8| matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | b'\x0c');
+
+    let start = (stmt_location.max(0) as usize).min(source.len());
+    let end = if stmt_location >= 0 && stmt_len > 0 {
+        (start + stmt_len as usize).min(source.len())
+    } else {
+        source.len()
+    };
+    let statement = &source[start..end];
+    let leading = statement.iter().take_while(|byte| is_space(byte)).count();
+    let trailing = statement[leading..]
+        .iter()
+        .rev()
+        .take_while(|byte| is_space(byte))
+        .count();
+    (start + leading, end - trailing)
+}
+
+/// The location of each constant relative to `statement_start`, with the length of its token,
+/// found by scanning the source text the way the parser did
+unsafe fn constant_lengths(
+    source_text: &CStr,
+    statement_start: usize,
+    locations: &[i32],
+) -> Vec<(usize, usize)> {
+    let mut lengths = Vec::with_capacity(locations.len());
+    if locations.is_empty() {
+        return lengths;
+    }
+
+    let statement = source_text.as_ptr().add(statement_start);
+    let mut extra: pg_sys::core_yy_extra_type = std::mem::zeroed();
+    #[cfg(any(feature = "pg10", feature = "pg11"))]
+    let scanner = pg_sys::scanner_init(
+        statement,
+        &mut extra,
+        pg_sys::ScanKeywords.as_ptr(),
+        pg_sys::NumScanKeywords,
+    );
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    let scanner = pg_sys::scanner_init(
+        statement,
+        &mut extra,
+        &pg_sys::ScanKeywords,
+        pg_sys::ScanKeywordTokens.as_ptr(),
+    );
+    // the parser already warned about them
+    extra.escape_string_warning = false;
+
+    let mut value: pg_sys::core_YYSTYPE = std::mem::zeroed();
+    let mut token_location = 0;
+    'constants: for location in locations {
+        let location = match usize::try_from(*location as isize - statement_start as isize) {
+            Ok(location) => location,
+            Err(_) => continue,
+        };
+        loop {
+            if pg_sys::core_yylex(&mut value, &mut token_location, scanner) == 0 {
+                break 'constants;
+            }
+            if token_location as usize >= location {
+                // a negative number is a "-" token before the number
+                if *statement.add(location) == b'-' as std::os::raw::c_char
+                    && pg_sys::core_yylex(&mut value, &mut token_location, scanner) == 0
+                {
+                    break 'constants;
+                }
+                // the scanner puts a NUL after the current token
+                let length = CStr::from_ptr(extra.scanbuf.add(location)).to_bytes().len();
+                lengths.push((location, length));
+                break;
+            }
+        }
+    }
+    pg_sys::scanner_finish(scanner);
+    lengths
+}
+
+/// A port of the query jumbling in `pg_stat_statements`
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+#[derive(Default)]
+struct Jumbler {
+    jumble: Vec<u8>,
+    constant_locations: Vec<i32>,
+    highest_extern_param_id: i32,
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+impl Jumbler {
+    fn append<T: Copy>(&mut self, value: T) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(&value as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.jumble.extend_from_slice(bytes);
+    }
+
+    unsafe fn append_string(&mut self, string: *const std::os::raw::c_char) {
+        if !string.is_null() {
+            self.jumble
+                .extend_from_slice(CStr::from_ptr(string).to_bytes_with_nul());
+        }
+    }
+
+    unsafe fn query(&mut self, query: *mut pg_sys::Query) {
+        let query = &*query;
+        self.append(query.commandType);
+        // resultRelation is usually predictable from commandType
+        self.expr(query.cteList.cast());
+        self.range_table(query.rtable);
+        self.expr(query.jointree.cast());
+        self.expr(query.targetList.cast());
+        self.expr(query.onConflict.cast());
+        self.expr(query.returningList.cast());
+        self.expr(query.groupClause.cast());
+        self.expr(query.groupingSets.cast());
+        self.expr(query.havingQual);
+        self.expr(query.windowClause.cast());
+        self.expr(query.distinctClause.cast());
+        self.expr(query.sortClause.cast());
+        self.expr(query.limitOffset);
+        self.expr(query.limitCount);
+        #[cfg(feature = "pg13")]
+        self.append(query.limitOption);
+        self.row_marks(query.rowMarks);
+        self.expr(query.setOperations);
+    }
+
+    unsafe fn range_table(&mut self, range_table: *mut pg_sys::List) {
+        for rte in crate::PgList::<pg_sys::RangeTblEntry>::from_pg(range_table).iter_ptr() {
+            let rte = &*rte;
+            self.append(rte.rtekind);
+            match rte.rtekind {
+                pg_sys::RTEKind_RTE_RELATION => {
+                    self.append(rte.relid);
+                    self.expr(rte.tablesample.cast());
+                }
+                pg_sys::RTEKind_RTE_SUBQUERY => self.query(rte.subquery),
+                pg_sys::RTEKind_RTE_JOIN => self.append(rte.jointype),
+                pg_sys::RTEKind_RTE_FUNCTION => self.expr(rte.functions.cast()),
+                pg_sys::RTEKind_RTE_TABLEFUNC => self.expr(rte.tablefunc.cast()),
+                pg_sys::RTEKind_RTE_VALUES => self.expr(rte.values_lists.cast()),
+                pg_sys::RTEKind_RTE_CTE => {
+                    // the name is all that identifies the WITH item
+                    self.append_string(rte.ctename);
+                    self.append(rte.ctelevelsup);
+                }
+                pg_sys::RTEKind_RTE_NAMEDTUPLESTORE => self.append_string(rte.enrname),
+                #[cfg(any(feature = "pg12", feature = "pg13"))]
+                pg_sys::RTEKind_RTE_RESULT => {}
+                kind => crate::error!("unrecognized RTE kind: {}", kind),
+            }
+        }
+    }
+
+    unsafe fn row_marks(&mut self, row_marks: *mut pg_sys::List) {
+        for row_mark in crate::PgList::<pg_sys::RowMarkClause>::from_pg(row_marks).iter_ptr() {
+            let row_mark = &*row_mark;
+            // ones the planner added aren't in the query text
+            if !row_mark.pushedDown {
+                self.append(row_mark.rti);
+                self.append(row_mark.strength);
+                self.append(row_mark.waitPolicy);
+            }
+        }
+    }
+
+    unsafe fn expr(&mut self, node: *mut pg_sys::Node) {
+        if node.is_null() {
+            return;
+        }
+        pg_sys::check_stack_depth();
+
+        let tag = (*node).type_;
+        self.append(tag);
+        match tag {
+            pg_sys::NodeTag_T_Var => {
+                let var = &*(node as *mut pg_sys::Var);
+                self.append(var.varno);
+                self.append(var.varattno);
+                self.append(var.varlevelsup);
+            }
+            pg_sys::NodeTag_T_Const => {
+                let constant = &*(node as *mut pg_sys::Const);
+                self.append(constant.consttype);
+                // the value is left out, and replaced in the normalized text
+                if constant.location >= 0 {
+                    self.constant_locations.push(constant.location);
+                }
+            }
+            pg_sys::NodeTag_T_Param => {
+                let param = &*(node as *mut pg_sys::Param);
+                self.append(param.paramkind);
+                self.append(param.paramid);
+                self.append(param.paramtype);
+                if param.paramkind == pg_sys::ParamKind_PARAM_EXTERN
+                    && param.paramid > self.highest_extern_param_id
+                {
+                    self.highest_extern_param_id = param.paramid;
+                }
+            }
+            pg_sys::NodeTag_T_Aggref => {
+                let aggref = &*(node as *mut pg_sys::Aggref);
+                self.append(aggref.aggfnoid);
+                self.expr(aggref.aggdirectargs.cast());
+                self.expr(aggref.args.cast());
+                self.expr(aggref.aggorder.cast());
+                self.expr(aggref.aggdistinct.cast());
+                self.expr(aggref.aggfilter.cast());
+            }
+            pg_sys::NodeTag_T_GroupingFunc => {
+                let grouping = &*(node as *mut pg_sys::GroupingFunc);
+                self.expr(grouping.refs.cast());
+            }
+            pg_sys::NodeTag_T_WindowFunc => {
+                let window_func = &*(node as *mut pg_sys::WindowFunc);
+                self.append(window_func.winfnoid);
+                self.append(window_func.winref);
+                self.expr(window_func.args.cast());
+                self.expr(window_func.aggfilter.cast());
+            }
+            #[cfg(any(feature = "pg10", feature = "pg11"))]
+            pg_sys::NodeTag_T_ArrayRef => {
+                let array_ref = &*(node as *mut pg_sys::ArrayRef);
+                self.expr(array_ref.refupperindexpr.cast());
+                self.expr(array_ref.reflowerindexpr.cast());
+                self.expr(array_ref.refexpr.cast());
+                self.expr(array_ref.refassgnexpr.cast());
+            }
+            #[cfg(any(feature = "pg12", feature = "pg13"))]
+            pg_sys::NodeTag_T_SubscriptingRef => {
+                let subscripting = &*(node as *mut pg_sys::SubscriptingRef);
+                self.expr(subscripting.refupperindexpr.cast());
+                self.expr(subscripting.reflowerindexpr.cast());
+                self.expr(subscripting.refexpr.cast());
+                self.expr(subscripting.refassgnexpr.cast());
+            }
+            pg_sys::NodeTag_T_FuncExpr => {
+                let func = &*(node as *mut pg_sys::FuncExpr);
+                self.append(func.funcid);
+                self.expr(func.args.cast());
+            }
+            pg_sys::NodeTag_T_NamedArgExpr => {
+                let named_arg = &*(node as *mut pg_sys::NamedArgExpr);
+                self.append(named_arg.argnumber);
+                self.expr(named_arg.arg.cast());
+            }
+            pg_sys::NodeTag_T_OpExpr
+            | pg_sys::NodeTag_T_DistinctExpr
+            | pg_sys::NodeTag_T_NullIfExpr => {
+                let op = &*(node as *mut pg_sys::OpExpr);
+                self.append(op.opno);
+                self.expr(op.args.cast());
+            }
+            pg_sys::NodeTag_T_ScalarArrayOpExpr => {
+                let op = &*(node as *mut pg_sys::ScalarArrayOpExpr);
+                self.append(op.opno);
+                self.append(op.useOr);
+                self.expr(op.args.cast());
+            }
+            pg_sys::NodeTag_T_BoolExpr => {
+                let bool_expr = &*(node as *mut pg_sys::BoolExpr);
+                self.append(bool_expr.boolop);
+                self.expr(bool_expr.args.cast());
+            }
+            pg_sys::NodeTag_T_SubLink => {
+                let sublink = &*(node as *mut pg_sys::SubLink);
+                self.append(sublink.subLinkType);
+                self.append(sublink.subLinkId);
+                self.expr(sublink.testexpr);
+                self.query(sublink.subselect.cast());
+            }
+            pg_sys::NodeTag_T_FieldSelect => {
+                let field_select = &*(node as *mut pg_sys::FieldSelect);
+                self.append(field_select.fieldnum);
+                self.expr(field_select.arg.cast());
+            }
+            pg_sys::NodeTag_T_FieldStore => {
+                let field_store = &*(node as *mut pg_sys::FieldStore);
+                self.expr(field_store.arg.cast());
+                self.expr(field_store.newvals.cast());
+            }
+            pg_sys::NodeTag_T_RelabelType => {
+                let relabel = &*(node as *mut pg_sys::RelabelType);
+                self.append(relabel.resulttype);
+                self.expr(relabel.arg.cast());
+            }
+            pg_sys::NodeTag_T_CoerceViaIO => {
+                let coerce = &*(node as *mut pg_sys::CoerceViaIO);
+                self.append(coerce.resulttype);
+                self.expr(coerce.arg.cast());
+            }
+            pg_sys::NodeTag_T_ArrayCoerceExpr => {
+                let coerce = &*(node as *mut pg_sys::ArrayCoerceExpr);
+                self.append(coerce.resulttype);
+                self.expr(coerce.arg.cast());
+                #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13"))]
+                self.expr(coerce.elemexpr.cast());
+            }
+            pg_sys::NodeTag_T_ConvertRowtypeExpr => {
+                let convert = &*(node as *mut pg_sys::ConvertRowtypeExpr);
+                self.append(convert.resulttype);
+                self.expr(convert.arg.cast());
+            }
+            pg_sys::NodeTag_T_CollateExpr => {
+                let collate = &*(node as *mut pg_sys::CollateExpr);
+                self.append(collate.collOid);
+                self.expr(collate.arg.cast());
+            }
+            pg_sys::NodeTag_T_CaseExpr => {
+                let case = &*(node as *mut pg_sys::CaseExpr);
+                self.expr(case.arg.cast());
+                self.expr(case.args.cast());
+                self.expr(case.defresult.cast());
+            }
+            pg_sys::NodeTag_T_CaseWhen => {
+                let when = &*(node as *mut pg_sys::CaseWhen);
+                self.expr(when.expr.cast());
+                self.expr(when.result.cast());
+            }
+            pg_sys::NodeTag_T_CaseTestExpr => {
+                let case_test = &*(node as *mut pg_sys::CaseTestExpr);
+                self.append(case_test.typeId);
+            }
+            pg_sys::NodeTag_T_ArrayExpr => {
+                let array = &*(node as *mut pg_sys::ArrayExpr);
+                self.expr(array.elements.cast());
+            }
+            pg_sys::NodeTag_T_RowExpr => {
+                let row = &*(node as *mut pg_sys::RowExpr);
+                self.expr(row.args.cast());
+            }
+            pg_sys::NodeTag_T_RowCompareExpr => {
+                let row_compare = &*(node as *mut pg_sys::RowCompareExpr);
+                self.append(row_compare.rctype);
+                self.expr(row_compare.largs.cast());
+                self.expr(row_compare.rargs.cast());
+            }
+            pg_sys::NodeTag_T_CoalesceExpr => {
+                let coalesce = &*(node as *mut pg_sys::CoalesceExpr);
+                self.expr(coalesce.args.cast());
+            }
+            pg_sys::NodeTag_T_MinMaxExpr => {
+                let min_max = &*(node as *mut pg_sys::MinMaxExpr);
+                self.append(min_max.op);
+                self.expr(min_max.args.cast());
+            }
+            pg_sys::NodeTag_T_SQLValueFunction => {
+                let value_function = &*(node as *mut pg_sys::SQLValueFunction);
+                self.append(value_function.op);
+                // type is fully determined by op
+                self.append(value_function.typmod);
+            }
+            pg_sys::NodeTag_T_XmlExpr => {
+                let xml = &*(node as *mut pg_sys::XmlExpr);
+                self.append(xml.op);
+                self.expr(xml.named_args.cast());
+                self.expr(xml.args.cast());
+            }
+            pg_sys::NodeTag_T_NullTest => {
+                let null_test = &*(node as *mut pg_sys::NullTest);
+                self.append(null_test.nulltesttype);
+                self.expr(null_test.arg.cast());
+            }
+            pg_sys::NodeTag_T_BooleanTest => {
+                let boolean_test = &*(node as *mut pg_sys::BooleanTest);
+                self.append(boolean_test.booltesttype);
+                self.expr(boolean_test.arg.cast());
+            }
+            pg_sys::NodeTag_T_CoerceToDomain => {
+                let coerce = &*(node as *mut pg_sys::CoerceToDomain);
+                self.append(coerce.resulttype);
+                self.expr(coerce.arg.cast());
+            }
+            pg_sys::NodeTag_T_CoerceToDomainValue => {
+                let value = &*(node as *mut pg_sys::CoerceToDomainValue);
+                self.append(value.typeId);
+            }
+            pg_sys::NodeTag_T_SetToDefault => {
+                let default = &*(node as *mut pg_sys::SetToDefault);
+                self.append(default.typeId);
+            }
+            pg_sys::NodeTag_T_CurrentOfExpr => {
+                let current_of = &*(node as *mut pg_sys::CurrentOfExpr);
+                self.append(current_of.cvarno);
+                self.append_string(current_of.cursor_name);
+                self.append(current_of.cursor_param);
+            }
+            pg_sys::NodeTag_T_NextValueExpr => {
+                let next_value = &*(node as *mut pg_sys::NextValueExpr);
+                self.append(next_value.seqid);
+                self.append(next_value.typeId);
+            }
+            pg_sys::NodeTag_T_InferenceElem => {
+                let inference = &*(node as *mut pg_sys::InferenceElem);
+                self.append(inference.infercollid);
+                self.append(inference.inferopclass);
+                self.expr(inference.expr);
+            }
+            pg_sys::NodeTag_T_TargetEntry => {
+                let target = &*(node as *mut pg_sys::TargetEntry);
+                self.append(target.resno);
+                self.append(target.ressortgroupref);
+                self.expr(target.expr.cast());
+            }
+            pg_sys::NodeTag_T_RangeTblRef => {
+                let range_table_ref = &*(node as *mut pg_sys::RangeTblRef);
+                self.append(range_table_ref.rtindex);
+            }
+            pg_sys::NodeTag_T_JoinExpr => {
+                let join = &*(node as *mut pg_sys::JoinExpr);
+                self.append(join.jointype);
+                self.append(join.isNatural);
+                self.append(join.rtindex);
+                self.expr(join.larg);
+                self.expr(join.rarg);
+                self.expr(join.quals);
+            }
+            pg_sys::NodeTag_T_FromExpr => {
+                let from = &*(node as *mut pg_sys::FromExpr);
+                self.expr(from.fromlist.cast());
+                self.expr(from.quals);
+            }
+            pg_sys::NodeTag_T_OnConflictExpr => {
+                let on_conflict = &*(node as *mut pg_sys::OnConflictExpr);
+                self.append(on_conflict.action);
+                self.expr(on_conflict.arbiterElems.cast());
+                self.expr(on_conflict.arbiterWhere);
+                self.expr(on_conflict.onConflictSet.cast());
+                self.expr(on_conflict.onConflictWhere);
+                self.append(on_conflict.constraint);
+                self.append(on_conflict.exclRelIndex);
+                self.expr(on_conflict.exclRelTlist.cast());
+            }
+            pg_sys::NodeTag_T_List => {
+                for item in crate::PgList::<pg_sys::Node>::from_pg(node.cast()).iter_ptr() {
+                    self.expr(item);
+                }
+            }
+            pg_sys::NodeTag_T_IntList => {
+                for item in crate::PgList::<i32>::from_pg(node.cast()).iter_int() {
+                    self.append(item);
+                }
+            }
+            pg_sys::NodeTag_T_SortGroupClause => {
+                let sort_group = &*(node as *mut pg_sys::SortGroupClause);
+                self.append(sort_group.tleSortGroupRef);
+                self.append(sort_group.eqop);
+                self.append(sort_group.sortop);
+                self.append(sort_group.nulls_first);
+            }
+            pg_sys::NodeTag_T_GroupingSet => {
+                let grouping_set = &*(node as *mut pg_sys::GroupingSet);
+                self.append(grouping_set.kind);
+                self.expr(grouping_set.content.cast());
+            }
+            pg_sys::NodeTag_T_WindowClause => {
+                let window = &*(node as *mut pg_sys::WindowClause);
+                self.append(window.winref);
+                self.append(window.frameOptions);
+                self.expr(window.partitionClause.cast());
+                self.expr(window.orderClause.cast());
+                self.expr(window.startOffset);
+                self.expr(window.endOffset);
+            }
+            pg_sys::NodeTag_T_CommonTableExpr => {
+                let cte = &*(node as *mut pg_sys::CommonTableExpr);
+                // range table entries refer to it by name
+                self.append_string(cte.ctename);
+                self.query(cte.ctequery.cast());
+            }
+            pg_sys::NodeTag_T_SetOperationStmt => {
+                let set_operation = &*(node as *mut pg_sys::SetOperationStmt);
+                self.append(set_operation.op);
+                self.append(set_operation.all);
+                self.expr(set_operation.larg);
+                self.expr(set_operation.rarg);
+            }
+            pg_sys::NodeTag_T_RangeTblFunction => {
+                let function = &*(node as *mut pg_sys::RangeTblFunction);
+                self.expr(function.funcexpr);
+            }
+            pg_sys::NodeTag_T_TableFunc => {
+                let table_func = &*(node as *mut pg_sys::TableFunc);
+                self.expr(table_func.ns_uris.cast());
+                self.expr(table_func.docexpr);
+                self.expr(table_func.rowexpr);
+                self.expr(table_func.colexprs.cast());
+            }
+            pg_sys::NodeTag_T_TableSampleClause => {
+                let table_sample = &*(node as *mut pg_sys::TableSampleClause);
+                self.append(table_sample.tsmhandler);
+                self.expr(table_sample.args.cast());
+                self.expr(table_sample.repeatable.cast());
+            }
+            // only its tag is in the jumble
+            tag => crate::warning!("unrecognized node type: {}", tag),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Provides a safe wrapper around Postgres' large object ("inversion") API
+use crate::{
+    ereport, pg_sys, register_xact_callback, FromDatum, IntoDatum, PgLogLevel, PgMemoryContexts,
+    PgSqlErrorCode, PgXactCallbackEvent, XactCallbackReceipt,
+};
+use std::cell::Cell;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::panic::AssertUnwindSafe;
+use std::rc::Rc;
+
+/// The `oid` that identifies a large object in `pg_largeobject_metadata`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LargeObjectId(pub pg_sys::Oid);
+
+impl FromDatum for LargeObjectId {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self> {
+        if is_null {
+            None
+        } else {
+            Some(LargeObjectId(datum as pg_sys::Oid))
+        }
+    }
+}
+
+impl IntoDatum for LargeObjectId {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.0 as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::OIDOID
+    }
+}
+
+/// How a [`LargeObject`] is opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LargeObjectMode {
+    /// Read the large object as of the snapshot current when it was opened
+    Read,
+
+    /// Read and write the current contents of the large object
+    Write,
+}
+
+/// An open large object, which can be read, written and seeked with the `std::io` traits.
+///
+/// The handle is closed when it's dropped or, at the latest, when the transaction that opened it
+/// ends.  Using a handle after its transaction has ended raises an ERROR.
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use std::io::{Seek, Write};
+///
+/// #[pg_extern]
+/// fn lo_append(id: LargeObjectId, suffix: &str) -> i64 {
+///     let mut lo = LargeObject::open(id, LargeObjectMode::Write);
+///     lo.seek(std::io::SeekFrom::End(0)).unwrap();
+///     lo.write_all(suffix.as_bytes()).unwrap();
+///     lo.stream_position().unwrap() as i64
+/// }
+/// ```
+pub struct LargeObject {
+    id: LargeObjectId,
+    desc: Rc<Cell<*mut pg_sys::LargeObjectDesc>>,
+    receipts: Vec<XactCallbackReceipt>,
+}
+
+impl LargeObject {
+    /// Create a new, empty, large object and return its id
+    pub fn create() -> LargeObjectId {
+        LargeObjectId(unsafe { pg_sys::inv_create(pg_sys::InvalidOid) })
+    }
+
+    /// Delete the large object `id`.
+    ///
+    /// Like `lo_unlink()`, raises an ERROR if the large object doesn't exist or if the current
+    /// user doesn't own it (unless `lo_compat_privileges` is on).
+    pub fn unlink(id: LargeObjectId) {
+        unsafe {
+            if !pg_sys::LargeObjectExists(id.0) {
+                ereport(
+                    PgLogLevel::ERROR,
+                    PgSqlErrorCode::ERRCODE_UNDEFINED_OBJECT,
+                    &format!("large object {} does not exist", id.0),
+                    file!(),
+                    line!(),
+                    column!(),
+                );
+            }
+
+            if !pg_sys::lo_compat_privileges
+                && !pg_sys::pg_largeobject_ownercheck(id.0, pg_sys::GetUserId())
+            {
+                ereport(
+                    PgLogLevel::ERROR,
+                    PgSqlErrorCode::ERRCODE_INSUFFICIENT_PRIVILEGE,
+                    &format!("must be owner of large object {}", id.0),
+                    file!(),
+                    line!(),
+                    column!(),
+                );
+            }
+
+            pg_sys::inv_drop(id.0);
+        }
+    }
+
+    /// Open the large object `id`.
+    ///
+    /// Raises an ERROR if the large object doesn't exist or if the current user isn't allowed to
+    /// access it in `mode`.
+    pub fn open(id: LargeObjectId, mode: LargeObjectMode) -> Self {
+        let flags = match mode {
+            LargeObjectMode::Read => pg_sys::INV_READ,
+            LargeObjectMode::Write => pg_sys::INV_READ | pg_sys::INV_WRITE,
+        };
+
+        // the descriptor is allocated in TopTransactionContext so it survives until we close it
+        // at the end of the transaction, even if it was opened in a subtransaction
+        let desc = unsafe {
+            pg_sys::inv_open(
+                id.0,
+                flags as i32,
+                PgMemoryContexts::TopTransactionContext.value(),
+            )
+        };
+        let desc = Rc::new(Cell::new(desc));
+
+        // a read-only descriptor holds a snapshot registered with the transaction, so it must be
+        // closed before the transaction commits.  An aborting transaction releases the snapshot
+        // and the descriptor's memory itself, so then we only forget about it
+        let at_commit = AssertUnwindSafe(Rc::clone(&desc));
+        let at_abort = AssertUnwindSafe(Rc::clone(&desc));
+        let receipts = vec![
+            register_xact_callback(PgXactCallbackEvent::PreCommit, move || close(&at_commit)),
+            register_xact_callback(PgXactCallbackEvent::Abort, move || {
+                at_abort.set(std::ptr::null_mut())
+            }),
+        ];
+
+        LargeObject { id, desc, receipts }
+    }
+
+    /// The id of this large object
+    pub fn id(&self) -> LargeObjectId {
+        self.id
+    }
+
+    /// Truncate (or zero-extend) the large object to `len` bytes.  This doesn't move the current
+    /// position.
+    ///
+    /// Raises an ERROR if the large object wasn't opened with [`LargeObjectMode::Write`].
+    pub fn truncate(&mut self, len: u64) {
+        let len = i64::try_from(len).unwrap_or_else(|_| {
+            crate::error!(
+                "cannot truncate large object {} to {} bytes",
+                self.id.0,
+                len
+            )
+        });
+        unsafe { pg_sys::inv_truncate(self.desc(), len) }
+    }
+
+    fn desc(&self) -> *mut pg_sys::LargeObjectDesc {
+        let desc = self.desc.get();
+        if desc.is_null() {
+            crate::error!(
+                "large object {} was closed at the end of the transaction that opened it",
+                self.id.0
+            );
+        }
+        desc
+    }
+}
+
+fn close(desc: &Cell<*mut pg_sys::LargeObjectDesc>) {
+    let desc = desc.replace(std::ptr::null_mut());
+    if !desc.is_null() {
+        unsafe { pg_sys::inv_close(desc) }
+    }
+}
+
+impl Drop for LargeObject {
+    fn drop(&mut self) {
+        // while unwinding from an ERROR the transaction is about to abort, and our Abort callback
+        // takes care of the descriptor
+        if std::thread::panicking() {
+            return;
+        }
+
+        close(&self.desc);
+        for receipt in self.receipts.drain(..) {
+            receipt.unregister_callback();
+        }
+    }
+}
+
+impl Read for LargeObject {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let nbytes = buf.len().min(i32::MAX as usize) as i32;
+        let got = unsafe { pg_sys::inv_read(self.desc(), buf.as_mut_ptr() as *mut _, nbytes) };
+        Ok(got as usize)
+    }
+}
+
+impl Write for LargeObject {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let nbytes = buf.len().min(i32::MAX as usize) as i32;
+        let wrote = unsafe { pg_sys::inv_write(self.desc(), buf.as_ptr() as *const _, nbytes) };
+        Ok(wrote as usize)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        // writes go straight to pg_largeobject
+        Ok(())
+    }
+}
+
+impl Seek for LargeObject {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let (offset, whence) = match pos {
+            SeekFrom::Start(offset) => match i64::try_from(offset) {
+                Ok(offset) => (offset, pg_sys::SEEK_SET),
+                Err(_) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "invalid seek to an overflowing position",
+                    ))
+                }
+            },
+            SeekFrom::Current(offset) => (offset, pg_sys::SEEK_CUR),
+            SeekFrom::End(offset) => (offset, pg_sys::SEEK_END),
+        };
+
+        // `inv_seek()` raises an ERROR for a negative or too large resulting position
+        let pos = unsafe { pg_sys::inv_seek(self.desc(), offset, whence as i32) };
+        Ok(pos as u64)
+    }
+
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        Ok(unsafe { pg_sys::inv_tell(self.desc()) } as u64)
+    }
+}
@@ -37,12 +37,15 @@ pub mod aggregate;
 pub mod callbacks;
 pub mod datum;
 pub mod enum_helper;
+pub mod error;
 pub mod fcinfo;
 pub mod guc;
 pub mod hooks;
 pub mod htup;
 pub mod inoutfuncs;
 pub mod itemptr;
+pub mod jumble;
+pub mod large_object;
 pub mod list;
 #[macro_use]
 pub mod log;
@@ -53,14 +56,22 @@ pub mod memcxt;
 pub mod misc;
 pub mod namespace;
 pub mod nodes;
+pub mod opclass;
 pub mod pgbox;
 pub mod rel;
 pub mod shmem;
 pub mod spi;
 pub mod stringinfo;
+#[cfg(feature = "pg14")]
+pub mod subscript;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+pub mod support;
 pub mod trigger_support;
+pub mod tsearch;
 pub mod tupdesc;
+pub mod tuplestore;
 pub mod varlena;
+pub mod window;
 pub mod wrappers;
 pub mod xid;
 
@@ -72,26 +83,37 @@ pub use atomics::*;
 pub use callbacks::*;
 pub use datum::*;
 pub use enum_helper::*;
+pub use error::*;
 pub use fcinfo::*;
 pub use guc::*;
 pub use hooks::*;
 pub use htup::*;
 pub use inoutfuncs::*;
 pub use itemptr::*;
+pub use jumble::*;
+pub use large_object::*;
 pub use list::*;
 pub use log::*;
 pub use lwlock::*;
 pub use memcxt::*;
 pub use namespace::*;
 pub use nodes::*;
+pub use opclass::*;
 pub use pgbox::*;
 pub use rel::*;
 pub use shmem::*;
 pub use spi::*;
 pub use stringinfo::*;
+#[cfg(feature = "pg14")]
+pub use subscript::*;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+pub use support::*;
 pub use trigger_support::*;
+pub use tsearch::*;
 pub use tupdesc::*;
+pub use tuplestore::*;
 pub use varlena::*;
+pub use window::*;
 pub use wrappers::*;
 pub use xid::*;
 
@@ -155,7 +177,11 @@ pub static DEFAULT_SOURCE_ONLY_SQL_MAPPING: Lazy<HashSet<RustSourceOnlySqlMappin
         let mut m = HashSet::new();
 
         map_source_only!(m, pg_sys::Oid, "Oid");
+        // as spelled in code generated by `#[derive(PostgresType)]`
+        map_source_only!(m, pgx::pg_sys::Oid, "Oid");
         map_source_only!(m, pg_sys::TimestampTz, "timestamp with time zone");
+        map_source_only!(m, JsonOf<_>, "json");
+        map_source_only!(m, JsonBOf<_>, "jsonb");
 
         m
     });
@@ -215,7 +241,13 @@ pub static DEFAULT_TYPEID_SQL_MAPPING: Lazy<HashSet<RustSqlMapping>> = Lazy::new
     map_type!(m, f32, "real");
     map_type!(m, f64, "double precision");
     map_type!(m, datum::JsonB, "jsonb");
+    map_type!(m, datum::JsonBRef<'static>, "jsonb");
+    map_type!(m, datum::JsonbBuilder, "jsonb");
     map_type!(m, datum::Json, "json");
+    map_type!(m, datum::PgRecord, "record");
+    map_type!(m, datum::PgByteaReader, "bytea");
+    map_type!(m, datum::PgTextReader, "text");
+    map_type!(m, large_object::LargeObjectId, "oid");
     map_type!(m, pgx_pg_sys::ItemPointerData, "tid");
     map_type!(m, pgx_pg_sys::Point, "point");
     map_type!(m, pgx_pg_sys::BOX, "box");
@@ -227,6 +259,7 @@ pub static DEFAULT_TYPEID_SQL_MAPPING: Lazy<HashSet<RustSqlMapping>> = Lazy::new
     map_type!(m, pgx_pg_sys::PlannerInfo, "internal");
     map_type!(m, datum::Internal, "internal");
     map_type!(m, pgbox::PgBox<pgx_pg_sys::IndexAmRoutine>, "internal");
+    map_type!(m, pgbox::PgBox<pgx_pg_sys::TSQueryData>, "tsquery");
     map_type!(m, rel::PgRelation, "regclass");
     map_type!(m, datum::Numeric, "numeric");
     map_type!(m, datum::AnyElement, "anyelement");
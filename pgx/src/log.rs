@@ -59,7 +59,7 @@ pub enum PgLogLevel {
 
 /// This list of SQL Error Codes is taken directly from Postgres 12's generated "utils/errcodes.h"
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PgSqlErrorCode {
     /// Class 00 - Successful Completion
     ERRCODE_SUCCESSFUL_COMPLETION = MAKE_SQLSTATE('0', '0', '0', '0', '0') as isize,
@@ -433,6 +433,13 @@ pub enum PgSqlErrorCode {
     ERRCODE_INDEX_CORRUPTED = MAKE_SQLSTATE('X', 'X', '0', '0', '2') as isize,
 }
 
+/// The SQLSTATE encoded as in [`ErrorReport::sqlerrcode`](crate::ErrorReport::sqlerrcode)
+impl From<PgSqlErrorCode> for i32 {
+    fn from(code: PgSqlErrorCode) -> Self {
+        code as i32
+    }
+}
+
 #[allow(non_snake_case)]
 #[inline]
 const fn PGSIXBIT(ch: i32) -> i32 {
@@ -531,6 +538,184 @@ pub fn ereport(
     }
 }
 
+/// An `ereport` message with the optional fields of Postgres' error reports, which are sent to
+/// the client along with the message.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// PgErrorReport::new(PgSqlErrorCode::ERRCODE_CHECK_VIOLATION, "value is out of range")
+///     .with_detail("the value must be positive")
+///     .with_hint("try a positive value")
+///     .with_table_name("values")
+///     .with_column_name("value")
+///     .report(PgLogLevel::ERROR);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgErrorReport {
+    code: PgSqlErrorCode,
+    message: String,
+    detail: Option<String>,
+    hint: Option<String>,
+    context: Option<String>,
+    position: Option<i32>,
+    schema_name: Option<String>,
+    table_name: Option<String>,
+    column_name: Option<String>,
+    datatype_name: Option<String>,
+    constraint_name: Option<String>,
+}
+
+impl PgErrorReport {
+    /// A report with the SQLSTATE `code` and primary `message`
+    pub fn new<S: Into<String>>(code: PgSqlErrorCode, message: S) -> Self {
+        PgErrorReport {
+            code,
+            message: message.into(),
+            detail: None,
+            hint: None,
+            context: None,
+            position: None,
+            schema_name: None,
+            table_name: None,
+            column_name: None,
+            datatype_name: None,
+            constraint_name: None,
+        }
+    }
+
+    /// The `DETAIL` line, through `errdetail`
+    pub fn with_detail<S: Into<String>>(mut self, detail: S) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// The `HINT` line, through `errhint`
+    pub fn with_hint<S: Into<String>>(mut self, hint: S) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// A `CONTEXT` line, through `errcontext`, which precedes the one with the Rust source location
+    pub fn with_context<S: Into<String>>(mut self, context: S) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// The cursor position in the query string, counting characters from 1, through `errposition`
+    pub fn with_position(mut self, position: i32) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// The schema of the object the report is about
+    pub fn with_schema_name<S: Into<String>>(mut self, schema_name: S) -> Self {
+        self.schema_name = Some(schema_name.into());
+        self
+    }
+
+    /// The table of the object the report is about
+    pub fn with_table_name<S: Into<String>>(mut self, table_name: S) -> Self {
+        self.table_name = Some(table_name.into());
+        self
+    }
+
+    /// The column the report is about, which also needs the table name
+    pub fn with_column_name<S: Into<String>>(mut self, column_name: S) -> Self {
+        self.column_name = Some(column_name.into());
+        self
+    }
+
+    /// The data type the report is about
+    pub fn with_datatype_name<S: Into<String>>(mut self, datatype_name: S) -> Self {
+        self.datatype_name = Some(datatype_name.into());
+        self
+    }
+
+    /// The constraint the report is about
+    pub fn with_constraint_name<S: Into<String>>(mut self, constraint_name: S) -> Self {
+        self.constraint_name = Some(constraint_name.into());
+        self
+    }
+
+    /// Emit the report at `level`.
+    ///
+    /// Reports of level `PgLogLevel::ERROR` will cause the current transaction to abort
+    #[track_caller]
+    pub fn report(self, level: PgLogLevel) {
+        use std::ffi::{CStr, CString};
+        use std::os::raw::c_char;
+
+        extern "C" {
+            fn pgx_ereport_full(
+                level: i32,
+                code: i32,
+                message: *const c_char,
+                detail: *const c_char,
+                hint: *const c_char,
+                context: *const c_char,
+                position: i32,
+                schema_name: *const c_char,
+                table_name: *const c_char,
+                column_name: *const c_char,
+                datatype_name: *const c_char,
+                constraint_name: *const c_char,
+                file: *const c_char,
+                lineno: i32,
+                colno: i32,
+            );
+        }
+
+        fn c_string(string: String) -> CString {
+            // keep what precedes a NUL rather than losing the string entirely
+            CString::new(string).unwrap_or_else(|e| {
+                let nul = e.nul_position();
+                let mut bytes = e.into_vec();
+                bytes.truncate(nul);
+                CString::new(bytes).unwrap()
+            })
+        }
+
+        fn c_ptr(string: Option<&CStr>) -> *const c_char {
+            string.map_or(std::ptr::null(), |string| string.as_ptr())
+        }
+
+        let location = std::panic::Location::caller();
+        let message = c_string(self.message);
+        let detail = self.detail.map(c_string);
+        let hint = self.hint.map(c_string);
+        let context = self.context.map(c_string);
+        let schema_name = self.schema_name.map(c_string);
+        let table_name = self.table_name.map(c_string);
+        let column_name = self.column_name.map(c_string);
+        let datatype_name = self.datatype_name.map(c_string);
+        let constraint_name = self.constraint_name.map(c_string);
+        let file = c_string(location.file().to_string());
+
+        unsafe {
+            crate::guard(|| {
+                pgx_ereport_full(
+                    level as i32,
+                    self.code as i32,
+                    message.as_ptr(),
+                    c_ptr(detail.as_deref()),
+                    c_ptr(hint.as_deref()),
+                    c_ptr(context.as_deref()),
+                    self.position.unwrap_or(0),
+                    c_ptr(schema_name.as_deref()),
+                    c_ptr(table_name.as_deref()),
+                    c_ptr(column_name.as_deref()),
+                    c_ptr(datatype_name.as_deref()),
+                    c_ptr(constraint_name.as_deref()),
+                    file.as_ptr(),
+                    location.line() as i32,
+                    location.column() as i32,
+                )
+            });
+        }
+    }
+}
+
 /// Log to Postgres' `debug5` log level.
 ///
 /// This macro accepts arguments like the [`println`](std::println) and [`format`](std::format) macros.
@@ -734,10 +919,21 @@ macro_rules! notice {
 ///          6
 /// (1 row)
 /// ```
+///
+/// A `detail = ...` and a `hint = ...`, in either order, may follow the format arguments, as the
+/// `DETAIL` and `HINT` lines of the warning.  Only the last arguments are taken as the `DETAIL` and
+/// `HINT`, so a named format argument called `detail` or `hint` can't come last:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let sum = -1;
+/// pgx::warning!("sum={}", sum, detail = "the sum is negative", hint = "add positive numbers");
+/// ```
 #[macro_export]
 macro_rules! warning {
-    ($($arg:tt)*) => (
-        $crate::log::elog($crate::log::PgLogLevel::WARNING, format!($($arg)*).as_str());
+    ($fmt:expr $(, $($arg:tt)*)?) => (
+        $crate::__pgx_log_report!(WARNING [$fmt] $(, $($arg)*)?);
     )
 }
 
@@ -771,14 +967,75 @@ macro_rules! warning {
 /// ERROR:  i=Some(1), sum=0
 /// CONTEXT:  src/lib.rs:37:9
 /// ```
+///
+/// A `detail = ...` and a `hint = ...`, in either order, may follow the format arguments, as the
+/// `DETAIL` and `HINT` lines of the error.  Only the last arguments are taken as the `DETAIL` and
+/// `HINT`, so a named format argument called `detail` or `hint` can't come last:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let index = 4;
+/// pgx::error!("no element {}", index, detail = "the array has 3 elements", hint = "index from 1");
+/// ```
 #[macro_export]
 macro_rules! error {
     () => ({ panic!("explicit ERROR") });
     ($msg:expr) => ({ panic!($msg) });
     ($msg:expr,) => ({ panic!($msg) });
     ($fmt:expr, $($arg:tt)+) => ({
-        panic!($fmt, $($arg)+)
+        $crate::__pgx_log_report!(ERROR [$fmt], $($arg)+)
+    });
+}
+
+/// Takes a trailing `detail = ...` and `hint = ...` off the arguments of [`warning!`] and
+/// [`error!`], collecting the rest, one argument at a time, as the format arguments of the message
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pgx_log_report {
+    (@report WARNING [$($fmt:tt)*] [] []) => ({
+        $crate::log::elog($crate::log::PgLogLevel::WARNING, format!($($fmt)*).as_str())
+    });
+    (@report ERROR [$($fmt:tt)*] [] []) => ({
+        panic!($($fmt)*)
+    });
+    (@report WARNING [$($fmt:tt)*] [$($detail:expr)?] [$($hint:expr)?]) => ({
+        let report = $crate::log::PgErrorReport::new(
+            $crate::log::PgSqlErrorCode::ERRCODE_WARNING,
+            format!($($fmt)*),
+        );
+        $(let report = report.with_detail($detail);)?
+        $(let report = report.with_hint($hint);)?
+        report.report($crate::log::PgLogLevel::WARNING)
+    });
+    (@report ERROR [$($fmt:tt)*] [$($detail:expr)?] [$($hint:expr)?]) => ({
+        let report = $crate::log::PgErrorReport::new(
+            $crate::log::PgSqlErrorCode::ERRCODE_INTERNAL_ERROR,
+            format!($($fmt)*),
+        );
+        $(let report = report.with_detail($detail);)?
+        $(let report = report.with_hint($hint);)?
+        report.report($crate::log::PgLogLevel::ERROR);
+        unreachable!("ereport(ERROR) returned")
     });
+    ($level:ident [$($fmt:tt)*] $(,)?) => (
+        $crate::__pgx_log_report!(@report $level [$($fmt)*] [] [])
+    );
+    ($level:ident [$($fmt:tt)*] , detail = $detail:expr $(, hint = $hint:expr)? $(,)?) => (
+        $crate::__pgx_log_report!(@report $level [$($fmt)*] [$detail] [$($hint)?])
+    );
+    ($level:ident [$($fmt:tt)*] , hint = $hint:expr, detail = $detail:expr $(,)?) => (
+        $crate::__pgx_log_report!(@report $level [$($fmt)*] [$detail] [$hint])
+    );
+    ($level:ident [$($fmt:tt)*] , hint = $hint:expr $(,)?) => (
+        $crate::__pgx_log_report!(@report $level [$($fmt)*] [] [$hint])
+    );
+    ($level:ident [$($fmt:tt)*] , $name:ident = $value:expr $(, $($rest:tt)*)?) => (
+        $crate::__pgx_log_report!($level [$($fmt)*, $name = $value] $(, $($rest)*)?)
+    );
+    ($level:ident [$($fmt:tt)*] , $value:expr $(, $($rest:tt)*)?) => (
+        $crate::__pgx_log_report!($level [$($fmt)*, $value] $(, $($rest)*)?)
+    );
 }
 
 /// Log to Postgres' `fatal` log level.  This will abort the current Postgres backend connection processs.
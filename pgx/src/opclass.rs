@@ -0,0 +1,731 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+/*!
+
+[Index operator class](https://www.postgresql.org/docs/current/xindex.html) support for the
+GiST, GIN, SP-GiST and BRIN access methods.
+
+Operator classes are created by implementing one of [`GistOpClass`], [`GinOpClass`],
+[`SpGistOpClass`] or [`BrinOpClass`] for a type and decorating the implementation with
+[`#[pg_opclass]`](pgx_macros::pg_opclass). (Btree and hash operator classes are created with
+`#[derive(PostgresOrd)]` and `#[derive(PostgresHash)]`.)
+
+The macro generates the `internal`-typed support functions the access method expects and emits
+the matching [`CREATE OPERATOR CLASS`](https://www.postgresql.org/docs/current/sql-createopclass.html)
+statement.  The operators listed in `OPERATORS` are not created by the macro, they are usually
+`#[pg_operator]` functions.
+
+# GiST Example
+
+```rust,no_run
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+// pg_module_magic!(); // Uncomment this outside of docs!
+
+#[derive(Clone, Serialize, Deserialize, PostgresType)]
+pub struct Span {
+    start: i32,
+    end: i32,
+}
+
+#[pg_operator(immutable, parallel_safe)]
+#[opname(&&)]
+fn span_overlaps(left: Span, right: Span) -> bool {
+    left.start <= right.end && right.start <= left.end
+}
+
+#[pg_opclass]
+impl GistOpClass for Span {
+    const OPERATORS: &'static [(u16, &'static str)] = &[(3, "&&")];
+    type Key = Span;
+    type Query = Span;
+
+    fn compress(self) -> Span {
+        self
+    }
+
+    fn consistent(key: &Span, query: Span, _strategy: u16, _is_leaf: bool) -> IndexMatch {
+        (key.start <= query.end && query.start <= key.end).into()
+    }
+
+    fn union(keys: &[Span]) -> Span {
+        Span {
+            start: keys.iter().map(|key| key.start).min().unwrap(),
+            end: keys.iter().map(|key| key.end).max().unwrap(),
+        }
+    }
+
+    fn penalty(original: &Span, new: &Span) -> f32 {
+        let grown = Span::union(&[original.clone(), new.clone()]);
+        ((grown.end - grown.start) - (original.end - original.start)) as f32
+    }
+
+    fn picksplit(keys: &[Span]) -> (Vec<usize>, Vec<usize>) {
+        let mut order = (0..keys.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| keys[i].start);
+        let right = order.split_off(order.len() / 2);
+        (order, right)
+    }
+
+    fn same(a: &Span, b: &Span) -> bool {
+        a.start == b.start && a.end == b.end
+    }
+}
+```
+
+This creates SQL like so:
+
+```sql
+-- src/lib.rs:22
+-- opclass::Span
+CREATE OPERATOR CLASS span_gist_ops DEFAULT FOR TYPE Span USING gist AS
+    OPERATOR 3 &&,
+    FUNCTION 1 "span_gist_consistent" /* opclass::span_gist_consistent */,
+    FUNCTION 2 "span_gist_union" /* opclass::span_gist_union */,
+    FUNCTION 3 "span_gist_compress" /* opclass::span_gist_compress */,
+    FUNCTION 4 "span_gist_decompress" /* opclass::span_gist_decompress */,
+    FUNCTION 5 "span_gist_penalty" /* opclass::span_gist_penalty */,
+    FUNCTION 6 "span_gist_picksplit" /* opclass::span_gist_picksplit */,
+    FUNCTION 7 "span_gist_same" /* opclass::span_gist_same */;
+```
+*/
+use crate::{pg_sys, FromDatum, Internal, IntoDatum, PgMemoryContexts};
+
+/// Whether an index entry matches a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexMatch {
+    False,
+    True,
+    /// The entry might match, the row needs to be rechecked against the operator.
+    Maybe,
+}
+
+impl From<bool> for IndexMatch {
+    fn from(matches: bool) -> Self {
+        if matches {
+            IndexMatch::True
+        } else {
+            IndexMatch::False
+        }
+    }
+}
+
+/// Which rows a GIN query matches beyond those containing its keys, as returned by
+/// [`GinOpClass::extract_query`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GinSearchMode {
+    /// Only rows containing at least one of the query's keys.
+    Default,
+    /// Also rows without any keys.
+    IncludeEmpty,
+    /// Every row except those with a `NULL` value.
+    All,
+    /// Every row.
+    Everything,
+}
+
+/// A GiST operator class, used with [`#[pg_opclass]`](pgx_macros::pg_opclass).
+///
+/// The index stores a `Key` per row, made by `compress`, and per subtree, made by `union`.
+pub trait GistOpClass: FromDatum + IntoDatum {
+    /// The operators of the class, by strategy number.  An operator with a different right-hand
+    /// type needs its argument types, like `(2, "@> (Span, int4)")`, and an ordering operator
+    /// its sort family, like `(15, "<-> (Span, Span) FOR ORDER BY float_ops")`.
+    const OPERATORS: &'static [(u16, &'static str)];
+
+    /// The type stored in the index.  If it isn't `Self`, it's the class's `STORAGE`.
+    type Key: FromDatum + IntoDatum;
+
+    /// The right-hand type of the operators.
+    type Query: FromDatum + IntoDatum;
+
+    /// Make the key of a row.
+    fn compress(self) -> Self::Key;
+
+    /// Whether rows under `key` can match `query` with the operator of `strategy`.
+    ///
+    /// `is_leaf` is `true` if `key` was made by `compress` rather than `union`.
+    fn consistent(key: &Self::Key, query: Self::Query, strategy: u16, is_leaf: bool) -> IndexMatch;
+
+    /// Make a key covering all of `keys`.  `keys` is never empty.
+    fn union(keys: &[Self::Key]) -> Self::Key;
+
+    /// The cost of adding `new` under `original`.  Must not be negative.
+    fn penalty(original: &Self::Key, new: &Self::Key) -> f32;
+
+    /// Split `keys` into two pages, returning the indices of `keys` that go to the left and to
+    /// the right page.
+    fn picksplit(keys: &[Self::Key]) -> (Vec<usize>, Vec<usize>);
+
+    /// Whether `a` and `b` are the same key.
+    fn same(a: &Self::Key, b: &Self::Key) -> bool;
+
+    /// The distance between `key` and `query` for the ordering operator of `strategy`.  For a
+    /// key made by `union`, it must not be more than the distance of any row under it.
+    ///
+    /// **Optional:** If this function isn't implemented in the `#[pg_opclass]` block, the class is
+    /// created without a `distance` function and can't have ordering operators.
+    fn distance(_key: &Self::Key, _query: Self::Query, _strategy: u16, _is_leaf: bool) -> f64 {
+        unimplemented!("Call to distance on a GiST operator class which does not support it.")
+    }
+}
+
+/// A GIN operator class, used with [`#[pg_opclass]`](pgx_macros::pg_opclass).
+///
+/// The index maps each `Key` to the rows containing it.
+pub trait GinOpClass: FromDatum + IntoDatum {
+    /// The operators of the class, by strategy number.  See [`GistOpClass::OPERATORS`].
+    const OPERATORS: &'static [(u16, &'static str)];
+
+    /// The type stored in the index.  If it isn't `Self`, it's the class's `STORAGE`.
+    type Key: FromDatum + IntoDatum + Ord;
+
+    /// The right-hand type of the operators.
+    type Query: FromDatum + IntoDatum;
+
+    /// The keys of a row.
+    fn extract_value(self) -> Vec<Self::Key>;
+
+    /// The keys to look up for `query` with the operator of `strategy`.
+    fn extract_query(query: Self::Query, strategy: u16) -> (Vec<Self::Key>, GinSearchMode);
+
+    /// Whether a row matches, where `check[i]` tells if it contains the `i`th key of
+    /// `extract_query`.
+    fn consistent(check: &[bool], strategy: u16) -> IndexMatch;
+
+    /// Like `consistent`, but where it's not known whether a row contains the keys that are
+    /// [`IndexMatch::Maybe`].
+    ///
+    /// **Optional:** If this function isn't implemented in the `#[pg_opclass]` block, the class is
+    /// created without a `triConsistent` function.
+    fn tri_consistent(_check: &[IndexMatch], _strategy: u16) -> IndexMatch {
+        unimplemented!("Call to tri_consistent on a GIN operator class which does not support it.")
+    }
+}
+
+/// An SP-GiST operator class, used with [`#[pg_opclass]`](pgx_macros::pg_opclass).
+///
+/// The support functions work directly with the structs of `spgist.h`, as described in the
+/// [Postgres documentation](https://www.postgresql.org/docs/current/spgist-extensibility.html).
+pub trait SpGistOpClass {
+    /// The operators of the class, by strategy number.  See [`GistOpClass::OPERATORS`].
+    const OPERATORS: &'static [(u16, &'static str)];
+
+    fn config(input: &pg_sys::spgConfigIn, output: &mut pg_sys::spgConfigOut);
+
+    fn choose(input: &pg_sys::spgChooseIn, output: &mut pg_sys::spgChooseOut);
+
+    fn picksplit(input: &pg_sys::spgPickSplitIn, output: &mut pg_sys::spgPickSplitOut);
+
+    fn inner_consistent(
+        input: &pg_sys::spgInnerConsistentIn,
+        output: &mut pg_sys::spgInnerConsistentOut,
+    );
+
+    /// Whether the leaf matches all of `input.scankeys`.
+    fn leaf_consistent(
+        input: &pg_sys::spgLeafConsistentIn,
+        output: &mut pg_sys::spgLeafConsistentOut,
+    ) -> bool;
+}
+
+/// A BRIN operator class, used with [`#[pg_opclass]`](pgx_macros::pg_opclass).
+///
+/// The index stores a `Summary` of the non-`NULL` values of each block range.
+pub trait BrinOpClass: FromDatum + IntoDatum {
+    /// The operators of the class, by strategy number.  See [`GistOpClass::OPERATORS`].
+    const OPERATORS: &'static [(u16, &'static str)];
+
+    /// The type stored in the index.  If it isn't `Self`, it's the class's `STORAGE`.
+    type Summary: FromDatum + IntoDatum;
+
+    /// The right-hand type of the operators.
+    type Query: FromDatum + IntoDatum;
+
+    /// Add `value` to `summary`, which is `None` if the range has no values yet.
+    ///
+    /// Return `None` if `summary` already covers `value`.
+    fn add_value(summary: Option<&Self::Summary>, value: Self) -> Option<Self::Summary>;
+
+    /// Whether the range of `summary` can have rows matching `query` with the operator of
+    /// `strategy`.
+    fn consistent(summary: &Self::Summary, query: Self::Query, strategy: u16) -> bool;
+
+    /// Make a summary covering both `a` and `b`.
+    fn union(a: Self::Summary, b: Self::Summary) -> Self::Summary;
+}
+
+impl IndexMatch {
+    unsafe fn into_recheck(self, recheck: Internal) -> bool {
+        if let Some(recheck) = recheck.get_mut::<bool>() {
+            *recheck = self == IndexMatch::Maybe;
+        }
+        self != IndexMatch::False
+    }
+}
+
+unsafe fn arg_datum(arg: Internal, name: &str) -> pg_sys::Datum {
+    arg.unwrap()
+        .unwrap_or_else(|| panic!("operator class argument `{}` was null", name))
+}
+
+unsafe fn from_arg<T: FromDatum + IntoDatum>(arg: Internal, name: &str) -> T {
+    T::from_datum(arg_datum(arg, name), false, T::type_oid())
+        .unwrap_or_else(|| panic!("operator class argument `{}` was null", name))
+}
+
+unsafe fn from_datum<T: FromDatum + IntoDatum>(datum: pg_sys::Datum) -> T {
+    T::from_datum(datum, false, T::type_oid()).expect("index key was null")
+}
+
+fn into_datum<T: IntoDatum>(value: T) -> pg_sys::Datum {
+    value
+        .into_datum()
+        .expect("operator class support functions can't return NULL")
+}
+
+/// The size of `datum`, as `datumGetSize()` would report it.
+unsafe fn datum_size(datum: pg_sys::Datum, typoid: pg_sys::Oid) -> i32 {
+    let mut typlen = 0;
+    let mut typbyval = false;
+    pg_sys::get_typlenbyval(typoid, &mut typlen, &mut typbyval);
+    match typlen {
+        -1 => crate::varsize_any(datum as *const pg_sys::varlena) as i32,
+        -2 => std::ffi::CStr::from_ptr(datum as *const std::os::raw::c_char)
+            .to_bytes_with_nul()
+            .len() as i32,
+        _ if typbyval => std::mem::size_of::<pg_sys::Datum>() as i32,
+        typlen => typlen as i32,
+    }
+}
+
+unsafe fn gist_entries<'a>(entryvec: Internal) -> &'a [pg_sys::GISTENTRY] {
+    let entryvec = arg_datum(entryvec, "entryvec") as *mut pg_sys::GistEntryVector;
+    (*entryvec).vector.as_slice((*entryvec).n as usize)
+}
+
+/// `GIST_LEAF()`: whether `entry` is on a leaf page.
+unsafe fn gist_entry_is_leaf(entry: &pg_sys::GISTENTRY) -> bool {
+    let page = entry.page;
+    let special = (*(page as *const pg_sys::PageHeaderData)).pd_special;
+    let opaque = page.add(special as usize) as *const pg_sys::GISTPageOpaqueData;
+    (*opaque).flags & pg_sys::F_LEAF as u16 != 0
+}
+
+#[doc(hidden)]
+pub unsafe fn gist_consistent<T: GistOpClass>(
+    entry: Internal,
+    query: Internal,
+    strategy: Internal,
+    _subtype: Internal,
+    recheck: Internal,
+) -> bool {
+    let entry = &*(arg_datum(entry, "entry") as *const pg_sys::GISTENTRY);
+    let key = from_datum::<T::Key>(entry.key);
+    let query = from_arg::<T::Query>(query, "query");
+    let strategy = arg_datum(strategy, "strategy") as u16;
+    T::consistent(&key, query, strategy, gist_entry_is_leaf(entry)).into_recheck(recheck)
+}
+
+#[doc(hidden)]
+pub unsafe fn gist_union<T: GistOpClass>(entryvec: Internal, size: Internal) -> Internal {
+    let keys = gist_entries(entryvec)
+        .iter()
+        .map(|entry| from_datum::<T::Key>(entry.key))
+        .collect::<Vec<_>>();
+    let union = into_datum(T::union(&keys));
+    if let Some(size) = size.get_mut::<i32>() {
+        *size = datum_size(union, T::Key::type_oid());
+    }
+    Internal::from(Some(union))
+}
+
+#[doc(hidden)]
+pub unsafe fn gist_compress<T: GistOpClass>(entry: Internal) -> Internal {
+    let entry = arg_datum(entry, "entry") as *mut pg_sys::GISTENTRY;
+    if !(*entry).leafkey {
+        return Internal::from(Some(entry as pg_sys::Datum));
+    }
+
+    let key = from_datum::<T>((*entry).key).compress();
+    let compressed = PgMemoryContexts::CurrentMemoryContext.palloc0_struct::<pg_sys::GISTENTRY>();
+    (*compressed).key = into_datum(key);
+    (*compressed).rel = (*entry).rel;
+    (*compressed).page = (*entry).page;
+    (*compressed).offset = (*entry).offset;
+    (*compressed).leafkey = false;
+    Internal::from(Some(compressed as pg_sys::Datum))
+}
+
+#[doc(hidden)]
+pub unsafe fn gist_decompress<T: GistOpClass>(entry: Internal) -> Internal {
+    // keys are detoasted by `FromDatum`, so there's nothing to do
+    entry
+}
+
+#[doc(hidden)]
+pub unsafe fn gist_penalty<T: GistOpClass>(
+    original: Internal,
+    new: Internal,
+    penalty: Internal,
+) -> Internal {
+    let original = &*(arg_datum(original, "original") as *const pg_sys::GISTENTRY);
+    let new = &*(arg_datum(new, "new") as *const pg_sys::GISTENTRY);
+    let result = T::penalty(
+        &from_datum::<T::Key>(original.key),
+        &from_datum::<T::Key>(new.key),
+    );
+    let penalty_datum = arg_datum(penalty, "penalty");
+    *(penalty_datum as *mut f32) = result;
+    Internal::from(Some(penalty_datum))
+}
+
+#[doc(hidden)]
+pub unsafe fn gist_picksplit<T: GistOpClass>(entryvec: Internal, split: Internal) -> Internal {
+    // the entries to split start at `FirstOffsetNumber`
+    let entries = &gist_entries(entryvec)[1..];
+    let keys = entries
+        .iter()
+        .map(|entry| from_datum::<T::Key>(entry.key))
+        .collect::<Vec<_>>();
+    let (left, right) = T::picksplit(&keys);
+
+    let mut seen = vec![false; keys.len()];
+    for &i in left.iter().chain(right.iter()) {
+        match seen.get_mut(i) {
+            Some(seen) if !*seen => *seen = true,
+            _ => panic!(
+                "GiST picksplit returned an invalid or duplicate index: {}",
+                i
+            ),
+        }
+    }
+    if seen.contains(&false) {
+        panic!("GiST picksplit must place every key on a page");
+    }
+
+    let mut context = PgMemoryContexts::CurrentMemoryContext;
+    let mut page = |indices: &[usize]| {
+        let offsets = context.palloc_slice::<pg_sys::OffsetNumber>(indices.len().max(1));
+        for (offset, &i) in offsets.iter_mut().zip(indices) {
+            *offset = (i + 1) as pg_sys::OffsetNumber;
+        }
+        let union = if indices.is_empty() {
+            0
+        } else {
+            let keys = indices
+                .iter()
+                .map(|&i| from_datum::<T::Key>(entries[i].key))
+                .collect::<Vec<_>>();
+            into_datum(T::union(&keys))
+        };
+        (offsets.as_mut_ptr(), indices.len() as i32, union)
+    };
+
+    let split_datum = arg_datum(split, "split");
+    let split = &mut *(split_datum as *mut pg_sys::GIST_SPLITVEC);
+    let (offsets, n, union) = page(&left);
+    split.spl_left = offsets;
+    split.spl_nleft = n;
+    split.spl_ldatum = union;
+    let (offsets, n, union) = page(&right);
+    split.spl_right = offsets;
+    split.spl_nright = n;
+    split.spl_rdatum = union;
+    // secondary splits aren't supported, Postgres unions the existing keys in itself
+    split.spl_ldatum_exists = false;
+    split.spl_rdatum_exists = false;
+    Internal::from(Some(split_datum))
+}
+
+#[doc(hidden)]
+pub unsafe fn gist_same<T: GistOpClass>(a: Internal, b: Internal, result: Internal) -> Internal {
+    let same = T::same(&from_arg::<T::Key>(a, "a"), &from_arg::<T::Key>(b, "b"));
+    let result_datum = arg_datum(result, "result");
+    *(result_datum as *mut bool) = same;
+    Internal::from(Some(result_datum))
+}
+
+#[doc(hidden)]
+pub unsafe fn gist_distance<T: GistOpClass>(
+    entry: Internal,
+    query: Internal,
+    strategy: Internal,
+    _subtype: Internal,
+    recheck: Internal,
+) -> f64 {
+    let entry = &*(arg_datum(entry, "entry") as *const pg_sys::GISTENTRY);
+    let key = from_datum::<T::Key>(entry.key);
+    let query = from_arg::<T::Query>(query, "query");
+    let strategy = arg_datum(strategy, "strategy") as u16;
+    if let Some(recheck) = recheck.get_mut::<bool>() {
+        *recheck = false;
+    }
+    T::distance(&key, query, strategy, gist_entry_is_leaf(entry))
+}
+
+/// Write `keys` to a palloc'd array, storing its length in `nkeys`.
+unsafe fn gin_keys<K: IntoDatum>(keys: Vec<K>, nkeys: Internal) -> Internal {
+    let datums = PgMemoryContexts::CurrentMemoryContext.palloc_slice::<pg_sys::Datum>(keys.len());
+    for (datum, key) in datums.iter_mut().zip(keys) {
+        *datum = into_datum(key);
+    }
+    if let Some(nkeys) = nkeys.get_mut::<i32>() {
+        *nkeys = datums.len() as i32;
+    }
+    Internal::from(Some(datums.as_mut_ptr() as pg_sys::Datum))
+}
+
+#[doc(hidden)]
+pub unsafe fn gin_compare<T: GinOpClass>(a: Internal, b: Internal) -> i32 {
+    match from_arg::<T::Key>(a, "a").cmp(&from_arg::<T::Key>(b, "b")) {
+        std::cmp::Ordering::Less => -1,
+        std::cmp::Ordering::Equal => 0,
+        std::cmp::Ordering::Greater => 1,
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn gin_extract_value<T: GinOpClass>(
+    value: Internal,
+    nkeys: Internal,
+    _null_flags: Internal,
+) -> Internal {
+    gin_keys(from_arg::<T>(value, "value").extract_value(), nkeys)
+}
+
+#[doc(hidden)]
+pub unsafe fn gin_extract_query<T: GinOpClass>(
+    query: Internal,
+    nkeys: Internal,
+    strategy: Internal,
+    _partial_match: Internal,
+    _extra_data: Internal,
+    _null_flags: Internal,
+    search_mode: Internal,
+) -> Internal {
+    let strategy = arg_datum(strategy, "strategy") as u16;
+    let (keys, mode) = T::extract_query(from_arg::<T::Query>(query, "query"), strategy);
+    if let Some(search_mode) = search_mode.get_mut::<i32>() {
+        *search_mode = match mode {
+            GinSearchMode::Default => pg_sys::GIN_SEARCH_MODE_DEFAULT,
+            GinSearchMode::IncludeEmpty => pg_sys::GIN_SEARCH_MODE_INCLUDE_EMPTY,
+            GinSearchMode::All => pg_sys::GIN_SEARCH_MODE_ALL,
+            GinSearchMode::Everything => pg_sys::GIN_SEARCH_MODE_EVERYTHING,
+        } as i32;
+    }
+    gin_keys(keys, nkeys)
+}
+
+#[doc(hidden)]
+pub unsafe fn gin_consistent<T: GinOpClass>(
+    check: Internal,
+    strategy: Internal,
+    _query: Internal,
+    nkeys: Internal,
+    _extra_data: Internal,
+    recheck: Internal,
+    _query_keys: Internal,
+    _null_flags: Internal,
+) -> bool {
+    let nkeys = arg_datum(nkeys, "nkeys") as i32;
+    let check = std::slice::from_raw_parts(
+        arg_datum(check, "check") as *const bool,
+        nkeys.max(0) as usize,
+    );
+    let strategy = arg_datum(strategy, "strategy") as u16;
+    T::consistent(check, strategy).into_recheck(recheck)
+}
+
+#[doc(hidden)]
+pub unsafe fn gin_tri_consistent<T: GinOpClass>(
+    check: Internal,
+    strategy: Internal,
+    _query: Internal,
+    nkeys: Internal,
+    _extra_data: Internal,
+    _query_keys: Internal,
+    _null_flags: Internal,
+) -> i8 {
+    let nkeys = arg_datum(nkeys, "nkeys") as i32;
+    let check = std::slice::from_raw_parts(
+        arg_datum(check, "check") as *const pg_sys::GinTernaryValue,
+        nkeys.max(0) as usize,
+    )
+    .iter()
+    .map(|&value| match value as u32 {
+        pg_sys::GIN_FALSE => IndexMatch::False,
+        pg_sys::GIN_TRUE => IndexMatch::True,
+        _ => IndexMatch::Maybe,
+    })
+    .collect::<Vec<_>>();
+    let strategy = arg_datum(strategy, "strategy") as u16;
+    (match T::tri_consistent(&check, strategy) {
+        IndexMatch::False => pg_sys::GIN_FALSE,
+        IndexMatch::True => pg_sys::GIN_TRUE,
+        IndexMatch::Maybe => pg_sys::GIN_MAYBE,
+    }) as i8
+}
+
+#[doc(hidden)]
+pub unsafe fn spgist_config<T: SpGistOpClass>(input: Internal, output: Internal) {
+    T::config(
+        input
+            .get::<pg_sys::spgConfigIn>()
+            .expect("spgConfigIn was null"),
+        output
+            .get_mut::<pg_sys::spgConfigOut>()
+            .expect("spgConfigOut was null"),
+    )
+}
+
+#[doc(hidden)]
+pub unsafe fn spgist_choose<T: SpGistOpClass>(input: Internal, output: Internal) {
+    T::choose(
+        input
+            .get::<pg_sys::spgChooseIn>()
+            .expect("spgChooseIn was null"),
+        output
+            .get_mut::<pg_sys::spgChooseOut>()
+            .expect("spgChooseOut was null"),
+    )
+}
+
+#[doc(hidden)]
+pub unsafe fn spgist_picksplit<T: SpGistOpClass>(input: Internal, output: Internal) {
+    T::picksplit(
+        input
+            .get::<pg_sys::spgPickSplitIn>()
+            .expect("spgPickSplitIn was null"),
+        output
+            .get_mut::<pg_sys::spgPickSplitOut>()
+            .expect("spgPickSplitOut was null"),
+    )
+}
+
+#[doc(hidden)]
+pub unsafe fn spgist_inner_consistent<T: SpGistOpClass>(input: Internal, output: Internal) {
+    T::inner_consistent(
+        input
+            .get::<pg_sys::spgInnerConsistentIn>()
+            .expect("spgInnerConsistentIn was null"),
+        output
+            .get_mut::<pg_sys::spgInnerConsistentOut>()
+            .expect("spgInnerConsistentOut was null"),
+    )
+}
+
+#[doc(hidden)]
+pub unsafe fn spgist_leaf_consistent<T: SpGistOpClass>(input: Internal, output: Internal) -> bool {
+    T::leaf_consistent(
+        input
+            .get::<pg_sys::spgLeafConsistentIn>()
+            .expect("spgLeafConsistentIn was null"),
+        output
+            .get_mut::<pg_sys::spgLeafConsistentOut>()
+            .expect("spgLeafConsistentOut was null"),
+    )
+}
+
+#[doc(hidden)]
+pub unsafe fn brin_opcinfo<T: BrinOpClass>(_typoid: Internal) -> Internal {
+    // a single stored value, the summary, followed by its `oi_typcache` entry
+    let info = PgMemoryContexts::CurrentMemoryContext.palloc0(
+        std::mem::size_of::<pg_sys::BrinOpcInfo>()
+            + std::mem::size_of::<*mut pg_sys::TypeCacheEntry>(),
+    ) as *mut pg_sys::BrinOpcInfo;
+    (*info).oi_nstored = 1;
+    (*info).oi_typcache.as_mut_slice(1)[0] = pg_sys::lookup_type_cache(T::Summary::type_oid(), 0);
+    Internal::from(Some(info as pg_sys::Datum))
+}
+
+#[doc(hidden)]
+pub unsafe fn brin_add_value<T: BrinOpClass>(
+    _desc: Internal,
+    column: Internal,
+    value: Internal,
+    is_null: Internal,
+) -> bool {
+    let column = column
+        .get_mut::<pg_sys::BrinValues>()
+        .expect("BrinValues was null");
+    if arg_datum(is_null, "is_null") != 0 {
+        let modified = !column.bv_hasnulls;
+        column.bv_hasnulls = true;
+        return modified;
+    }
+
+    let summary = if column.bv_allnulls {
+        None
+    } else {
+        Some(from_datum::<T::Summary>(*column.bv_values))
+    };
+    match T::add_value(summary.as_ref(), from_arg::<T>(value, "value")) {
+        Some(summary) => {
+            *column.bv_values = into_datum(summary);
+            column.bv_allnulls = false;
+            true
+        }
+        None => false,
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn brin_consistent<T: BrinOpClass>(
+    _desc: Internal,
+    column: Internal,
+    key: Internal,
+) -> bool {
+    let column = column
+        .get::<pg_sys::BrinValues>()
+        .expect("BrinValues was null");
+    let key = key.get::<pg_sys::ScanKeyData>().expect("ScanKey was null");
+
+    // `IS NULL` and `IS NOT NULL` are answered from the range's null flags
+    let flags = key.sk_flags as u32;
+    if flags & pg_sys::SK_ISNULL != 0 {
+        return if flags & pg_sys::SK_SEARCHNULL != 0 {
+            column.bv_allnulls || column.bv_hasnulls
+        } else if flags & pg_sys::SK_SEARCHNOTNULL != 0 {
+            !column.bv_allnulls
+        } else {
+            false
+        };
+    }
+    if column.bv_allnulls {
+        return false;
+    }
+
+    let summary = from_datum::<T::Summary>(*column.bv_values);
+    let query = from_datum::<T::Query>(key.sk_argument);
+    T::consistent(&summary, query, key.sk_strategy)
+}
+
+#[doc(hidden)]
+pub unsafe fn brin_union<T: BrinOpClass>(_desc: Internal, a: Internal, b: Internal) -> bool {
+    let a = a
+        .get_mut::<pg_sys::BrinValues>()
+        .expect("BrinValues was null");
+    let b = b.get::<pg_sys::BrinValues>().expect("BrinValues was null");
+    a.bv_hasnulls |= b.bv_hasnulls;
+    if b.bv_allnulls {
+        return true;
+    }
+
+    let b_summary = from_datum::<T::Summary>(*b.bv_values);
+    let union = if a.bv_allnulls {
+        b_summary
+    } else {
+        T::union(from_datum::<T::Summary>(*a.bv_values), b_summary)
+    };
+    *a.bv_values = into_datum(union);
+    a.bv_allnulls = false;
+    true
+}
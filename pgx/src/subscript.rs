@@ -0,0 +1,270 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Support for `value[subscript]` subscripting of `#[derive(PostgresType)]` types, through
+//! Postgres 14's `SubscriptRoutines`
+use crate::{ereport, pg_sys, FromDatum, IntoDatum, PgList, PgLogLevel, PgSqlErrorCode};
+use std::panic::AssertUnwindSafe;
+
+/// `#[derive(PostgresType)]` types with the `#[pgx(subscript)]` attribute need to implement this
+/// trait to support `value[subscript]` expressions, like `jsonb` and `hstore` do.
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use serde::{Deserialize, Serialize};
+/// use std::collections::HashMap;
+///
+/// // SELECT ('{"a": "b"}'::Store)['a'];
+/// // UPDATE stores SET store['a'] = 'c';
+/// #[derive(Default, Serialize, Deserialize, PostgresType)]
+/// #[pgx(subscript)]
+/// pub struct Store(HashMap<String, String>);
+///
+/// impl Subscriptable for Store {
+///     type Subscript = String;
+///     type Element = String;
+///
+///     fn fetch(&self, key: String) -> Option<String> {
+///         self.0.get(&key).cloned()
+///     }
+///
+///     fn assign(&mut self, key: String, value: Option<String>) {
+///         match value {
+///             Some(value) => self.0.insert(key, value),
+///             None => self.0.remove(&key),
+///         };
+///     }
+///
+///     fn assign_to_null() -> Self {
+///         Store::default()
+///     }
+/// }
+/// ```
+///
+/// Only a single subscript is supported, and not slices.  Subscripts are coerced to
+/// `Subscript`'s type, and a `NULL` subscript fetches `NULL`.
+pub trait Subscriptable: FromDatum + IntoDatum {
+    /// The type of the subscript, as in `value[subscript]`
+    type Subscript: FromDatum + IntoDatum;
+
+    /// The type of the elements a subscript fetches and assigns
+    type Element: FromDatum + IntoDatum;
+
+    /// Fetch the element at `subscript`, or `None` for `NULL`
+    fn fetch(&self, subscript: Self::Subscript) -> Option<Self::Element>;
+
+    /// Assign `element` to `subscript`, where `None` is `NULL`.
+    ///
+    /// It is expected that an invalid assignment will raise an `error!()` or `panic!()`
+    fn assign(&mut self, subscript: Self::Subscript, element: Option<Self::Element>);
+
+    /// Create the value a subscripted assignment to a `NULL` value assigns into.
+    ///
+    /// The default raises an `error!()`
+    fn assign_to_null() -> Self {
+        crate::error!(
+            "cannot assign to a subscript of a NULL {}",
+            type_name(Self::type_oid())
+        )
+    }
+}
+
+fn type_name(typoid: pg_sys::Oid) -> String {
+    unsafe {
+        let name = pg_sys::format_type_be(typoid);
+        let result = std::ffi::CStr::from_ptr(name)
+            .to_string_lossy()
+            .into_owned();
+        pg_sys::pfree(name as crate::void_mut_ptr);
+        result
+    }
+}
+
+/// The `SubscriptTransform` of a [`Subscriptable`] type
+#[doc(hidden)]
+pub unsafe extern "C" fn subscript_transform<T: Subscriptable>(
+    sbsref: *mut pg_sys::SubscriptingRef,
+    indirection: *mut pg_sys::List,
+    pstate: *mut pg_sys::ParseState,
+    is_slice: bool,
+    _is_assignment: bool,
+) {
+    crate::guard::guard(AssertUnwindSafe(|| {
+        let container = type_name((*sbsref).refcontainertype);
+        let indirection = PgList::<pg_sys::A_Indices>::from_pg(indirection);
+        if is_slice || indirection.len() != 1 {
+            ereport(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_DATATYPE_MISMATCH,
+                &format!("{} allows only one subscript, and not slices", container),
+                file!(),
+                line!(),
+                column!(),
+            );
+        }
+
+        // coerce the subscript to the type `fetch()` and `assign()` take
+        let indices = indirection.head().unwrap();
+        let subscript = pg_sys::transformExpr(pstate, (*indices).uidx, (*pstate).p_expr_kind);
+        let subscript_type = T::Subscript::type_oid();
+        let subscript = pg_sys::coerce_to_target_type(
+            pstate,
+            subscript,
+            pg_sys::exprType(subscript),
+            subscript_type,
+            -1,
+            pg_sys::CoercionContext_COERCION_ASSIGNMENT,
+            pg_sys::CoercionForm_COERCE_IMPLICIT_CAST,
+            -1,
+        );
+        if subscript.is_null() {
+            ereport(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_DATATYPE_MISMATCH,
+                &format!(
+                    "{} subscript must have type {}",
+                    container,
+                    type_name(subscript_type)
+                ),
+                file!(),
+                line!(),
+                column!(),
+            );
+        }
+
+        let mut upper = PgList::<pg_sys::Node>::new();
+        upper.push(subscript);
+        (*sbsref).refupperindexpr = upper.into_pg();
+        (*sbsref).reflowerindexpr = std::ptr::null_mut();
+        (*sbsref).refrestype = T::Element::type_oid();
+        (*sbsref).reftypmod = -1;
+    }))
+}
+
+/// The `SubscriptExecSetup` of a [`Subscriptable`] type
+#[doc(hidden)]
+pub unsafe extern "C" fn subscript_exec_setup<T: Subscriptable>(
+    _sbsref: *const pg_sys::SubscriptingRef,
+    _sbsrefstate: *mut pg_sys::SubscriptingRefState,
+    methods: *mut pg_sys::SubscriptExecSteps,
+) {
+    // a NULL subscript is handled by `subscript_fetch()` and `subscript_assign()`
+    (*methods).sbs_check_subscripts = None;
+    (*methods).sbs_fetch = Some(subscript_fetch::<T>);
+    (*methods).sbs_assign = Some(subscript_assign::<T>);
+    (*methods).sbs_fetch_old = Some(subscript_fetch_old::<T>);
+}
+
+/// The datum of the element at the (first and only) subscript of `sbsrefstate`, or `None` for
+/// `NULL`
+unsafe fn fetch_element<T: Subscriptable>(
+    sbsrefstate: *mut pg_sys::SubscriptingRefState,
+    container: pg_sys::Datum,
+    container_null: bool,
+) -> Option<pg_sys::Datum> {
+    if container_null || *(*sbsrefstate).upperindexnull {
+        return None;
+    }
+
+    let container = T::from_datum(container, false, T::type_oid())?;
+    let subscript =
+        T::Subscript::from_datum(*(*sbsrefstate).upperindex, false, T::Subscript::type_oid())?;
+    container.fetch(subscript).and_then(IntoDatum::into_datum)
+}
+
+unsafe extern "C" fn subscript_fetch<T: Subscriptable>(
+    _state: *mut pg_sys::ExprState,
+    op: *mut pg_sys::ExprEvalStep,
+    _econtext: *mut pg_sys::ExprContext,
+) {
+    crate::guard::guard(AssertUnwindSafe(|| {
+        let sbsrefstate = (*op).d.sbsref.state;
+        match fetch_element::<T>(sbsrefstate, *(*op).resvalue, *(*op).resnull) {
+            Some(datum) => {
+                *(*op).resvalue = datum;
+                *(*op).resnull = false;
+            }
+            None => {
+                *(*op).resvalue = 0;
+                *(*op).resnull = true;
+            }
+        }
+    }))
+}
+
+/// Fetches the element a nested assignment, such as `value[subscript].field = ..`, assigns into
+unsafe extern "C" fn subscript_fetch_old<T: Subscriptable>(
+    _state: *mut pg_sys::ExprState,
+    op: *mut pg_sys::ExprEvalStep,
+    _econtext: *mut pg_sys::ExprContext,
+) {
+    crate::guard::guard(AssertUnwindSafe(|| {
+        let sbsrefstate = (*op).d.sbsref.state;
+        match fetch_element::<T>(sbsrefstate, *(*op).resvalue, *(*op).resnull) {
+            Some(datum) => {
+                (*sbsrefstate).prevvalue = datum;
+                (*sbsrefstate).prevnull = false;
+            }
+            None => {
+                (*sbsrefstate).prevvalue = 0;
+                (*sbsrefstate).prevnull = true;
+            }
+        }
+    }))
+}
+
+unsafe extern "C" fn subscript_assign<T: Subscriptable>(
+    _state: *mut pg_sys::ExprState,
+    op: *mut pg_sys::ExprEvalStep,
+    _econtext: *mut pg_sys::ExprContext,
+) {
+    crate::guard::guard(AssertUnwindSafe(|| {
+        let sbsrefstate = (*op).d.sbsref.state;
+        if *(*sbsrefstate).upperindexnull {
+            ereport(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_NULL_VALUE_NOT_ALLOWED,
+                &format!(
+                    "{} subscript in assignment must not be null",
+                    type_name(T::type_oid())
+                ),
+                file!(),
+                line!(),
+                column!(),
+            );
+        }
+
+        let subscript =
+            T::Subscript::from_datum(*(*sbsrefstate).upperindex, false, T::Subscript::type_oid())
+                .expect("subscript was NULL");
+        let element = T::Element::from_datum(
+            (*sbsrefstate).replacevalue,
+            (*sbsrefstate).replacenull,
+            T::Element::type_oid(),
+        );
+
+        let mut container = if *(*op).resnull {
+            T::assign_to_null()
+        } else {
+            T::from_datum(*(*op).resvalue, false, T::type_oid()).expect("container was NULL")
+        };
+        container.assign(subscript, element);
+
+        match container.into_datum() {
+            Some(datum) => {
+                *(*op).resvalue = datum;
+                *(*op).resnull = false;
+            }
+            None => {
+                *(*op).resvalue = 0;
+                *(*op).resnull = true;
+            }
+        }
+    }))
+}
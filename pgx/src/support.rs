@@ -0,0 +1,154 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Planner support functions, which `#[pg_extern(support = my_support)]` attaches to a function
+//! as its `prosupport` on Postgres 12 and later
+use crate::{is_a, pg_sys, FromDatum, Internal, PgList};
+
+/// A request from the planner to the support function of a `#[pg_extern(support = ...)]`.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern(support = repeat_support)]
+/// fn repeat(word: &str, times: i32) -> impl Iterator<Item = String> + '_ {
+///     (0..times).map(move |_| word.to_string())
+/// }
+///
+/// // `repeat('a', 20)` is planned as returning 20 rows, rather than the default 1000
+/// fn repeat_support(request: SupportRequest) -> SupportResponse {
+///     match request {
+///         SupportRequest::Rows(rows) => match unsafe { const_argument::<i32>(rows.node, 1) } {
+///             Some(times) => {
+///                 rows.rows = times.max(0) as f64;
+///                 SupportResponse::Handled
+///             }
+///             None => SupportResponse::Unhandled,
+///         },
+///         _ => SupportResponse::Unhandled,
+///     }
+/// }
+/// ```
+///
+/// Each variant borrows the request node of `nodes/supportnodes.h`, whose output fields the
+/// support function fills in.
+pub enum SupportRequest<'a> {
+    /// Replace a call to the function, for instance one with constant arguments, with a simpler
+    /// expression
+    Simplify(&'a mut pg_sys::SupportRequestSimplify),
+    /// Estimate the `selectivity` of a boolean function used as a qual
+    Selectivity(&'a mut pg_sys::SupportRequestSelectivity),
+    /// Estimate the `startup` and `per_tuple` costs of a call
+    Cost(&'a mut pg_sys::SupportRequestCost),
+    /// Estimate the number of `rows` a set-returning function returns
+    Rows(&'a mut pg_sys::SupportRequestRows),
+    /// Derive index conditions from a boolean function, so it can use an index
+    IndexCondition(&'a mut pg_sys::SupportRequestIndexCondition),
+    /// A request of a type unknown to pgx
+    Other(pg_sys::NodeTag),
+}
+
+impl<'a> SupportRequest<'a> {
+    /// The arguments of the call the request is about, if it has them
+    pub fn arguments(&self) -> PgList<pg_sys::Node> {
+        unsafe {
+            match self {
+                SupportRequest::Simplify(simplify) => call_arguments(simplify.fcall as _),
+                SupportRequest::Selectivity(selectivity) => PgList::from_pg(selectivity.args),
+                SupportRequest::Cost(cost) => call_arguments(cost.node),
+                SupportRequest::Rows(rows) => call_arguments(rows.node),
+                SupportRequest::IndexCondition(index) => call_arguments(index.node),
+                SupportRequest::Other(_) => PgList::new(),
+            }
+        }
+    }
+}
+
+/// The response of a support function to a [`SupportRequest`]
+pub enum SupportResponse {
+    /// The request isn't handled, and the planner does without
+    Unhandled,
+    /// The output fields of a `Selectivity`, `Cost` or `Rows` request have been filled in
+    Handled,
+    /// The expression replacing the call of a `Simplify` request
+    Simplified(*mut pg_sys::Node),
+    /// The index conditions of an `IndexCondition` request, whose `lossy` field should be set
+    /// if they don't exactly match the call
+    IndexConditions(PgList<pg_sys::Expr>),
+}
+
+/// The value of the `index`'th argument of a function or operator call, if it's a non-`NULL`
+/// constant.
+///
+/// ## Safety
+///
+/// `node` must be `NULL` or point to a valid node
+pub unsafe fn const_argument<T: FromDatum>(node: *mut pg_sys::Node, index: usize) -> Option<T> {
+    let argument = call_arguments(node).get_ptr(index)?;
+    if !is_a(argument, pg_sys::NodeTag_T_Const) {
+        return None;
+    }
+
+    let constant = argument as *mut pg_sys::Const;
+    T::from_datum(
+        (*constant).constvalue,
+        (*constant).constisnull,
+        (*constant).consttype,
+    )
+}
+
+unsafe fn call_arguments(node: *mut pg_sys::Node) -> PgList<pg_sys::Node> {
+    if is_a(node, pg_sys::NodeTag_T_FuncExpr) {
+        PgList::from_pg((*(node as *mut pg_sys::FuncExpr)).args)
+    } else if is_a(node, pg_sys::NodeTag_T_OpExpr) {
+        PgList::from_pg((*(node as *mut pg_sys::OpExpr)).args)
+    } else {
+        PgList::new()
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn support_request(
+    request: Internal,
+    support: fn(SupportRequest) -> SupportResponse,
+) -> Internal {
+    let node = match request.unwrap() {
+        Some(datum) => datum as *mut pg_sys::Node,
+        None => return Internal::from(Some(0)),
+    };
+
+    let typed = match (*node).type_ {
+        pg_sys::NodeTag_T_SupportRequestSimplify => {
+            SupportRequest::Simplify(&mut *(node as *mut pg_sys::SupportRequestSimplify))
+        }
+        pg_sys::NodeTag_T_SupportRequestSelectivity => {
+            SupportRequest::Selectivity(&mut *(node as *mut pg_sys::SupportRequestSelectivity))
+        }
+        pg_sys::NodeTag_T_SupportRequestCost => {
+            SupportRequest::Cost(&mut *(node as *mut pg_sys::SupportRequestCost))
+        }
+        pg_sys::NodeTag_T_SupportRequestRows => {
+            SupportRequest::Rows(&mut *(node as *mut pg_sys::SupportRequestRows))
+        }
+        pg_sys::NodeTag_T_SupportRequestIndexCondition => SupportRequest::IndexCondition(
+            &mut *(node as *mut pg_sys::SupportRequestIndexCondition),
+        ),
+        other => SupportRequest::Other(other),
+    };
+
+    // the planner treats a NULL pointer as "not handled"; the function itself mustn't return NULL
+    let response = match support(typed) {
+        SupportResponse::Unhandled => 0,
+        SupportResponse::Handled => node as pg_sys::Datum,
+        SupportResponse::Simplified(expr) => expr as pg_sys::Datum,
+        SupportResponse::IndexConditions(conditions) if conditions.is_empty() => 0,
+        SupportResponse::IndexConditions(conditions) => conditions.into_pg() as pg_sys::Datum,
+    };
+    Internal::from(Some(response))
+}
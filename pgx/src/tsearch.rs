@@ -0,0 +1,423 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+/*!
+
+[Text search](https://www.postgresql.org/docs/current/textsearch-parsers.html) parser and
+dictionary template support.
+
+Parsers are created by implementing [`TextSearchParser`] for a type and decorating the
+implementation with [`#[pg_ts_parser]`](pgx_macros::pg_ts_parser). Dictionary templates are
+created by implementing [`TextSearchDictionary`] and decorating the implementation with
+[`#[pg_ts_template]`](pgx_macros::pg_ts_template).
+
+The macros generate the `internal`-typed support functions Postgres expects and emit the
+matching [`CREATE TEXT SEARCH PARSER`](https://www.postgresql.org/docs/current/sql-createtsparser.html)
+or [`CREATE TEXT SEARCH TEMPLATE`](https://www.postgresql.org/docs/current/sql-createtstemplate.html)
+statement.
+
+# Parser Example
+
+```rust
+use pgx::*;
+
+// pg_module_magic!(); // Uncomment this outside of docs!
+
+pub struct WhitespaceParser {
+    position: usize,
+}
+
+#[pg_ts_parser]
+impl TextSearchParser for WhitespaceParser {
+    const NAME: &'static str = "whitespace";
+
+    fn start(_input: &[u8]) -> Self {
+        WhitespaceParser { position: 0 }
+    }
+
+    fn get_token(&mut self, input: &[u8]) -> Option<TextSearchToken> {
+        while self.position < input.len() && input[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+        if self.position >= input.len() {
+            return None;
+        }
+        let start = self.position;
+        while self.position < input.len() && !input[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+        Some(TextSearchToken::new(1, start, self.position - start))
+    }
+
+    fn lextypes() -> Vec<TextSearchLexType> {
+        vec![TextSearchLexType::new(1, "word", "Word")]
+    }
+}
+```
+
+This creates SQL like so:
+
+```sql
+-- src/lib.rs:11
+-- tsearch::WhitespaceParser
+CREATE TEXT SEARCH PARSER whitespace (
+    START = "whitespace_parser_start", /* tsearch::WhitespaceParser::start */
+    GETTOKEN = "whitespace_parser_gettoken", /* tsearch::WhitespaceParser::get_token */
+    END = "whitespace_parser_end", /* tsearch::WhitespaceParser::end */
+    LEXTYPES = "whitespace_parser_lextypes" /* tsearch::WhitespaceParser::lextypes */
+);
+```
+
+# Dictionary Template Example
+
+```rust
+use pgx::*;
+
+// pg_module_magic!(); // Uncomment this outside of docs!
+
+pub struct LowercaseDictionary;
+
+#[pg_ts_template]
+impl TextSearchDictionary for LowercaseDictionary {
+    const NAME: &'static str = "lowercase";
+
+    fn init(_options: Vec<(String, String)>) -> Self {
+        LowercaseDictionary
+    }
+
+    fn lexize(&mut self, token: &str) -> Option<Vec<TextSearchLexeme>> {
+        Some(vec![TextSearchLexeme::new(token.to_lowercase())])
+    }
+}
+```
+*/
+use crate::{
+    ereport, pg_sys, Internal, PgBox, PgList, PgLogLevel, PgMemoryContexts, PgSqlErrorCode,
+};
+
+/// A token produced by [`TextSearchParser::get_token`].
+///
+/// `start` and `len` are byte offsets into the input passed to the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextSearchToken {
+    pub token_type: i32,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl TextSearchToken {
+    pub fn new(token_type: i32, start: usize, len: usize) -> Self {
+        TextSearchToken {
+            token_type,
+            start,
+            len,
+        }
+    }
+}
+
+/// A token type, as reported by [`TextSearchParser::lextypes`] and shown by `ts_token_type()`.
+///
+/// The `id` must be greater than zero, as Postgres uses `0` to signal the end of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextSearchLexType {
+    pub id: i32,
+    pub alias: &'static str,
+    pub description: &'static str,
+}
+
+impl TextSearchLexType {
+    pub fn new(id: i32, alias: &'static str, description: &'static str) -> Self {
+        TextSearchLexType {
+            id,
+            alias,
+            description,
+        }
+    }
+}
+
+/// A lexeme produced by [`TextSearchDictionary::lexize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextSearchLexeme {
+    pub lexeme: String,
+    /// Lexemes sharing the same `nvariant` are treated as one alternative by Postgres.
+    pub nvariant: u16,
+    /// A combination of `TSL_ADDPOS`, `TSL_PREFIX` and `TSL_FILTER`.
+    pub flags: u16,
+}
+
+impl TextSearchLexeme {
+    pub fn new(lexeme: impl Into<String>) -> Self {
+        TextSearchLexeme {
+            lexeme: lexeme.into(),
+            nvariant: 0,
+            flags: 0,
+        }
+    }
+}
+
+/// A text search parser, used with [`#[pg_ts_parser]`](pgx_macros::pg_ts_parser).
+///
+/// A value of `Self` is created per document by `start` and lives until `end`.
+pub trait TextSearchParser
+where
+    Self: Sized,
+{
+    /// The name of the parser. (eg. What you'd pass to `CREATE TEXT SEARCH CONFIGURATION ... (PARSER = name)`.)
+    const NAME: &'static str;
+
+    /// Begin parsing `input`.
+    fn start(input: &[u8]) -> Self;
+
+    /// Return the next token of `input`, or `None` once the input is exhausted.
+    ///
+    /// `input` is always the same buffer that was passed to `start`.
+    fn get_token(&mut self, input: &[u8]) -> Option<TextSearchToken>;
+
+    /// Called once the parser is done with the document.
+    fn end(self) {}
+
+    /// Describe the token types this parser can produce.
+    fn lextypes() -> Vec<TextSearchLexType>;
+
+    /// Select the fragments of `prs` to show for `ts_headline()`.
+    ///
+    /// **Optional:** If this function isn't implemented in the `#[pg_ts_parser]` block, the parser
+    /// is created without a `HEADLINE` function.
+    fn headline(
+        _prs: PgBox<pg_sys::HeadlineParsedText>,
+        _options: PgList<pg_sys::DefElem>,
+        _query: PgBox<pg_sys::TSQueryData>,
+    ) {
+        unimplemented!("Call to headline on a text search parser which does not support it.")
+    }
+}
+
+/// A text search dictionary template, used with [`#[pg_ts_template]`](pgx_macros::pg_ts_template).
+pub trait TextSearchDictionary
+where
+    Self: Sized,
+{
+    /// The name of the template. (eg. What you'd pass to `CREATE TEXT SEARCH DICTIONARY ... (TEMPLATE = name)`.)
+    const NAME: &'static str;
+
+    /// Create the dictionary from the options given to `CREATE TEXT SEARCH DICTIONARY`.
+    fn init(options: Vec<(String, String)>) -> Self;
+
+    /// Normalize `token`.
+    ///
+    /// Return `None` if the token is not recognized, letting the next dictionary have a go,
+    /// or `Some(vec![])` if it is a stop word.
+    fn lexize(&mut self, token: &str) -> Option<Vec<TextSearchLexeme>>;
+}
+
+struct TextSearchParserState<T> {
+    input: *const u8,
+    len: usize,
+    parser: Option<T>,
+}
+
+impl<T> TextSearchParserState<T> {
+    unsafe fn input(&self) -> &[u8] {
+        if self.input.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(self.input, self.len)
+        }
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn ts_parser_start<T: TextSearchParser>(input: Internal, len: i32) -> Internal {
+    let input = input.unwrap().unwrap_or(0) as *const u8;
+    let len = len.max(0) as usize;
+    let mut state = TextSearchParserState::<T> {
+        input,
+        len,
+        parser: None,
+    };
+    state.parser = Some(T::start(state.input()));
+    Internal::from(Some(
+        PgMemoryContexts::CurrentMemoryContext.leak_and_drop_on_delete(state) as pg_sys::Datum,
+    ))
+}
+
+#[doc(hidden)]
+pub unsafe fn ts_parser_gettoken<T: TextSearchParser>(
+    state: Internal,
+    token: Internal,
+    len: Internal,
+) -> Internal {
+    let state = state
+        .get_mut::<TextSearchParserState<T>>()
+        .expect("text search parser state was null");
+    let token_out = token.get_mut::<*mut std::os::raw::c_char>();
+    let len_out = len.get_mut::<i32>();
+    let input = state.input;
+    let found = match state.parser.take() {
+        Some(mut parser) => {
+            let found = parser.get_token(state.input());
+            state.parser = Some(parser);
+            found
+        }
+        None => None,
+    };
+
+    let token_type = match found {
+        Some(found) => {
+            if found.start + found.len > state.len {
+                panic!(
+                    "text search token at {}..{} is out of bounds for input of length {}",
+                    found.start,
+                    found.start + found.len,
+                    state.len
+                );
+            }
+            if let Some(token_out) = token_out {
+                *token_out = input.add(found.start) as *mut std::os::raw::c_char;
+            }
+            if let Some(len_out) = len_out {
+                *len_out = found.len as i32;
+            }
+            found.token_type
+        }
+        None => 0,
+    };
+    Internal::from(Some(token_type as pg_sys::Datum))
+}
+
+#[doc(hidden)]
+pub unsafe fn ts_parser_end<T: TextSearchParser>(state: Internal) {
+    if let Some(state) = state.get_mut::<TextSearchParserState<T>>() {
+        if let Some(parser) = state.parser.take() {
+            parser.end();
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn ts_parser_lextypes<T: TextSearchParser>() -> Internal {
+    let lextypes = T::lextypes();
+    let mut context = PgMemoryContexts::CurrentMemoryContext;
+    // the array is terminated by an entry with a `lexid` of zero
+    let descrs = context.palloc0_slice::<pg_sys::LexDescr>(lextypes.len() + 1);
+    for (descr, lextype) in descrs.iter_mut().zip(lextypes.iter()) {
+        descr.lexid = lextype.id;
+        descr.alias = context.pstrdup(lextype.alias);
+        descr.descr = context.pstrdup(lextype.description);
+    }
+    Internal::from(Some(descrs.as_mut_ptr() as pg_sys::Datum))
+}
+
+#[doc(hidden)]
+pub unsafe fn ts_parser_headline<T: TextSearchParser>(
+    prs: Internal,
+    options: Internal,
+    query: PgBox<pg_sys::TSQueryData>,
+) -> Internal {
+    let prs_datum = prs.unwrap().expect("HeadlineParsedText was null");
+    let options = PgList::from_pg(options.unwrap().unwrap_or(0) as *mut pg_sys::List);
+    let query = PgBox::from_pg(
+        pg_sys::pg_detoast_datum(query.as_ptr() as *mut pg_sys::varlena)
+            as *mut pg_sys::TSQueryData,
+    );
+    T::headline(
+        PgBox::from_pg(prs_datum as *mut pg_sys::HeadlineParsedText),
+        options,
+        query,
+    );
+    Internal::from(Some(prs_datum))
+}
+
+#[doc(hidden)]
+pub unsafe fn ts_dictionary_init<T: TextSearchDictionary>(options: Internal) -> Internal {
+    let list =
+        PgList::<pg_sys::DefElem>::from_pg(options.unwrap().unwrap_or(0) as *mut pg_sys::List);
+    let options = list
+        .iter_ptr()
+        .map(|defel| {
+            let name = std::ffi::CStr::from_ptr((*defel).defname)
+                .to_string_lossy()
+                .into_owned();
+            let value = std::ffi::CStr::from_ptr(pg_sys::defGetString(defel))
+                .to_string_lossy()
+                .into_owned();
+            (name, value)
+        })
+        .collect();
+    Internal::from(Some(
+        PgMemoryContexts::CurrentMemoryContext.leak_and_drop_on_delete(T::init(options))
+            as pg_sys::Datum,
+    ))
+}
+
+#[doc(hidden)]
+pub unsafe fn ts_dictionary_lexize<T: TextSearchDictionary>(
+    dictionary: Internal,
+    token: Internal,
+    len: Internal,
+    _substate: Internal,
+) -> Internal {
+    // Postgres declares every argument of a `LEXIZE` function as `internal`, even the length
+    let len = len.unwrap().unwrap_or(0) as i32;
+    let dictionary = dictionary
+        .get_mut::<T>()
+        .expect("text search dictionary was null");
+    let token = match token.unwrap() {
+        Some(token) if len > 0 => server_to_utf8(token as *const std::os::raw::c_char, len),
+        _ => &[],
+    };
+    let token = match std::str::from_utf8(token) {
+        Ok(token) => token,
+        Err(_) => {
+            ereport(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_CHARACTER_NOT_IN_REPERTOIRE,
+                "text search token is not valid UTF8",
+                file!(),
+                line!(),
+                column!(),
+            );
+            unreachable!()
+        }
+    };
+
+    match dictionary.lexize(token) {
+        // a NULL result means the token wasn't recognized
+        None => Internal::from(Some(0)),
+        Some(lexemes) => {
+            let mut context = PgMemoryContexts::CurrentMemoryContext;
+            // the array is terminated by an entry with a NULL `lexeme`
+            let out = context.palloc0_slice::<pg_sys::TSLexeme>(lexemes.len() + 1);
+            for (tslexeme, lexeme) in out.iter_mut().zip(lexemes.iter()) {
+                tslexeme.lexeme = utf8_to_server(context.pstrdup(&lexeme.lexeme));
+                tslexeme.nvariant = lexeme.nvariant;
+                tslexeme.flags = lexeme.flags;
+            }
+            Internal::from(Some(out.as_mut_ptr() as pg_sys::Datum))
+        }
+    }
+}
+
+/// Convert `len` bytes at `token` from the server encoding to UTF8.
+///
+/// The returned slice is either the original bytes, when no conversion was necessary, or a
+/// palloc'd copy in the `CurrentMemoryContext`.
+unsafe fn server_to_utf8<'a>(token: *const std::os::raw::c_char, len: i32) -> &'a [u8] {
+    let converted = pg_sys::pg_server_to_any(token, len, pg_sys::pg_enc_PG_UTF8 as i32);
+    if converted as *const std::os::raw::c_char == token {
+        std::slice::from_raw_parts(token as *const u8, len as usize)
+    } else {
+        std::ffi::CStr::from_ptr(converted).to_bytes()
+    }
+}
+
+/// Convert the NUL-terminated UTF8 string `lexeme` into the server encoding.
+unsafe fn utf8_to_server(lexeme: *mut std::os::raw::c_char) -> *mut std::os::raw::c_char {
+    let len = std::ffi::CStr::from_ptr(lexeme).to_bytes().len() as i32;
+    pg_sys::pg_any_to_server(lexeme, len, pg_sys::pg_enc_PG_UTF8 as i32)
+}
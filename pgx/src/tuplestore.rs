@@ -0,0 +1,125 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Materialize-mode set-returning functions, which put all of their rows into a `Tuplestorestate`
+//! in a single call
+use crate::{is_a, pg_sys, PgMemoryContexts};
+
+/// The `Tuplestorestate` a `#[pg_extern(materialize)]` function returns its rows in.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// // SELECT * FROM words('a b c');
+/// #[pg_extern(materialize)]
+/// fn words<'a>(text: &'a str) -> impl Iterator<Item = &'a str> {
+///     text.split_whitespace()
+/// }
+/// ```
+///
+/// Unlike a value-per-call set-returning function, the iterator is run to completion in one call
+/// of the function, with each row being put into the store as it's returned.
+pub struct TableStore {
+    tupstore: *mut pg_sys::Tuplestorestate,
+    tupdesc: pg_sys::TupleDesc,
+    datums: Vec<pg_sys::Datum>,
+    nulls: Vec<bool>,
+    row_context: PgMemoryContexts,
+}
+
+impl TableStore {
+    /// Start returning the rows of the set-returning function called with `fcinfo` in
+    /// materialize mode.
+    ///
+    /// ## Safety
+    ///
+    /// `fcinfo` must be the call info of a set-returning function
+    pub unsafe fn materialize(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        let rsinfo = fcinfo.as_ref().unwrap().resultinfo as *mut pg_sys::ReturnSetInfo;
+        if !is_a(rsinfo as *mut pg_sys::Node, pg_sys::NodeTag_T_ReturnSetInfo) {
+            crate::error!("set-valued function called in context that cannot accept a set");
+        }
+        let rsinfo = rsinfo.as_mut().unwrap();
+        let allowed_modes = rsinfo.allowedModes as pg_sys::SetFunctionReturnMode;
+        if allowed_modes & pg_sys::SetFunctionReturnMode_SFRM_Materialize == 0 {
+            crate::error!("materialize mode required, but it is not allowed in this context");
+        }
+
+        let per_query_context = rsinfo.econtext.as_ref().unwrap().ecxt_per_query_memory;
+        let (tupstore, tupdesc) = PgMemoryContexts::For(per_query_context).switch_to(|_| {
+            let tupdesc = result_tupdesc(fcinfo);
+            let random_access =
+                allowed_modes & pg_sys::SetFunctionReturnMode_SFRM_Materialize_Random != 0;
+            let tupstore = pg_sys::tuplestore_begin_heap(random_access, false, pg_sys::work_mem);
+            (tupstore, tupdesc)
+        });
+
+        rsinfo.returnMode = pg_sys::SetFunctionReturnMode_SFRM_Materialize;
+        rsinfo.setResult = tupstore;
+        rsinfo.setDesc = tupdesc;
+
+        let natts = tupdesc.as_ref().unwrap().natts as usize;
+        TableStore {
+            tupstore,
+            tupdesc,
+            datums: vec![0; natts],
+            nulls: vec![false; natts],
+            row_context: PgMemoryContexts::new("TableStore row"),
+        }
+    }
+
+    /// Put a row into the store, whose columns `fill` sets in the datums and nulls slices.
+    ///
+    /// Each row's datums are built in a memory context which is reset after the store copies them.
+    pub fn put_row<F: FnOnce(&mut [pg_sys::Datum], &mut [bool])>(&mut self, fill: F) {
+        self.datums.iter_mut().for_each(|datum| *datum = 0);
+        self.nulls.iter_mut().for_each(|isnull| *isnull = false);
+
+        let previous_context = self.row_context.set_as_current();
+        fill(&mut self.datums, &mut self.nulls);
+        unsafe {
+            pg_sys::tuplestore_putvalues(
+                self.tupstore,
+                self.tupdesc,
+                self.datums.as_mut_ptr(),
+                self.nulls.as_mut_ptr(),
+            );
+        }
+        previous_context.set_as_current();
+        self.row_context.reset();
+    }
+}
+
+/// The descriptor of the function's rows, a single column if it returns a scalar
+unsafe fn result_tupdesc(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::TupleDesc {
+    let mut typoid = pg_sys::InvalidOid;
+    let mut tupdesc: pg_sys::TupleDesc = std::ptr::null_mut();
+    match pg_sys::get_call_result_type(fcinfo, &mut typoid, &mut tupdesc) {
+        pg_sys::TypeFuncClass_TYPEFUNC_COMPOSITE => tupdesc,
+        pg_sys::TypeFuncClass_TYPEFUNC_SCALAR => {
+            #[cfg(any(feature = "pg10", feature = "pg11"))]
+            let tupdesc = pg_sys::CreateTemplateTupleDesc(1, false);
+            #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+            let tupdesc = pg_sys::CreateTemplateTupleDesc(1);
+
+            pg_sys::TupleDescInitEntry(
+                tupdesc,
+                1,
+                "value\0".as_ptr() as *const std::os::raw::c_char,
+                typoid,
+                -1,
+                0,
+            );
+            tupdesc
+        }
+        _ => crate::error!(
+            "function returning a set of records called in a context that cannot accept them"
+        ),
+    }
+}
@@ -27,6 +27,17 @@ pub unsafe fn set_varsize_short(ptr: *mut pg_sys::varlena, len: i32) {
     pgx_SET_VARSIZE_SHORT(ptr, len)
 }
 
+/// The size of a varlena's data, including its header, once fully detoasted and decompressed.
+///
+/// Unlike detoasting the value, this doesn't need to fetch or decompress any of its data.
+pub unsafe fn toast_raw_datum_size(datum: pg_sys::Datum) -> usize {
+    extern "C" {
+        fn pgx_toast_raw_datum_size(datum: pg_sys::Datum) -> pg_sys::Size;
+    }
+
+    pgx_toast_raw_datum_size(datum) as usize
+}
+
 /// ```c
 /// #define VARSIZE_EXTERNAL(PTR)                        (VARHDRSZ_EXTERNAL + VARTAG_SIZE(VARTAG_EXTERNAL(PTR)))
 /// ```
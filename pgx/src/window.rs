@@ -0,0 +1,192 @@
+/*
+Portions Copyright 2019-2021 ZomboDB, LLC.
+Portions Copyright 2021-2022 Technology Concepts & Design, Inc. <support@tcdi.com>
+
+All rights reserved.
+
+Use of this source code is governed by the MIT license that can be found in the LICENSE file.
+*/
+
+//! Access to the partition and frame of a `#[pg_window]` function, through Postgres'
+//! `WindowObject` API
+use crate::{pg_sys, FromDatum};
+use std::mem::MaybeUninit;
+
+/// Where a [`WindowContext`] seeks from, relative to the partition or frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowSeek {
+    /// Relative to the current row
+    Current,
+    /// Relative to the first row
+    Head,
+    /// Relative to the last row
+    Tail,
+}
+
+impl WindowSeek {
+    fn seek_type(self) -> i32 {
+        (match self {
+            WindowSeek::Current => pg_sys::WINDOW_SEEK_CURRENT,
+            WindowSeek::Head => pg_sys::WINDOW_SEEK_HEAD,
+            WindowSeek::Tail => pg_sys::WINDOW_SEEK_TAIL,
+        }) as i32
+    }
+}
+
+/// The window of a `#[pg_window]` function's current row.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// // SELECT value, previous_or(value, 0) OVER (ORDER BY value) FROM values;
+/// #[pg_window(immutable)]
+/// fn previous_or(window: WindowContext, _value: Option<i32>, default: i32) -> Option<i32> {
+///     window
+///         .arg_in_partition::<i32>(0, -1, WindowSeek::Current, false)
+///         .unwrap_or(Some(default))
+/// }
+/// ```
+///
+/// The function's other arguments are those of the current row.  Arguments are numbered from
+/// 0, not counting the `WindowContext`.
+pub struct WindowContext {
+    fcinfo: pg_sys::FunctionCallInfo,
+    winobj: pg_sys::WindowObject,
+}
+
+impl WindowContext {
+    /// Wrap the `WindowObject` of a window function call
+    ///
+    /// ## Safety
+    ///
+    /// `fcinfo` must be the call info of a function called as a window function
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        let winobj = fcinfo.as_ref().unwrap().context as pg_sys::WindowObject;
+        if !crate::is_a(
+            winobj as *mut pg_sys::Node,
+            pg_sys::NodeTag_T_WindowObjectData,
+        ) {
+            crate::error!("window function called in a non-window context");
+        }
+        WindowContext { fcinfo, winobj }
+    }
+
+    /// The position of the current row in its partition, from 0
+    pub fn current_position(&self) -> i64 {
+        unsafe { pg_sys::WinGetCurrentPosition(self.winobj) }
+    }
+
+    /// The number of rows in the current partition
+    pub fn partition_row_count(&self) -> i64 {
+        unsafe { pg_sys::WinGetPartitionRowCount(self.winobj) }
+    }
+
+    /// Allow the rows before `position` in the partition to be discarded, as they won't be
+    /// fetched again
+    pub fn set_mark_position(&self, position: i64) {
+        unsafe { pg_sys::WinSetMarkPosition(self.winobj, position) }
+    }
+
+    /// Are the rows at positions `left` and `right` peers in the window's `ORDER BY`?
+    pub fn rows_are_peers(&self, left: i64, right: i64) -> bool {
+        unsafe { pg_sys::WinRowsArePeers(self.winobj, left, right) }
+    }
+
+    /// The `argno`th argument evaluated for the current row
+    pub fn arg_current<T: FromDatum>(&self, argno: usize) -> Option<T> {
+        unsafe {
+            let mut isnull = false;
+            let datum = pg_sys::WinGetFuncArgCurrent(self.winobj, argno as i32, &mut isnull);
+            T::from_datum(datum, isnull, self.arg_type(argno))
+        }
+    }
+
+    /// The `argno`th argument evaluated for the row `offset` rows away from `seek` in the
+    /// partition, or `None` if that row is outside of the partition.  With `set_mark`, the rows
+    /// before it can be discarded.
+    pub fn arg_in_partition<T: FromDatum>(
+        &self,
+        argno: usize,
+        offset: i32,
+        seek: WindowSeek,
+        set_mark: bool,
+    ) -> Option<Option<T>> {
+        unsafe {
+            let mut isnull = false;
+            let mut isout = false;
+            let datum = pg_sys::WinGetFuncArgInPartition(
+                self.winobj,
+                argno as i32,
+                offset,
+                seek.seek_type(),
+                set_mark,
+                &mut isnull,
+                &mut isout,
+            );
+            if isout {
+                None
+            } else {
+                Some(T::from_datum(datum, isnull, self.arg_type(argno)))
+            }
+        }
+    }
+
+    /// The `argno`th argument evaluated for the row `offset` rows away from `seek` in the
+    /// frame, or `None` if that row is outside of the frame.  With `set_mark`, the rows before
+    /// it can be discarded.
+    pub fn arg_in_frame<T: FromDatum>(
+        &self,
+        argno: usize,
+        offset: i32,
+        seek: WindowSeek,
+        set_mark: bool,
+    ) -> Option<Option<T>> {
+        unsafe {
+            let mut isnull = false;
+            let mut isout = false;
+            let datum = pg_sys::WinGetFuncArgInFrame(
+                self.winobj,
+                argno as i32,
+                offset,
+                seek.seek_type(),
+                set_mark,
+                &mut isnull,
+                &mut isout,
+            );
+            if isout {
+                None
+            } else {
+                Some(T::from_datum(datum, isnull, self.arg_type(argno)))
+            }
+        }
+    }
+
+    /// State local to the current partition, which starts as `T::default()` for each partition.
+    ///
+    /// It lives in the memory context of the partition, and isn't dropped, hence `Copy`.
+    pub fn partition_local<T: Copy + Default>(&mut self) -> &mut T {
+        unsafe {
+            // the memory is zeroed when the partition starts, so `initialized` starts as false
+            let local = pg_sys::WinGetPartitionLocalMemory(
+                self.winobj,
+                std::mem::size_of::<PartitionLocal<T>>(),
+            ) as *mut PartitionLocal<T>;
+            let local = local.as_mut().unwrap();
+            if !local.initialized {
+                local.value = MaybeUninit::new(T::default());
+                local.initialized = true;
+            }
+            &mut *local.value.as_mut_ptr()
+        }
+    }
+
+    fn arg_type(&self, argno: usize) -> pg_sys::Oid {
+        unsafe { pg_sys::get_fn_expr_argtype(self.fcinfo.as_ref().unwrap().flinfo, argno as i32) }
+    }
+}
+
+#[repr(C)]
+struct PartitionLocal<T> {
+    initialized: bool,
+    value: MaybeUninit<T>,
+}